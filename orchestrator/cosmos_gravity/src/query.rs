@@ -1,4 +1,4 @@
-use std::convert::TryFrom;
+use std::{collections::HashMap, convert::TryFrom};
 
 use gravity_proto::gravity::{
     query_client::QueryClient as GravityQueryClient, Attestation, Params, QueryAttestationsRequest,
@@ -110,6 +110,20 @@ pub async fn get_all_valset_confirms(
     Ok(parsed_confirms)
 }
 
+/// Gets the valset with the given nonce along with every confirm submitted for it, None if
+/// there is no valset with this nonce. Confirms are not checked against the valset checkpoint
+pub async fn get_valset_with_confirms(
+    client: &mut GravityQueryClient<Channel>,
+    nonce: u64,
+) -> Result<Option<(Valset, Vec<ValsetConfirmResponse>)>, GravityError> {
+    let valset = match get_valset(client, nonce).await? {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+    let confirms = get_all_valset_confirms(client, nonce).await?;
+    Ok(Some((valset, confirms)))
+}
+
 pub async fn get_oldest_unsigned_transaction_batches(
     client: &mut GravityQueryClient<Channel>,
     address: Address,
@@ -300,3 +314,17 @@ pub async fn get_pending_batch_fees(
     let request = client.batch_fees(QueryBatchFeeRequest {}).await?;
     Ok(request.into_inner())
 }
+
+/// Gets the number of transfers waiting to be batched for each token across every sender,
+/// tokens with no waiting transfers are not included
+pub async fn get_pending_transfer_counts(
+    client: &mut GravityQueryClient<Channel>,
+) -> Result<HashMap<EthAddress, u64>, GravityError> {
+    let batch_fees = get_pending_batch_fees(client).await?;
+    let mut counts = HashMap::new();
+    for fee in batch_fees.batch_fees {
+        let token: EthAddress = fee.token.parse()?;
+        *counts.entry(token).or_insert(0) += fee.tx_count;
+    }
+    Ok(counts)
+}
@@ -1,4 +1,4 @@
-use std::{collections::BTreeMap, time::Duration};
+use std::{collections::BTreeMap, sync::Mutex, time::Duration};
 
 use ethereum_gravity::message_signatures::{
     encode_logic_call_confirm, encode_tx_batch_confirm, encode_valset_confirm,
@@ -15,10 +15,11 @@ use gravity_proto::{
 use gravity_utils::{
     clarity::{
         constants::ZERO_ADDRESS, Address as EthAddress, PrivateKey as EthPrivateKey, Signature,
+        Uint256,
     },
     deep_space::{
-        address::Address, coin::Coin, error::CosmosGrpcError, private_key::PrivateKey,
-        utils::bytes_to_hex_str, Contact, Msg,
+        address::Address, client::LatestBlock, coin::Coin, error::CosmosGrpcError,
+        private_key::PrivateKey, utils::bytes_to_hex_str, Contact, Msg,
     },
     types::*,
 };
@@ -28,6 +29,35 @@ use crate::utils::BadSignatureEvidence;
 pub const MEMO: &str = "Sent using Onomy Gravity Bridge Orchestrator";
 pub const TIMEOUT: Duration = Duration::from_secs(60);
 
+/// The chain-id transactions may be signed for, None accepts whatever the node reports
+static EXPECTED_CHAIN_ID: Mutex<Option<String>> = Mutex::new(None);
+
+/// Pins the Cosmos chain-id, every transaction sent by this module first checks that the
+/// node is on this chain
+pub fn set_expected_chain_id(chain_id: Option<String>) {
+    *EXPECTED_CHAIN_ID.lock().unwrap() = chain_id;
+}
+
+/// Checks that the node behind `contact` reports the pinned chain-id, if one is set. Signing
+/// for the wrong chain otherwise only shows up as confusing signature verification failures
+pub async fn check_chain_id(contact: &Contact) -> Result<(), CosmosGrpcError> {
+    let expected = match EXPECTED_CHAIN_ID.lock().unwrap().clone() {
+        Some(expected) => expected,
+        None => return Ok(()),
+    };
+    let block = match contact.get_latest_block().await? {
+        LatestBlock::Latest { block } | LatestBlock::Syncing { block } => block,
+        LatestBlock::WaitingToStart => return Err(CosmosGrpcError::ChainNotRunning),
+    };
+    let chain_id = block.header.map(|h| h.chain_id).unwrap_or_default();
+    if chain_id != expected {
+        return Err(CosmosGrpcError::BadInput(format!(
+            "Cosmos node is on chain-id {chain_id:?} but {expected:?} is configured, refusing to sign for it"
+        )));
+    }
+    Ok(())
+}
+
 /// Send a transaction updating the eth address for the sending
 /// Cosmos address. The sending Cosmos address should be a validator
 /// this can only be called once! Key rotation code is possible but
@@ -60,6 +90,7 @@ pub async fn set_gravity_delegate_addresses(
         "/gravity.v1.MsgSetOrchestratorAddress",
         msg_set_orch_address,
     );
+    check_chain_id(contact).await?;
     contact
         .send_message(
             &[msg],
@@ -105,6 +136,7 @@ pub async fn send_valset_confirms(
         let msg = Msg::new("/gravity.v1.MsgValsetConfirm", confirm);
         messages.push(msg);
     }
+    check_chain_id(contact).await?;
     let res = contact
         .send_message(
             &messages,
@@ -151,6 +183,7 @@ pub async fn send_batch_confirm(
         let msg = Msg::new("/gravity.v1.MsgConfirmBatch", confirm);
         messages.push(msg);
     }
+    check_chain_id(contact).await?;
     contact
         .send_message(
             &messages,
@@ -195,6 +228,7 @@ pub async fn send_logic_call_confirm(
         let msg = Msg::new("/gravity.v1.MsgConfirmLogicCall", confirm);
         messages.push(msg);
     }
+    check_chain_id(contact).await?;
     contact
         .send_message(
             &messages,
@@ -293,6 +327,7 @@ pub async fn send_ethereum_claims(
 
     let msgs: Vec<Msg> = ordered_msgs.into_values().collect();
 
+    check_chain_id(contact).await?;
     contact
         .send_message(&msgs, None, &[fee], Some(TIMEOUT), private_key)
         .await
@@ -353,6 +388,7 @@ pub async fn send_to_eth(
     };
 
     let msg = Msg::new("/gravity.v1.MsgSendToEth", msg_send_to_eth);
+    check_chain_id(contact).await?;
     contact
         .send_message(
             &[msg],
@@ -364,6 +400,9 @@ pub async fn send_to_eth(
         .await
 }
 
+/// Requests a batch of the pending transfers of `denom`. MsgRequestBatch has no timeout field,
+/// the Ethereum timeout height of the batch is always derived from the target_batch_timeout
+/// module parameter, so batch lifetimes are tuned per chain through governance
 pub async fn send_request_batch(
     private_key: PrivateKey,
     denom: String,
@@ -382,6 +421,7 @@ pub async fn send_request_batch(
         Some(fee) => vec![fee],
         None => vec![],
     };
+    check_chain_id(contact).await?;
     contact
         .send_message(
             &[msg],
@@ -403,22 +443,114 @@ pub async fn submit_bad_signature_evidence(
     signature: Signature,
 ) -> Result<TxResponse, CosmosGrpcError> {
     let our_address = private_key.to_address(&contact.get_prefix()).unwrap();
+    let msg = bad_signature_evidence_msg(our_address, &signed_object, &signature);
+    send_evidence(contact, &[msg], fee, private_key).await
+}
+
+/// Sends several pieces of bad signature evidence, up to `max_per_tx` in each transaction with
+/// `fee` paid per piece. A transaction fails as a whole if any of its evidence is rejected, so
+/// the evidence of a rejected transaction is resubmitted one piece at a time, keeping one invalid
+/// piece from holding back the rest. Other failures, such as an unreachable node, are returned
+/// for every piece of the transaction without retrying. Returns the result for each piece in
+/// the order given
+pub async fn submit_bad_signature_evidence_batch(
+    private_key: PrivateKey,
+    fee: Coin,
+    contact: &Contact,
+    evidence: Vec<(BadSignatureEvidence, Signature)>,
+    max_per_tx: usize,
+) -> Result<Vec<Result<TxResponse, CosmosGrpcError>>, CosmosGrpcError> {
+    let our_address = private_key.to_address(&contact.get_prefix()).unwrap();
+    let msgs: Vec<Msg> = evidence
+        .iter()
+        .map(|(signed_object, signature)| {
+            bad_signature_evidence_msg(our_address, signed_object, signature)
+        })
+        .collect();
+
+    let mut results = Vec::new();
+    for chunk in msgs.chunks(max_per_tx.max(1)) {
+        let chunk_fee = evidence_fee(&fee, chunk.len())?;
+        match send_evidence(contact, chunk, chunk_fee, private_key).await {
+            Ok(res) => results.extend(chunk.iter().map(|_| Ok(res.clone()))),
+            Err(e) if chunk.len() > 1 && is_rejected(&e) => {
+                warn!(
+                    "Evidence transaction of {} items was rejected {:?}, submitting them individually",
+                    chunk.len(),
+                    e
+                );
+                for msg in chunk {
+                    let res =
+                        send_evidence(contact, &[msg.clone()], fee.clone(), private_key).await;
+                    if let Err(e) = &res {
+                        warn!("Evidence was rejected {:?}", e);
+                    }
+                    results.push(res);
+                }
+            }
+            Err(e) => {
+                warn!(
+                    "Evidence transaction of {} items failed {:?}",
+                    chunk.len(),
+                    e
+                );
+                results.push(Err(e));
+                // the error can't be cloned, the rest of the chunk gets a description of it
+                for _ in 1..chunk.len() {
+                    results.push(Err(CosmosGrpcError::BadInput(
+                        "Evidence transaction failed, see the error of its first piece".to_string(),
+                    )));
+                }
+            }
+        }
+    }
+    Ok(results)
+}
 
-    let any = signed_object.to_any();
+/// The fee for a transaction of `count` pieces of evidence paying `fee` each
+fn evidence_fee(fee: &Coin, count: usize) -> Result<Coin, CosmosGrpcError> {
+    let amount = fee
+        .amount
+        .checked_mul(Uint256::from_usize(count))
+        .ok_or_else(|| CosmosGrpcError::BadInput("overflow of `U256::checked_mul`".to_owned()))?;
+    Ok(Coin {
+        amount,
+        denom: fee.denom.clone(),
+    })
+}
+
+/// Whether the chain rejected a transaction, as opposed to it not reaching the chain, only
+/// then can resubmitting its messages one at a time get some of them accepted
+fn is_rejected(error: &CosmosGrpcError) -> bool {
+    matches!(error, CosmosGrpcError::TransactionFailed { .. })
+}
 
+fn bad_signature_evidence_msg(
+    sender: Address,
+    signed_object: &BadSignatureEvidence,
+    signature: &Signature,
+) -> Msg {
     let msg_submit_bad_signature_evidence = MsgSubmitBadSignatureEvidence {
-        subject: Some(any),
+        subject: Some(signed_object.to_any()),
         signature: bytes_to_hex_str(&signature.to_bytes()),
-        sender: our_address.to_string(),
+        sender: sender.to_string(),
     };
-
-    let msg = Msg::new(
+    Msg::new(
         "/gravity.v1.MsgSubmitBadSignatureEvidence",
         msg_submit_bad_signature_evidence,
-    );
+    )
+}
+
+async fn send_evidence(
+    contact: &Contact,
+    msgs: &[Msg],
+    fee: Coin,
+    private_key: PrivateKey,
+) -> Result<TxResponse, CosmosGrpcError> {
+    check_chain_id(contact).await?;
     contact
         .send_message(
-            &[msg],
+            msgs,
             Some(MEMO.to_string()),
             &[fee],
             Some(TIMEOUT),
@@ -443,6 +575,7 @@ pub async fn cancel_send_to_eth(
     };
 
     let msg = Msg::new("/gravity.v1.MsgCancelSendToEth", msg_cancel_send_to_eth);
+    check_chain_id(contact).await?;
     contact
         .send_message(
             &[msg],
@@ -453,3 +586,29 @@ pub async fn cancel_send_to_eth(
         )
         .await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evidence_fee() {
+        let fee = Coin {
+            amount: Uint256::from_u64(50),
+            denom: "footoken".to_string(),
+        };
+        let total = evidence_fee(&fee, 3).unwrap();
+        assert_eq!(total.amount, Uint256::from_u64(150));
+        assert_eq!(total.denom, "footoken");
+
+        let fee = Coin {
+            amount: Uint256::max_value(),
+            denom: "footoken".to_string(),
+        };
+        assert!(evidence_fee(&fee, 1).is_ok());
+        assert!(matches!(
+            evidence_fee(&fee, 2),
+            Err(CosmosGrpcError::BadInput(_))
+        ));
+    }
+}
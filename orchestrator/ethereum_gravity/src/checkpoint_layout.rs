@@ -0,0 +1,187 @@
+//! Human readable layouts of the messages hashed into confirm checkpoints, for debugging
+//! signatures that mysteriously fail to verify. Every 32 byte word of the ABI encoding is
+//! printed along with the Solidity argument it belongs to, so the output can be diffed
+//! against the `abi.encode` the Gravity contract performs for the same item
+
+use gravity_utils::{
+    clarity::utils::{bytes_to_hex_str, get_ethereum_msg_hash},
+    types::{LogicCall, TransactionBatch, Valset},
+};
+use sha3::{Digest, Keccak256};
+
+use crate::message_signatures::{
+    encode_logic_call_confirm, encode_tx_batch_confirm, encode_valset_confirm,
+};
+
+/// The arguments of `makeCheckpoint` in Gravity.sol, true for dynamic types which are encoded
+/// as an offset to their data after the head
+const VALSET_FIELDS: &[(&str, bool)] = &[
+    ("gravityId", false),
+    ("methodName", false),
+    ("valsetNonce", false),
+    ("validators", true),
+    ("powers", true),
+    ("rewardAmount", false),
+    ("rewardToken", false),
+];
+
+/// The arguments of the batch checkpoint in `submitBatch`
+const TX_BATCH_FIELDS: &[(&str, bool)] = &[
+    ("gravityId", false),
+    ("methodName", false),
+    ("amounts", true),
+    ("destinations", true),
+    ("fees", true),
+    ("batchNonce", false),
+    ("tokenContract", false),
+    ("batchTimeout", false),
+];
+
+/// The arguments of the logic call checkpoint in `submitLogicCall`
+const LOGIC_CALL_FIELDS: &[(&str, bool)] = &[
+    ("gravityId", false),
+    ("methodName", false),
+    ("transferAmounts", true),
+    ("transferTokenContracts", true),
+    ("feeAmounts", true),
+    ("feeTokenContracts", true),
+    ("logicContractAddress", false),
+    ("payload", true),
+    ("timeOut", false),
+    ("invalidationId", false),
+    ("invalidationNonce", false),
+];
+
+/// Describes the checkpoint message of `valset`, see encode_valset_confirm
+pub fn describe_valset_confirm(gravity_id: String, valset: &Valset) -> String {
+    describe_encoding(
+        &format!("Valset {}", valset.nonce),
+        VALSET_FIELDS,
+        &encode_valset_confirm(gravity_id, valset),
+    )
+}
+
+/// Describes the checkpoint message of `batch`, see encode_tx_batch_confirm
+pub fn describe_tx_batch_confirm(gravity_id: String, batch: &TransactionBatch) -> String {
+    describe_encoding(
+        &format!("Batch {}/{}", batch.token_contract, batch.nonce),
+        TX_BATCH_FIELDS,
+        &encode_tx_batch_confirm(gravity_id, batch),
+    )
+}
+
+/// Describes the checkpoint message of `call`, see encode_logic_call_confirm
+pub fn describe_logic_call_confirm(gravity_id: String, call: LogicCall) -> String {
+    let name = format!(
+        "Logic call 0x{}/{}",
+        bytes_to_hex_str(&call.invalidation_id),
+        call.invalidation_nonce
+    );
+    describe_encoding(
+        &name,
+        LOGIC_CALL_FIELDS,
+        &encode_logic_call_confirm(gravity_id, call),
+    )
+}
+
+/// Labels every word of `message` with the field of `fields` it belongs to, the words after
+/// the head are the length and then the elements of each dynamic field
+fn describe_encoding(name: &str, fields: &[(&str, bool)], message: &[u8]) -> String {
+    let words: Vec<&[u8]> = message.chunks(32).collect();
+    // the data of a dynamic field starts at the offset in it's head word
+    let mut tails: Vec<(usize, &str)> = fields
+        .iter()
+        .zip(words.iter())
+        .filter(|((_, dynamic), _)| *dynamic)
+        .map(|((field, _), word)| (word_to_usize(word) / 32, *field))
+        .collect();
+    tails.sort_unstable();
+
+    let mut out = format!(
+        "{} checkpoint keccak256 0x{} signed as 0x{}\n",
+        name,
+        bytes_to_hex_str(Keccak256::digest(message).as_slice()),
+        bytes_to_hex_str(&get_ethereum_msg_hash(message))
+    );
+    for (i, word) in words.iter().enumerate() {
+        let label = match fields.get(i) {
+            Some((field, true)) => format!("{field} offset"),
+            Some((field, false)) => field.to_string(),
+            None => match tails.iter().rev().find(|(start, _)| *start <= i) {
+                Some((start, field)) if *start == i => format!("{field} length"),
+                Some((start, field)) => format!("{field}[{}]", i - start - 1),
+                None => "unknown".to_string(),
+            },
+        };
+        out += &format!(
+            "0x{:04x} {:<26} 0x{}\n",
+            i * 32,
+            label,
+            bytes_to_hex_str(word)
+        );
+    }
+    out
+}
+
+/// Offsets and lengths are small, only the low bytes of the word are used
+fn word_to_usize(word: &[u8]) -> usize {
+    let start = word.len().saturating_sub(8);
+    word[start..]
+        .iter()
+        .fold(0usize, |acc, b| (acc << 8) | *b as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use gravity_utils::{clarity::u256, types::ValsetMember, u64_array_bigints};
+
+    use super::*;
+
+    #[test]
+    fn test_describe_valset_confirm() {
+        let valset = Valset {
+            nonce: 7,
+            reward_amount: u256!(0),
+            reward_token: None,
+            members: vec![
+                ValsetMember {
+                    eth_address: "0xc783df8a850f42e7F7e57013759C285caa701eB6"
+                        .parse()
+                        .unwrap(),
+                    power: 3333,
+                },
+                ValsetMember {
+                    eth_address: "0xeAD9C93b79Ae7C1591b1FB5323BD777E86e150d4"
+                        .parse()
+                        .unwrap(),
+                    power: 3333,
+                },
+            ],
+        };
+        let description = describe_valset_confirm("foo".to_string(), &valset);
+        let labels: Vec<&str> = description
+            .lines()
+            .skip(1)
+            .map(|l| l.split_whitespace().nth(1).unwrap())
+            .collect();
+        assert_eq!(
+            labels,
+            vec![
+                "gravityId",
+                "methodName",
+                "valsetNonce",
+                "validators",
+                "powers",
+                "rewardAmount",
+                "rewardToken",
+                "validators",
+                "validators[0]",
+                "validators[1]",
+                "powers",
+                "powers[0]",
+                "powers[1]",
+            ]
+        );
+        assert!(description.contains("0x00e0 validators length"));
+    }
+}
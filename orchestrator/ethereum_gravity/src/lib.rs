@@ -3,9 +3,12 @@
 #[macro_use]
 extern crate log;
 
+pub mod checkpoint_layout;
 pub mod deploy_erc20;
 pub mod logic_call;
 pub mod message_signatures;
+pub mod nonce_manager;
+pub mod relay_log;
 pub mod send_to_cosmos;
 pub mod submit_batch;
 mod test_cases;
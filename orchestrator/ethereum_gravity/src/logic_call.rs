@@ -15,6 +15,8 @@ use gravity_utils::{
 
 use crate::{
     message_signatures::encode_logic_call_confirm_hashed,
+    nonce_manager::NonceManager,
+    relay_log::{self, RelayItem, RelayStatus},
     utils::{encode_valset_struct, get_logic_call_nonce, GasCost},
 };
 
@@ -30,6 +32,7 @@ pub async fn send_eth_logic_call(
     gravity_contract_address: EthAddress,
     gravity_id: String,
     our_eth_key: EthPrivateKey,
+    nonce_manager: &NonceManager,
 ) -> Result<(), GravityError> {
     let new_call_nonce = call.invalidation_nonce;
     let eth_address = our_eth_key.to_address();
@@ -64,19 +67,40 @@ pub async fn send_eth_logic_call(
 
     let payload = encode_logic_call_payload(current_valset, &call, confirms, gravity_id)?;
 
-    let tx = web3
+    let (tx, in_flight) = nonce_manager
         .send_transaction(
+            web3,
             gravity_contract_address,
             payload,
             u256!(0),
-            eth_address,
             &our_eth_key,
             vec![],
+            RelayItem {
+                kind: "logic call",
+                token: None,
+                nonce: new_call_nonce,
+            },
         )
         .await?;
     info!("Sent batch update with txid {:#066x}", tx);
 
-    web3.wait_for_transaction(tx, timeout, None).await?;
+    let res = web3.wait_for_transaction(tx, timeout, None).await;
+    // frees the in flight slot now that the transaction is mined or given up on
+    drop(in_flight);
+    let res = match res {
+        Ok(res) => res,
+        Err(e) => {
+            relay_log::update_status(tx, RelayStatus::Failed(format!("{e:?}")));
+            return Err(e.into());
+        }
+    };
+    relay_log::update_status(
+        tx,
+        RelayStatus::Confirmed {
+            gas: res.gas,
+            gas_price: res.gas_price,
+        },
+    );
 
     let last_nonce = get_logic_call_nonce(
         gravity_contract_address,
@@ -0,0 +1,466 @@
+//! Assigns nonces to the Ethereum transactions sent by the relayer. Asking the node for the
+//! account nonce on every submission races when several relays are submitted concurrently or
+//! a previous relay is still pending, so the next nonce can instead be tracked locally.
+//! The locally tracked nonce can optionally be persisted so that a restarted relayer does not
+//! reuse the nonces of transactions it sent before the restart that are still in flight.
+//! The number of relay transactions in flight at once can also be capped, a submission past
+//! the cap waits until an earlier relay transaction is mined or given up on
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use gravity_utils::{
+    clarity::{u256, Address as EthAddress, PrivateKey as EthPrivateKey, Uint256},
+    types::NonceManagement,
+    u64_array_bigints,
+    web30::{client::Web3, jsonrpc::error::Web3Error, types::SendTxOption},
+};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::relay_log::{self, RelayItem};
+
+/// A restored nonce further than this ahead of the node is assumed to belong to transactions
+/// that were dropped while the relayer was down and is discarded
+const MAX_RESTORED_NONCE_GAP: u64 = 64;
+
+pub struct NonceManager {
+    mode: NonceManagement,
+    /// The next nonce to assign, None if it must be read from the node
+    next_nonce: Mutex<Option<Uint256>>,
+    /// Where the next nonce is persisted in local mode, if anywhere
+    state_file: Option<PathBuf>,
+    /// Serializes writes of the state file so the last write always holds the latest nonce
+    state_file_lock: Mutex<()>,
+    /// Slots for relay transactions in flight, None if they are not limited
+    in_flight: Option<Arc<Semaphore>>,
+}
+
+impl NonceManager {
+    pub fn new(mode: NonceManagement) -> Self {
+        NonceManager {
+            mode,
+            next_nonce: Mutex::new(None),
+            state_file: None,
+            state_file_lock: Mutex::new(()),
+            in_flight: None,
+        }
+    }
+
+    /// Allows at most `max` relay transactions in flight at once, zero does not limit them
+    pub fn with_max_in_flight(mut self, max: usize) -> Self {
+        self.in_flight = if max == 0 {
+            None
+        } else {
+            Some(Arc::new(Semaphore::new(max)))
+        };
+        self
+    }
+
+    /// Persists the locally tracked nonce to `path` so it can be restored with `restore_state`
+    pub fn with_state_file(mut self, path: PathBuf) -> Self {
+        self.state_file = Some(path);
+        self
+    }
+
+    /// Restores the nonce persisted by a previous run, reconciling it against the node. A
+    /// persisted nonce behind the node or implausibly far ahead of it is discarded, otherwise
+    /// it is kept so that transactions still in flight from before the restart are not replaced
+    pub async fn restore_state(&self, web3: &Web3, our_eth_address: EthAddress) {
+        let path = match (&self.state_file, self.mode) {
+            (Some(path), NonceManagement::Local) => path,
+            _ => return,
+        };
+        let stored = match read_state(path) {
+            Ok(Some((address, nonce))) if address == our_eth_address => nonce,
+            Ok(Some((address, _))) => {
+                warn!(
+                    "Ignoring nonce state in {} for {}, we are {}",
+                    path.display(),
+                    address,
+                    our_eth_address
+                );
+                return;
+            }
+            Ok(None) => return,
+            Err(e) => {
+                warn!(
+                    "Could not read nonce state {} {:?}, using the node nonce",
+                    path.display(),
+                    e
+                );
+                return;
+            }
+        };
+        let node_nonce = match web3.eth_get_transaction_count(our_eth_address).await {
+            Ok(n) => n,
+            Err(e) => {
+                warn!(
+                    "Could not get our nonce to reconcile the nonce state {:?}, using the node nonce",
+                    e
+                );
+                return;
+            }
+        };
+        if let Some(nonce) = reconcile_restored_nonce(stored, node_nonce) {
+            *self.next_nonce.lock().unwrap() = Some(nonce);
+        }
+    }
+
+    /// Sends a transaction from `our_eth_key`, assigning its nonce according to the mode. In
+    /// local mode the nonce is reserved before sending so concurrent callers never share one.
+    /// The sent transaction is recorded as pending in the relay log under `relay`. A submission
+    /// waits for a free in flight slot, the returned slot must be held until the transaction is
+    /// mined or given up on and frees the slot when dropped
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_transaction(
+        &self,
+        web3: &Web3,
+        to: EthAddress,
+        payload: Vec<u8>,
+        value: Uint256,
+        our_eth_key: &EthPrivateKey,
+        options: Vec<SendTxOption>,
+        relay: RelayItem,
+    ) -> Result<(Uint256, Option<OwnedSemaphorePermit>), Web3Error> {
+        let our_eth_address = our_eth_key.to_address();
+        let permit = self.acquire_in_flight_slot(&relay).await;
+        if self.mode == NonceManagement::Node {
+            let tx_hash = web3
+                .send_transaction(to, payload, value, our_eth_address, our_eth_key, options)
+                .await?;
+            relay_log::record_sent(relay, tx_hash, None);
+            return Ok((tx_hash, permit));
+        }
+
+        // the node is only asked until there is a local nonce, which is never cleared
+        let node_nonce = if self.next_nonce.lock().unwrap().is_none() {
+            Some(web3.eth_get_transaction_count(our_eth_address).await?)
+        } else {
+            None
+        };
+        let nonce = {
+            let mut next_nonce = self.next_nonce.lock().unwrap();
+            let nonce = match node_nonce {
+                Some(node_nonce) => resynced_nonce(*next_nonce, node_nonce),
+                None => next_nonce.unwrap(),
+            };
+            *next_nonce = Some(nonce.checked_add(u256!(1)).unwrap());
+            nonce
+        };
+        // written after the nonce lock is dropped so that reserving a nonce never waits on io
+        self.save_state(our_eth_address);
+
+        let mut options = options;
+        options.push(SendTxOption::Nonce(nonce));
+        let res = web3
+            .send_transaction(to, payload, value, our_eth_address, our_eth_key, options)
+            .await;
+        match res {
+            Ok(tx_hash) => {
+                relay_log::record_sent(relay, tx_hash, Some(nonce));
+                Ok((tx_hash, permit))
+            }
+            Err(e) if is_nonce_error(&e) => {
+                warn!(
+                    "Relay transaction with nonce {} failed {:?}, resyncing nonce from the node",
+                    nonce, e
+                );
+                self.resync(web3, our_eth_address).await;
+                Err(e)
+            }
+            Err(e) => {
+                warn!("Relay transaction with nonce {} failed {:?}", nonce, e);
+                self.release_nonce(nonce, our_eth_address);
+                Err(e)
+            }
+        }
+    }
+
+    /// Waits for an in flight slot for `relay`, None if in flight transactions are not limited
+    async fn acquire_in_flight_slot(&self, relay: &RelayItem) -> Option<OwnedSemaphorePermit> {
+        let in_flight = self.in_flight.as_ref()?;
+        if in_flight.available_permits() == 0 {
+            info!(
+                "The maximum number of relay transactions are in flight, {} {} is waiting for one to be mined",
+                relay.kind, relay.nonce
+            );
+        }
+        // the semaphore is never closed
+        Some(in_flight.clone().acquire_owned().await.unwrap())
+    }
+
+    /// Moves the locally tracked nonce up to the node's after a nonce error. It is never moved
+    /// back, while our transactions are pending the local nonce is ahead of the node's
+    async fn resync(&self, web3: &Web3, our_eth_address: EthAddress) {
+        let node_nonce = match web3.eth_get_transaction_count(our_eth_address).await {
+            Ok(n) => n,
+            Err(e) => {
+                warn!("Could not get our nonce to resync {:?}", e);
+                return;
+            }
+        };
+        {
+            let mut next_nonce = self.next_nonce.lock().unwrap();
+            *next_nonce = Some(resynced_nonce(*next_nonce, node_nonce));
+        }
+        self.save_state(our_eth_address);
+    }
+
+    /// Hands back `nonce` after its transaction failed to send for a reason other than its
+    /// nonce, so that it doesn't leave a gap. This is only possible while no later nonce has
+    /// been reserved
+    fn release_nonce(&self, nonce: Uint256, our_eth_address: EthAddress) {
+        {
+            let mut next_nonce = self.next_nonce.lock().unwrap();
+            if *next_nonce == nonce.checked_add(u256!(1)) {
+                *next_nonce = Some(nonce);
+            }
+        }
+        self.save_state(our_eth_address);
+    }
+
+    /// Writes the current next nonce to the state file if there is one. The state only saves a
+    /// restarted relayer from some nonce errors, so failures are logged rather than returned
+    fn save_state(&self, our_eth_address: EthAddress) {
+        let _write = self.state_file_lock.lock().unwrap();
+        let next_nonce = *self.next_nonce.lock().unwrap();
+        if let (Some(path), Some(nonce)) = (&self.state_file, next_nonce) {
+            // written to a temporary file first so a crash never leaves a partial state
+            let tmp = path.with_extension("tmp");
+            let res = fs::write(&tmp, format!("{our_eth_address} {nonce}\n"))
+                .and_then(|_| fs::rename(&tmp, path));
+            if let Err(e) = res {
+                warn!("Failed to save nonce state {} {:?}", path.display(), e);
+            }
+        }
+    }
+}
+
+/// The next nonce given the `local` nonce, if there is one, and the `node_nonce`, the larger of
+/// the two
+fn resynced_nonce(local: Option<Uint256>, node_nonce: Uint256) -> Uint256 {
+    match local {
+        Some(n) if n >= node_nonce => n,
+        Some(n) => {
+            info!(
+                "Ethereum node nonce {} is ahead of our local nonce {}, resyncing",
+                node_nonce, n
+            );
+            node_nonce
+        }
+        None => node_nonce,
+    }
+}
+
+/// Whether the node refused a transaction because of its nonce, such as "nonce too low" or a
+/// transaction with the same nonce already being pending
+fn is_nonce_error(error: &Web3Error) -> bool {
+    let error = format!("{error:?}").to_lowercase();
+    error.contains("nonce") || error.contains("already known") || error.contains("replacement")
+}
+
+/// The next nonce to use after a restart given the `stored` nonce and the `node_nonce`, None if
+/// the node nonce should be used. A stored nonce behind the node or more than
+/// MAX_RESTORED_NONCE_GAP ahead of it is discarded
+fn reconcile_restored_nonce(stored: Uint256, node_nonce: Uint256) -> Option<Uint256> {
+    let max_nonce = node_nonce
+        .checked_add(Uint256::from_u64(MAX_RESTORED_NONCE_GAP))
+        .unwrap();
+    if stored <= node_nonce {
+        info!(
+            "Ethereum node nonce {} has caught up with our stored nonce {}",
+            node_nonce, stored
+        );
+        None
+    } else if stored > max_nonce {
+        warn!(
+            "Stored nonce {} is too far ahead of the node nonce {}, discarding it",
+            stored, node_nonce
+        );
+        None
+    } else {
+        info!(
+            "Restored nonce {}, {} relay transactions may still be in flight",
+            stored,
+            stored.checked_sub(node_nonce).unwrap()
+        );
+        Some(stored)
+    }
+}
+
+/// Reads the address and next nonce from a state file, None if there is no state
+fn read_state(path: &Path) -> io::Result<Option<(EthAddress, Uint256)>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "malformed nonce state");
+    let mut parts = contents.split_whitespace();
+    let address = parts
+        .next()
+        .and_then(|a| a.parse().ok())
+        .ok_or_else(invalid)?;
+    let nonce = parts
+        .next()
+        .and_then(|n| Uint256::from_dec_or_hex_str_restricted(n).ok())
+        .ok_or_else(invalid)?;
+    Ok(Some((address, nonce)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::time::timeout;
+
+    use super::*;
+
+    fn relay(nonce: u64) -> RelayItem {
+        RelayItem {
+            kind: "batch",
+            token: None,
+            nonce,
+        }
+    }
+
+    #[test]
+    fn test_state_round_trip() {
+        let path = std::env::temp_dir().join(format!("gbt-nonce-state-{}", std::process::id()));
+        let address: EthAddress = "0x6Bd41fCdF129297c3524395d669c0865b3CA85B2"
+            .parse()
+            .unwrap();
+        let manager = NonceManager::new(NonceManagement::Local).with_state_file(path.clone());
+        assert!(read_state(&path).unwrap().is_none());
+
+        // nothing is written when there is no nonce to save
+        manager.save_state(address);
+        assert!(read_state(&path).unwrap().is_none());
+
+        *manager.next_nonce.lock().unwrap() = Some(Uint256::from_u64(42));
+        manager.save_state(address);
+        assert_eq!(
+            read_state(&path).unwrap(),
+            Some((address, Uint256::from_u64(42)))
+        );
+
+        // a released nonce is saved as the next one
+        *manager.next_nonce.lock().unwrap() = Some(Uint256::from_u64(43));
+        manager.release_nonce(Uint256::from_u64(42), address);
+        assert_eq!(
+            read_state(&path).unwrap(),
+            Some((address, Uint256::from_u64(42)))
+        );
+
+        fs::write(&path, "not a nonce state").unwrap();
+        assert!(read_state(&path).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_release_nonce() {
+        let address: EthAddress = "0x6Bd41fCdF129297c3524395d669c0865b3CA85B2"
+            .parse()
+            .unwrap();
+        let manager = NonceManager::new(NonceManagement::Local);
+        *manager.next_nonce.lock().unwrap() = Some(Uint256::from_u64(11));
+        manager.release_nonce(Uint256::from_u64(10), address);
+        assert_eq!(
+            *manager.next_nonce.lock().unwrap(),
+            Some(Uint256::from_u64(10))
+        );
+
+        // a later nonce has been reserved, releasing an earlier one would reuse it
+        *manager.next_nonce.lock().unwrap() = Some(Uint256::from_u64(12));
+        manager.release_nonce(Uint256::from_u64(10), address);
+        assert_eq!(
+            *manager.next_nonce.lock().unwrap(),
+            Some(Uint256::from_u64(12))
+        );
+    }
+
+    #[test]
+    fn test_resynced_nonce() {
+        let node_nonce = Uint256::from_u64(100);
+        assert_eq!(resynced_nonce(None, node_nonce), node_nonce);
+        // the node is ahead, our transactions were mined or replaced
+        assert_eq!(
+            resynced_nonce(Some(Uint256::from_u64(90)), node_nonce),
+            node_nonce
+        );
+        // our pending transactions keep the local nonce ahead
+        assert_eq!(
+            resynced_nonce(Some(Uint256::from_u64(105)), node_nonce),
+            Uint256::from_u64(105)
+        );
+    }
+
+    #[test]
+    fn test_is_nonce_error() {
+        assert!(is_nonce_error(&Web3Error::BadResponse(
+            "nonce too low".to_string()
+        )));
+        assert!(is_nonce_error(&Web3Error::BadResponse(
+            "already known".to_string()
+        )));
+        assert!(is_nonce_error(&Web3Error::BadResponse(
+            "replacement transaction underpriced".to_string()
+        )));
+        assert!(!is_nonce_error(&Web3Error::BadResponse(
+            "insufficient funds for gas * price + value".to_string()
+        )));
+    }
+
+    #[test]
+    fn test_reconcile_restored_nonce() {
+        let node_nonce = Uint256::from_u64(100);
+        // the node has seen every transaction we sent
+        assert_eq!(
+            reconcile_restored_nonce(Uint256::from_u64(90), node_nonce),
+            None
+        );
+        assert_eq!(reconcile_restored_nonce(node_nonce, node_nonce), None);
+        // some may still be in flight
+        assert_eq!(
+            reconcile_restored_nonce(Uint256::from_u64(101), node_nonce),
+            Some(Uint256::from_u64(101))
+        );
+        let max = 100 + MAX_RESTORED_NONCE_GAP;
+        assert_eq!(
+            reconcile_restored_nonce(Uint256::from_u64(max), node_nonce),
+            Some(Uint256::from_u64(max))
+        );
+        // too far ahead to still be in flight
+        assert_eq!(
+            reconcile_restored_nonce(Uint256::from_u64(max + 1), node_nonce),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_slot_waits_for_release() {
+        let manager = NonceManager::new(NonceManagement::Local).with_max_in_flight(4);
+        let mut slots = Vec::new();
+        for i in 0..4 {
+            slots.push(manager.acquire_in_flight_slot(&relay(i)).await.unwrap());
+        }
+
+        // a fifth submission waits while four are in flight
+        let fifth = manager.acquire_in_flight_slot(&relay(4));
+        tokio::pin!(fifth);
+        assert!(timeout(Duration::from_millis(50), &mut fifth)
+            .await
+            .is_err());
+
+        // and proceeds once one of them is mined or given up on
+        slots.pop();
+        let slot = timeout(Duration::from_secs(1), &mut fifth).await;
+        assert!(slot.unwrap().is_some());
+
+        let unlimited = NonceManager::new(NonceManagement::Local).with_max_in_flight(0);
+        assert!(unlimited.acquire_in_flight_slot(&relay(0)).await.is_none());
+    }
+}
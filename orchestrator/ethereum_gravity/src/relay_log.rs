@@ -0,0 +1,183 @@
+//! A bounded log of the relay transactions most recently sent to Ethereum and their status,
+//! so that operators responding to an incident can see what the relayer has in flight without
+//! searching the logs. The log is shared by the whole process and served by the admin socket
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::Mutex,
+    time::{Duration, SystemTime},
+};
+
+use gravity_utils::{
+    clarity::{Address as EthAddress, Uint256},
+    num_conversion::print_gwei,
+};
+
+/// How many relay transactions are remembered, the oldest are dropped first
+pub const RELAY_LOG_CAPACITY: usize = 100;
+
+static RELAY_LOG: Mutex<RelayLog> = Mutex::new(RelayLog::new());
+
+/// The Gravity item a relay transaction submits
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelayItem {
+    /// "valset", "batch" or "logic call"
+    pub kind: &'static str,
+    /// the token of a batch, None for other items
+    pub token: Option<EthAddress>,
+    /// the nonce of the valset, batch or logic call
+    pub nonce: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RelayStatus {
+    /// sent and not yet seen in a block
+    Pending,
+    /// included in a block, with the gas limit and gas price of the transaction
+    Confirmed { gas: Uint256, gas_price: Uint256 },
+    /// not included within the timeout or rejected
+    Failed(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelayRecord {
+    pub item: RelayItem,
+    pub tx_hash: Uint256,
+    /// the Ethereum nonce of the transaction, only known with local nonce management
+    pub eth_nonce: Option<Uint256>,
+    pub sent_at: SystemTime,
+    pub status: RelayStatus,
+}
+
+impl fmt::Display for RelayRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let age = self.sent_at.elapsed().unwrap_or(Duration::ZERO).as_secs();
+        write!(f, "{}s ago {} ", age, self.item.kind)?;
+        if let Some(token) = self.item.token {
+            write!(f, "{}/", token)?;
+        }
+        write!(f, "{} tx {:#066x}", self.item.nonce, self.tx_hash)?;
+        match self.eth_nonce {
+            Some(nonce) => write!(f, " eth nonce {}", nonce)?,
+            None => write!(f, " eth nonce unknown")?,
+        }
+        match &self.status {
+            RelayStatus::Pending => write!(f, " pending"),
+            RelayStatus::Confirmed { gas, gas_price } => write!(
+                f,
+                " confirmed gas limit {} @ {} gwei",
+                gas,
+                print_gwei(*gas_price)
+            ),
+            RelayStatus::Failed(e) => write!(f, " failed {}", e),
+        }
+    }
+}
+
+/// The relay records, oldest first, holding at most RELAY_LOG_CAPACITY
+struct RelayLog {
+    records: VecDeque<RelayRecord>,
+}
+
+impl RelayLog {
+    const fn new() -> Self {
+        RelayLog {
+            records: VecDeque::new(),
+        }
+    }
+
+    fn push(&mut self, record: RelayRecord) {
+        if self.records.len() >= RELAY_LOG_CAPACITY {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+
+    fn update_status(&mut self, tx_hash: Uint256, status: RelayStatus) {
+        if let Some(record) = self.records.iter_mut().rev().find(|r| r.tx_hash == tx_hash) {
+            record.status = status;
+        }
+    }
+}
+
+/// Records a relay transaction that was just sent as pending
+pub fn record_sent(item: RelayItem, tx_hash: Uint256, eth_nonce: Option<Uint256>) {
+    RELAY_LOG.lock().unwrap().push(RelayRecord {
+        item,
+        tx_hash,
+        eth_nonce,
+        sent_at: SystemTime::now(),
+        status: RelayStatus::Pending,
+    });
+}
+
+/// Updates the status of the relay transaction `tx_hash` if it is still in the log
+pub fn update_status(tx_hash: Uint256, status: RelayStatus) {
+    RELAY_LOG.lock().unwrap().update_status(tx_hash, status);
+}
+
+/// The remembered relay transactions, oldest first
+pub fn recent_relays() -> Vec<RelayRecord> {
+    RELAY_LOG.lock().unwrap().records.iter().cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(tx_hash: u64) -> RelayRecord {
+        RelayRecord {
+            item: RelayItem {
+                kind: "valset",
+                token: None,
+                nonce: tx_hash,
+            },
+            tx_hash: Uint256::from_u64(tx_hash),
+            eth_nonce: None,
+            sent_at: SystemTime::now(),
+            status: RelayStatus::Pending,
+        }
+    }
+
+    #[test]
+    fn test_relay_log_capacity() {
+        let mut log = RelayLog::new();
+        for i in 0..RELAY_LOG_CAPACITY as u64 + 5 {
+            log.push(record(i));
+        }
+        // the oldest records are dropped first
+        assert_eq!(log.records.len(), RELAY_LOG_CAPACITY);
+        assert_eq!(log.records.front().unwrap().tx_hash, Uint256::from_u64(5));
+        assert_eq!(
+            log.records.back().unwrap().tx_hash,
+            Uint256::from_u64(RELAY_LOG_CAPACITY as u64 + 4)
+        );
+    }
+
+    #[test]
+    fn test_relay_log_update_status() {
+        let mut log = RelayLog::new();
+        log.push(record(1));
+        log.push(record(2));
+        let confirmed = RelayStatus::Confirmed {
+            gas: Uint256::from_u64(200_000),
+            gas_price: Uint256::from_u64(30_000_000_000),
+        };
+        log.update_status(Uint256::from_u64(2), confirmed.clone());
+        assert_eq!(log.records[0].status, RelayStatus::Pending);
+        assert_eq!(log.records[1].status, confirmed);
+        // a transaction that was dropped from the log is ignored
+        log.update_status(
+            Uint256::from_u64(3),
+            RelayStatus::Failed("timeout".to_string()),
+        );
+        assert!(log
+            .records
+            .iter()
+            .all(|r| r.tx_hash != Uint256::from_u64(3)));
+        assert!(log.records[1]
+            .to_string()
+            .ends_with("confirmed gas limit 200000 @ 30 gwei"));
+    }
+}
@@ -15,6 +15,8 @@ use gravity_utils::{
 
 use crate::{
     message_signatures::encode_tx_batch_confirm_hashed,
+    nonce_manager::NonceManager,
+    relay_log::{self, RelayItem, RelayStatus},
     utils::{encode_valset_struct, get_tx_batch_nonce, GasCost},
 };
 
@@ -30,6 +32,7 @@ pub async fn send_eth_transaction_batch(
     gravity_contract_address: EthAddress,
     gravity_id: String,
     our_eth_key: EthPrivateKey,
+    nonce_manager: &NonceManager,
 ) -> Result<(), GravityError> {
     let new_batch_nonce = batch.nonce;
     let eth_address = our_eth_key.to_address();
@@ -63,19 +66,40 @@ pub async fn send_eth_transaction_batch(
 
     let payload = encode_batch_payload(current_valset, &batch, confirms, gravity_id)?;
 
-    let tx = web3
+    let (tx, in_flight) = nonce_manager
         .send_transaction(
+            web3,
             gravity_contract_address,
             payload,
             u256!(0),
-            eth_address,
             &our_eth_key,
             vec![SendTxOption::GasPriceMultiplier(1.10f32)],
+            RelayItem {
+                kind: "batch",
+                token: Some(batch.token_contract),
+                nonce: new_batch_nonce,
+            },
         )
         .await?;
     info!("Sent batch update with txid {:#066x}", tx);
 
-    web3.wait_for_transaction(tx, timeout, None).await?;
+    let res = web3.wait_for_transaction(tx, timeout, None).await;
+    // frees the in flight slot now that the transaction is mined or given up on
+    drop(in_flight);
+    let res = match res {
+        Ok(res) => res,
+        Err(e) => {
+            relay_log::update_status(tx, RelayStatus::Failed(format!("{e:?}")));
+            return Err(e.into());
+        }
+    };
+    relay_log::update_status(
+        tx,
+        RelayStatus::Confirmed {
+            gas: res.gas,
+            gas_price: res.gas_price,
+        },
+    );
 
     let last_nonce = get_tx_batch_nonce(
         gravity_contract_address,
@@ -95,6 +119,29 @@ pub async fn send_eth_transaction_batch(
     Ok(())
 }
 
+/// Simulates submitting this batch with an `eth_call` against the current contract state,
+/// returning the error, which includes the revert reason, if the submission would fail
+pub async fn simulate_eth_transaction_batch(
+    current_valset: &Valset,
+    batch: &TransactionBatch,
+    confirms: &[BatchConfirmResponse],
+    web3: &Web3,
+    gravity_contract_address: EthAddress,
+    gravity_id: String,
+    our_eth_address: EthAddress,
+) -> Result<(), GravityError> {
+    let payload = encode_batch_payload(current_valset, batch, confirms, gravity_id)?;
+    web3.simulate_transaction(
+        gravity_contract_address,
+        u256!(0),
+        payload,
+        our_eth_address,
+        None,
+    )
+    .await?;
+    Ok(())
+}
+
 /// Returns the cost in Eth of sending this batch
 pub async fn estimate_tx_batch_cost(
     current_valset: &Valset,
@@ -4,10 +4,14 @@ use gravity_utils::{
         constants::ZERO_ADDRESS,
         u256, Address as EthAddress, Uint256,
     },
-    types::*,
+    error::GravityError,
+    types::{event_signatures::VALSET_UPDATED_EVENT_SIG, *},
     u64_array_bigints,
     web30::{client::Web3, jsonrpc::error::Web3Error},
 };
+use sha3::{Digest, Keccak256};
+
+use crate::message_signatures::encode_valset_confirm;
 
 /// Gets the latest validator set nonce
 pub async fn get_valset_nonce(
@@ -134,6 +138,101 @@ pub async fn get_gravity_id(
     }
 }
 
+/// Gets if the Gravity contract is paused, this reads `paused()` so deployments that can not
+/// be paused return an error
+pub async fn get_gravity_paused(
+    contract_address: EthAddress,
+    caller_address: EthAddress,
+    web3: &Web3,
+) -> Result<bool, Web3Error> {
+    let payload = encode_call("paused()", &[]).unwrap();
+    let val = web3
+        .simulate_transaction(contract_address, u256!(0), payload, caller_address, None)
+        .await?;
+    // a bool is returned as a single 32 byte word
+    if val.len() != 32 {
+        return Err(Web3Error::BadResponse(format!(
+            "Invalid paused() response {:?}",
+            val
+        )));
+    }
+    Ok(val.iter().any(|b| *b != 0))
+}
+
+/// Gets the checkpoint hash of the validator set currently in the contract
+pub async fn get_valset_checkpoint(
+    contract_address: EthAddress,
+    caller_address: EthAddress,
+    web3: &Web3,
+) -> Result<Vec<u8>, Web3Error> {
+    let payload = encode_call("state_lastValsetCheckpoint()", &[]).unwrap();
+    web3.simulate_transaction(contract_address, u256!(0), payload, caller_address, None)
+        .await
+}
+
+/// Gets the validator set currently active in the Gravity contract. The contract only stores
+/// the nonce and checkpoint of its valset, so the members are taken from the ValsetUpdatedEvent
+/// with that nonce, searching back from the latest block, and verified against the checkpoint
+pub async fn get_ethereum_valset(
+    contract_address: EthAddress,
+    caller_address: EthAddress,
+    web3: &Web3,
+) -> Result<Valset, GravityError> {
+    const BLOCKS_TO_SEARCH: Uint256 = u256!(5_000);
+    let nonce = get_valset_nonce(contract_address, caller_address, web3).await?;
+    let checkpoint = get_valset_checkpoint(contract_address, caller_address, web3).await?;
+    let gravity_id = get_gravity_id(contract_address, caller_address, web3).await?;
+
+    let mut current_block = web3.eth_block_number().await?;
+    while !current_block.is_zero() {
+        let end_search = current_block
+            .checked_sub(BLOCKS_TO_SEARCH)
+            .unwrap_or_else(|| u256!(0));
+        let events = web3
+            .check_for_events(
+                end_search,
+                Some(current_block),
+                vec![contract_address],
+                vec![VALSET_UPDATED_EVENT_SIG],
+            )
+            .await?;
+        for event in ValsetUpdatedEvent::from_logs(&events)? {
+            if event.valset_nonce != nonce {
+                continue;
+            }
+            let valset = Valset {
+                nonce: event.valset_nonce,
+                members: event.members,
+                reward_amount: event.reward_amount,
+                reward_token: event.reward_token,
+            };
+            check_valset_checkpoint(gravity_id, &valset, &checkpoint)?;
+            return Ok(valset);
+        }
+        current_block = end_search;
+    }
+    Err(GravityError::ValidationError(format!(
+        "Could not find the ValsetUpdatedEvent for valset {} of contract {}",
+        nonce, contract_address
+    )))
+}
+
+/// Checks that `valset` hashes to the `checkpoint` stored by the Gravity contract
+fn check_valset_checkpoint(
+    gravity_id: String,
+    valset: &Valset,
+    checkpoint: &[u8],
+) -> Result<(), GravityError> {
+    let expected = Keccak256::digest(encode_valset_confirm(gravity_id, valset));
+    if expected.as_slice() != checkpoint {
+        return Err(GravityError::ValidationError(format!(
+            "Valset {} from the ValsetUpdatedEvent does not match the contract's checkpoint",
+            valset.nonce
+        )));
+    }
+    Ok(())
+}
+
 /// Just a helper struct to represent the cost of actions on Ethereum
 #[derive(Debug, Default, Clone, Copy)]
 pub struct GasCost {
@@ -178,3 +277,48 @@ pub fn encode_valset_struct(valset: &Valset) -> Token {
     ];
     Token::Struct(struct_tokens.to_vec())
 }
+
+#[cfg(test)]
+mod tests {
+    use gravity_utils::clarity::utils::hex_str_to_bytes;
+
+    use super::*;
+
+    #[test]
+    fn test_check_valset_checkpoint() {
+        // the valset and checkpoint of test_valset_signature
+        let checkpoint =
+            hex_str_to_bytes("0xaca2f283f21a03ba182dc7d34a55c04771b25087401d680011df7dcba453f798")
+                .unwrap();
+        let mut valset = Valset {
+            nonce: 0,
+            reward_amount: u256!(0),
+            reward_token: None,
+            members: vec![
+                ValsetMember {
+                    eth_address: "0xE5904695748fe4A84b40b3fc79De2277660BD1D3"
+                        .parse()
+                        .unwrap(),
+                    power: 3333,
+                },
+                ValsetMember {
+                    eth_address: "0xc783df8a850f42e7F7e57013759C285caa701eB6"
+                        .parse()
+                        .unwrap(),
+                    power: 3333,
+                },
+                ValsetMember {
+                    eth_address: "0xeAD9C93b79Ae7C1591b1FB5323BD777E86e150d4"
+                        .parse()
+                        .unwrap(),
+                    power: 3333,
+                },
+            ],
+        };
+        assert!(check_valset_checkpoint("foo".to_string(), &valset, &checkpoint).is_ok());
+        assert!(check_valset_checkpoint("bar".to_string(), &valset, &checkpoint).is_err());
+
+        valset.members[0].power = 3334;
+        assert!(check_valset_checkpoint("foo".to_string(), &valset, &checkpoint).is_err());
+    }
+}
@@ -15,6 +15,8 @@ use gravity_utils::{
 
 use crate::{
     message_signatures::encode_valset_confirm_hashed,
+    nonce_manager::NonceManager,
+    relay_log::{self, RelayItem, RelayStatus},
     utils::{encode_valset_struct, get_valset_nonce, GasCost},
 };
 
@@ -30,6 +32,7 @@ pub async fn send_eth_valset_update(
     gravity_contract_address: EthAddress,
     gravity_id: String,
     our_eth_key: EthPrivateKey,
+    nonce_manager: &NonceManager,
 ) -> Result<(), GravityError> {
     let old_nonce = old_valset.nonce;
     let new_nonce = new_valset.nonce;
@@ -50,19 +53,40 @@ pub async fn send_eth_valset_update(
 
     let payload = encode_valset_update_payload(new_valset, old_valset, confirms, gravity_id)?;
 
-    let tx = web3
+    let (tx, in_flight) = nonce_manager
         .send_transaction(
+            web3,
             gravity_contract_address,
             payload,
             u256!(0),
-            eth_address,
             &our_eth_key,
             vec![SendTxOption::GasPriceMultiplier(1.10f32)],
+            RelayItem {
+                kind: "valset",
+                token: None,
+                nonce: new_nonce,
+            },
         )
         .await?;
     info!("Sent valset update with txid {:#066x}", tx);
 
-    web3.wait_for_transaction(tx, timeout, None).await?;
+    let res = web3.wait_for_transaction(tx, timeout, None).await;
+    // frees the in flight slot now that the transaction is mined or given up on
+    drop(in_flight);
+    let res = match res {
+        Ok(res) => res,
+        Err(e) => {
+            relay_log::update_status(tx, RelayStatus::Failed(format!("{e:?}")));
+            return Err(e.into());
+        }
+    };
+    relay_log::update_status(
+        tx,
+        RelayStatus::Confirmed {
+            gas: res.gas,
+            gas_price: res.gas_price,
+        },
+    );
 
     let last_nonce = get_valset_nonce(gravity_contract_address, eth_address, web3).await?;
     if last_nonce != new_nonce {
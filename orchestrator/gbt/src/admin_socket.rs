@@ -0,0 +1,88 @@
+//! A unix socket for incident response. Each connection sends one command line and receives a
+//! plain text reply, currently only the relay transactions recently sent to Ethereum can be
+//! dumped, which shows what the relayer has in flight without searching the logs
+
+use std::{fs, io::ErrorKind, os::unix::fs::FileTypeExt, path::PathBuf};
+
+use ethereum_gravity::relay_log::recent_relays;
+use gravity_utils::error::GravityError;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{UnixListener, UnixStream},
+};
+
+const HELP: &str = "commands:\n  relays  the recent relay transactions and their status\n";
+
+/// Binds the admin socket at `path` and serves it in the background. A socket file left
+/// behind by a previous run is replaced, any other file at `path` is left alone and is an error
+pub fn start_admin_socket(path: &str) -> Result<(), GravityError> {
+    let path = PathBuf::from(path);
+    match fs::symlink_metadata(&path) {
+        Ok(metadata) if metadata.file_type().is_socket() => {
+            fs::remove_file(&path).map_err(|e| {
+                GravityError::UnrecoverableError(format!(
+                    "Could not remove the old admin socket {} {:?}",
+                    path.display(),
+                    e
+                ))
+            })?;
+        }
+        Ok(_) => {
+            return Err(GravityError::UnrecoverableError(format!(
+                "Admin socket path {} exists and is not a socket",
+                path.display()
+            )));
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        Err(e) => {
+            return Err(GravityError::UnrecoverableError(format!(
+                "Could not check the admin socket path {} {:?}",
+                path.display(),
+                e
+            )));
+        }
+    }
+    let listener = UnixListener::bind(&path).map_err(|e| {
+        GravityError::UnrecoverableError(format!(
+            "Could not bind the admin socket {} {:?}",
+            path.display(),
+            e
+        ))
+    })?;
+    info!("Serving the admin socket on {}", path.display());
+
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(handle_connection(stream));
+                }
+                Err(e) => warn!("Admin socket failed to accept a connection {:?}", e),
+            }
+        }
+    });
+    Ok(())
+}
+
+async fn handle_connection(stream: UnixStream) {
+    let (read, mut write) = stream.into_split();
+    let mut command = String::new();
+    if let Err(e) = BufReader::new(read).read_line(&mut command).await {
+        warn!("Failed to read an admin socket command {:?}", e);
+        return;
+    }
+    let reply = match command.trim() {
+        "relays" => {
+            let relays = recent_relays();
+            if relays.is_empty() {
+                "no relay transactions sent yet\n".to_string()
+            } else {
+                relays.iter().map(|r| format!("{r}\n")).collect()
+            }
+        }
+        _ => HELP.to_string(),
+    };
+    if let Err(e) = write.write_all(reply.as_bytes()).await {
+        warn!("Failed to reply on the admin socket {:?}", e);
+    }
+}
@@ -5,7 +5,7 @@ use std::path::PathBuf;
 
 use clap::Parser;
 use gravity_utils::{
-    clarity::{Address as EthAddress, PrivateKey as EthPrivateKey},
+    clarity::{Address as EthAddress, PrivateKey as EthPrivateKey, Signature as EthSignature},
     deep_space::{address::Address as CosmosAddress, Coin, PrivateKey as CosmosPrivateKey},
     DEFAULT_ADDRESS_PREFIX,
 };
@@ -27,6 +27,10 @@ pub struct Opts {
     /// Set the address prefix for the Cosmos chain
     #[arg(short, long, default_value = DEFAULT_ADDRESS_PREFIX)]
     pub address_prefix: String,
+    /// Exit with an error message instead of panicking when a Cosmos or Ethereum
+    /// RPC connection can not be established
+    #[arg(long)]
+    pub exit_on_connection_failure: bool,
     #[command(subcommand)]
     pub subcmd: SubCommand,
 }
@@ -35,10 +39,12 @@ pub struct Opts {
 pub enum SubCommand {
     Orchestrator(OrchestratorOpts),
     Relayer(RelayerOpts),
+    Monitor(MonitorOpts),
     Client(ClientOpts),
     Gov(GovOpts),
     Keys(KeyOpts),
     Init(InitOpts),
+    ExportConfig(ExportConfigOpts),
 }
 
 /// The Gravity Bridge orchestrator is required for all validators of the Cosmos chain running
@@ -61,9 +67,13 @@ pub struct OrchestratorOpts {
     /// The Cosmos Denom and amount to pay Cosmos chain fees
     #[arg(short, long)]
     pub fees: Coin,
-    /// The address fo the Gravity contract on Ethereum
+    /// The address of the Gravity contract on Ethereum
     #[arg(short, long)]
     pub gravity_contract_address: Option<EthAddress>,
+    /// (Optional) Cosmos mnemonic phrase of an account used to initialize the Orchestrator
+    /// account when paying zero fees, requires auto_initialize_account in the config
+    #[arg(long)]
+    pub funding_phrase: Option<CosmosPrivateKey>,
 }
 
 /// The Gravity Bridge Relayer is an unpermissioned role that takes data from the Cosmos blockchain
@@ -81,7 +91,7 @@ pub struct RelayerOpts {
     /// request batches
     #[arg(short, long)]
     pub fees: Option<Coin>,
-    /// The address fo the Gravity contract on Ethereum
+    /// The address of the Gravity contract on Ethereum
     #[arg(short, long)]
     pub gravity_contract_address: Option<EthAddress>,
     /// (Optional) The Ethereum RPC server that will be used
@@ -92,6 +102,25 @@ pub struct RelayerOpts {
     pub cosmos_grpc: String,
 }
 
+/// The Gravity Bridge monitor watches the status of the whole bridge without any keys, it never
+/// signs or relays anything and reports what it sees through the metrics exporter
+#[derive(Parser)]
+pub struct MonitorOpts {
+    /// The address of the Gravity contract on Ethereum
+    #[arg(short, long)]
+    pub gravity_contract_address: Option<EthAddress>,
+    /// (Optional) The Ethereum RPC server that will be used
+    #[arg(long, default_value = "http://localhost:8545")]
+    pub ethereum_rpc: String,
+    /// (Optional) The Cosmos gRPC server that will be used
+    #[arg(long, default_value = "http://localhost:9090")]
+    pub cosmos_grpc: String,
+    /// (Optional) The Orchestrator address of a validator whose oracle progress should be
+    /// reported, its last event nonce and last checked Ethereum block
+    #[arg(long)]
+    pub orchestrator_address: Option<CosmosAddress>,
+}
+
 /// The Gravity Bridge client contains helpful command line tools for interacting with the Gravity bridge
 #[derive(Parser)]
 pub struct ClientOpts {
@@ -104,6 +133,9 @@ pub enum ClientSubcommand {
     CosmosToEth(CosmosToEthOpts),
     EthToCosmos(EthToCosmosOpts),
     DeployErc20Representation(DeployErc20RepresentationOpts),
+    EstimateRelayCost(EstimateRelayCostOpts),
+    Reconcile(ReconcileOpts),
+    ValsetConfirms(ValsetConfirmsOpts),
 }
 
 /// Send Cosmos tokens to Ethereum
@@ -139,7 +171,7 @@ pub struct EthToCosmosOpts {
     /// (Optional) The Ethereum RPC server that will be used to submit the transaction
     #[arg(long, default_value = "http://localhost:8545")]
     pub ethereum_rpc: String,
-    /// The address fo the Gravity contract on Ethereum
+    /// The address of the Gravity contract on Ethereum
     #[arg(short, long)]
     pub gravity_contract_address: EthAddress,
     /// The ERC20 contract address of the ERC20 you are sending
@@ -169,7 +201,61 @@ pub struct DeployErc20RepresentationOpts {
     /// An Ethereum private key, containing enough ETH to pay for the transaction
     #[arg(short, long)]
     pub ethereum_key: EthPrivateKey,
-    /// The address fo the Gravity contract on Ethereum
+    /// The address of the Gravity contract on Ethereum
+    #[arg(short, long)]
+    pub gravity_contract_address: Option<EthAddress>,
+}
+
+/// Estimate the total ETH required to relay all batches that are currently
+/// ready to submit to Ethereum, nothing is submitted
+#[derive(Parser)]
+pub struct EstimateRelayCostOpts {
+    /// (Optional) The Cosmos gRPC server that will be used to query batches
+    #[arg(long, default_value = "http://localhost:9090")]
+    pub cosmos_grpc: String,
+    /// (Optional) The Ethereum RPC server that will be used to estimate gas
+    #[arg(long, default_value = "http://localhost:8545")]
+    pub ethereum_rpc: String,
+    /// The Ethereum private key of the relayer, used as the sender of the estimates
+    #[arg(short, long)]
+    pub ethereum_key: EthPrivateKey,
+    /// The address of the Gravity contract on Ethereum
+    #[arg(short, long)]
+    pub gravity_contract_address: Option<EthAddress>,
+}
+
+/// Compare the amount of an Ethereum originated token locked in the Gravity contract with
+/// the amount issued on Cosmos, reporting any shortfall. Nothing is submitted
+#[derive(Parser)]
+pub struct ReconcileOpts {
+    /// The ERC20 contract address of the token to reconcile
+    #[arg(short, long)]
+    pub token_contract_address: EthAddress,
+    /// (Optional) The Cosmos gRPC server that will be used to query the supply
+    #[arg(long, default_value = "http://localhost:9090")]
+    pub cosmos_grpc: String,
+    /// (Optional) The Ethereum RPC server that will be used to query the locked balance
+    #[arg(long, default_value = "http://localhost:8545")]
+    pub ethereum_rpc: String,
+    /// The address of the Gravity contract on Ethereum
+    #[arg(short, long)]
+    pub gravity_contract_address: Option<EthAddress>,
+}
+
+/// Show which validators have confirmed the valset with the given nonce and if the confirms
+/// hold enough power for the valset to be relayed. Nothing is submitted
+#[derive(Parser)]
+pub struct ValsetConfirmsOpts {
+    /// The nonce of the valset to show the confirms for
+    #[arg(short, long)]
+    pub nonce: u64,
+    /// (Optional) The Cosmos gRPC server that will be used to query the confirms
+    #[arg(long, default_value = "http://localhost:9090")]
+    pub cosmos_grpc: String,
+    /// (Optional) The Ethereum RPC server that will be used to find the current valset
+    #[arg(long, default_value = "http://localhost:8545")]
+    pub ethereum_rpc: String,
+    /// The address of the Gravity contract on Ethereum
     #[arg(short, long)]
     pub gravity_contract_address: Option<EthAddress>,
 }
@@ -184,6 +270,8 @@ pub struct KeyOpts {
 #[derive(Parser)]
 pub enum KeysSubcommand {
     RegisterOrchestratorAddress(RegisterOrchestratorAddressOpts),
+    PrintAddresses(PrintAddressesOpts),
+    VerifySignature(VerifySignatureOpts),
 }
 
 /// Register delegate keys for the Gravity Orchestrator.
@@ -208,10 +296,52 @@ pub struct RegisterOrchestratorAddressOpts {
     pub fees: Coin,
 }
 
+/// Print the addresses the provided keys derive to and exit, useful for finding
+/// which accounts to fund or register as delegate keys
+#[derive(Parser)]
+pub struct PrintAddressesOpts {
+    /// (Optional) The Cosmos mnemonic phrase to derive an address for
+    #[arg(short, long)]
+    pub cosmos_phrase: Option<CosmosPrivateKey>,
+    /// (Optional) The Ethereum private key to derive an address for
+    #[arg(short, long)]
+    pub ethereum_key: Option<EthPrivateKey>,
+}
+
+/// Check offline that an Ethereum signature recovers to a delegate Ethereum address, using the
+/// same Ethereum signed message recovery the Gravity module applies to confirms. Useful for
+/// finding out which key actually produced a signature when delegate keys are mixed up
+#[derive(Parser)]
+pub struct VerifySignatureOpts {
+    /// The delegate Ethereum address the signature should recover to
+    #[arg(short, long)]
+    pub eth_address: EthAddress,
+    /// The signature to verify, as hex
+    #[arg(short, long)]
+    pub signature: EthSignature,
+    /// The signed message as hex, for confirms this is the ABI encoded checkpoint
+    #[arg(
+        short,
+        long,
+        required_unless_present = "digest",
+        conflicts_with = "digest"
+    )]
+    pub message: Option<String>,
+    /// The 32 byte Ethereum signed message hash as hex instead of the message, as printed
+    /// after "signed as" in checkpoint layouts
+    #[arg(short, long)]
+    pub digest: Option<String>,
+}
+
 /// Initialize configuration
 #[derive(Parser)]
 pub struct InitOpts {}
 
+/// Print the config as it will be used, with defaults applied and credentials redacted,
+/// for sharing in bug reports
+#[derive(Parser)]
+pub struct ExportConfigOpts {}
+
 /// The Gravity Bridge Governance subcommand contains tools for interacting with governance and submitting
 /// proposal types custom to Gravity Bridge
 #[derive(Parser)]
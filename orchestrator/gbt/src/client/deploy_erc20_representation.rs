@@ -10,7 +10,10 @@ use gravity_utils::{
 };
 use tokio::time::sleep;
 
-use crate::{args::DeployErc20RepresentationOpts, utils::TIMEOUT};
+use crate::{
+    args::DeployErc20RepresentationOpts,
+    utils::{get_gravity_contract_address, TIMEOUT},
+};
 
 pub async fn deploy_erc20_representation(
     args: DeployErc20RepresentationOpts,
@@ -31,18 +34,8 @@ pub async fn deploy_erc20_representation(
     let ethereum_public_key = ethereum_key.to_address();
     check_for_eth(ethereum_public_key, &web3).await?;
 
-    let contract_address = if let Some(c) = args.gravity_contract_address {
-        c
-    } else {
-        let params = get_gravity_params(&mut grpc).await.unwrap();
-        let c = params.bridge_ethereum_address.parse();
-        if c.is_err() {
-            return Err(GravityError::UnrecoverableError(
-                "The Gravity address is not yet set as a chain parameter! You must specify --gravity-contract-address".into(),
-            ));
-        }
-        c.unwrap()
-    };
+    let params = get_gravity_params(&mut grpc).await?;
+    let contract_address = get_gravity_contract_address(args.gravity_contract_address, &params)?;
 
     let res = grpc
         .denom_to_erc20(QueryDenomToErc20Request {
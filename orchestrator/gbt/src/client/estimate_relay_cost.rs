@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+
+use cosmos_gravity::query::get_gravity_params;
+use gravity_utils::{
+    clarity::{u256, Uint256},
+    connection_prep::create_rpc_connections,
+    error::GravityError,
+    num_conversion::{print_eth, print_gwei},
+    u64_array_bigints,
+};
+use relayer::{
+    batch_relaying::estimate_pending_batch_costs, find_latest_valset::find_latest_valset,
+};
+
+use crate::{
+    args::EstimateRelayCostOpts,
+    utils::{get_gravity_contract_address, TIMEOUT},
+};
+
+pub async fn estimate_relay_cost(
+    args: EstimateRelayCostOpts,
+    address_prefix: String,
+) -> Result<(), GravityError> {
+    let connections = create_rpc_connections(
+        address_prefix,
+        Some(args.cosmos_grpc),
+        Some(args.ethereum_rpc),
+        TIMEOUT,
+    )
+    .await;
+    let web3 = connections.web3.unwrap();
+    let mut grpc = connections.grpc.unwrap();
+
+    let params = get_gravity_params(&mut grpc).await?;
+    let gravity_contract_address =
+        get_gravity_contract_address(args.gravity_contract_address, &params)?;
+
+    let current_valset = find_latest_valset(&mut grpc, gravity_contract_address, &web3).await?;
+    let estimates = estimate_pending_batch_costs(
+        &current_valset,
+        args.ethereum_key,
+        &web3,
+        &mut grpc,
+        gravity_contract_address,
+        params.gravity_id,
+    )
+    .await?;
+
+    if estimates.is_empty() {
+        info!("There are no batches ready to relay");
+        return Ok(());
+    }
+
+    let mut per_token: HashMap<_, (usize, Uint256)> = HashMap::new();
+    let mut total = u256!(0);
+    for estimate in estimates {
+        info!(
+            "Batch {}/{} is estimated to cost {} Gas @ {} gwei / {:.4} ETH",
+            estimate.token_contract,
+            estimate.nonce,
+            estimate.cost.gas,
+            print_gwei(estimate.cost.gas_price),
+            print_eth(estimate.cost.get_total())
+        );
+        let entry = per_token
+            .entry(estimate.token_contract)
+            .or_insert((0, u256!(0)));
+        entry.0 += 1;
+        entry.1 = entry.1.checked_add(estimate.cost.get_total()).unwrap();
+        total = total.checked_add(estimate.cost.get_total()).unwrap();
+    }
+
+    for (token, (count, cost)) in per_token {
+        info!(
+            "Token {}: {} batches, {:.4} ETH",
+            token,
+            count,
+            print_eth(cost)
+        );
+    }
+    info!(
+        "Relaying all pending batches is estimated to cost {:.4} ETH",
+        print_eth(total)
+    );
+
+    Ok(())
+}
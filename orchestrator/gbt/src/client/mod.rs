@@ -1,3 +1,6 @@
 pub mod cosmos_to_eth;
 pub mod deploy_erc20_representation;
+pub mod estimate_relay_cost;
 pub mod eth_to_cosmos;
+pub mod reconcile;
+pub mod valset_confirms;
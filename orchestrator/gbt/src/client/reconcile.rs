@@ -0,0 +1,170 @@
+use cosmos_gravity::query::{
+    get_erc20_to_denom, get_gravity_params, get_latest_transaction_batches,
+};
+use ethereum_gravity::utils::get_tx_batch_nonce;
+use gravity_utils::{
+    clarity::{constants::ZERO_ADDRESS, u256, Address as EthAddress, Uint256},
+    connection_prep::{create_rpc_connections, get_denom_supply},
+    error::GravityError,
+    types::TransactionBatch,
+    u64_array_bigints,
+};
+
+use crate::{
+    args::ReconcileOpts,
+    utils::{get_gravity_contract_address, TIMEOUT},
+};
+
+/// Compares the balance of an Ethereum originated token held by the Gravity contract with the
+/// supply of its voucher on Cosmos. Vouchers of outgoing transfers stay in the Gravity module
+/// until the batch is observed, so the two sides match except for deposits that are not yet
+/// observed on Cosmos, tokens sent straight to the contract and batches executed on Ethereum
+/// but not yet observed. The last of these is accounted for, so a supply larger than the
+/// locked balance means the bridge is undercollateralized
+pub async fn reconcile(args: ReconcileOpts, address_prefix: String) -> Result<(), GravityError> {
+    let token = args.token_contract_address;
+    let connections = create_rpc_connections(
+        address_prefix,
+        Some(args.cosmos_grpc),
+        Some(args.ethereum_rpc),
+        TIMEOUT,
+    )
+    .await;
+    let web3 = connections.web3.unwrap();
+    let contact = connections.contact.unwrap();
+    let mut grpc = connections.grpc.unwrap();
+
+    let params = get_gravity_params(&mut grpc).await?;
+    let gravity_contract_address =
+        get_gravity_contract_address(args.gravity_contract_address, &params)?;
+
+    let denom = get_erc20_to_denom(&mut grpc, token).await?;
+    if denom.cosmos_originated {
+        return Err(GravityError::ValidationError(format!(
+            "{} represents the Cosmos originated {}, only Ethereum originated tokens can be reconciled",
+            token, denom.denom
+        )));
+    }
+
+    let locked = web3
+        .get_erc20_balance(token, gravity_contract_address)
+        .await?;
+    let supply = get_denom_supply(&denom.denom, &contact).await?;
+
+    let ethereum_batch_nonce =
+        get_tx_batch_nonce(gravity_contract_address, token, ZERO_ADDRESS, &web3).await?;
+    let batches = get_latest_transaction_batches(&mut grpc).await?;
+    let unobserved_withdrawals = unobserved_withdrawals(&batches, token, ethereum_batch_nonce);
+    let backing = locked.checked_add(unobserved_withdrawals).unwrap();
+
+    info!("Token {} is {} on Cosmos", token, denom.denom);
+    info!("Locked in the Gravity contract: {}", locked);
+    info!(
+        "Executed on Ethereum but not yet observed on Cosmos: {}",
+        unobserved_withdrawals
+    );
+    info!("Issued on Cosmos: {}", supply);
+
+    if backing < supply {
+        return Err(GravityError::UnrecoverableError(format!(
+            "{} is undercollateralized! {} more is issued on Cosmos than is locked on Ethereum",
+            denom.denom,
+            supply.checked_sub(backing).unwrap()
+        )));
+    }
+    let surplus = backing.checked_sub(supply).unwrap();
+    if surplus.is_zero() {
+        info!("The locked balance matches the Cosmos supply");
+    } else {
+        info!(
+            "{} more is locked than issued, this is expected for deposits not yet observed on Cosmos or tokens sent directly to the contract",
+            surplus
+        );
+    }
+    Ok(())
+}
+
+/// The total withdrawn by the batches of `token` executed on Ethereum but still pending on
+/// Cosmos, which are all pending batches up to `ethereum_batch_nonce`. Ethereum may execute
+/// several batches before Cosmos observes any of them, batches are only removed from Cosmos
+/// and their vouchers burned once observed, until then their tokens have left the contract
+/// but the vouchers still exist
+fn unobserved_withdrawals(
+    batches: &[TransactionBatch],
+    token: EthAddress,
+    ethereum_batch_nonce: u64,
+) -> Uint256 {
+    let mut total = u256!(0);
+    for batch in batches
+        .iter()
+        .filter(|b| b.token_contract == token && b.nonce <= ethereum_batch_nonce)
+    {
+        for tx in batch.transactions.iter() {
+            total = total
+                .checked_add(tx.erc20_token.amount)
+                .and_then(|v| v.checked_add(tx.erc20_fee.amount))
+                .unwrap();
+        }
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use gravity_utils::types::{BatchTransaction, Erc20Token};
+
+    use super::*;
+
+    fn batch(token: EthAddress, nonce: u64, amount: u64) -> TransactionBatch {
+        let amount = Erc20Token {
+            amount: Uint256::from_u64(amount),
+            token_contract_address: token,
+        };
+        TransactionBatch {
+            nonce,
+            batch_timeout: 1000,
+            transactions: vec![BatchTransaction {
+                id: nonce,
+                sender: "althea1c8nkaxk3d0p2gd7ummvmyqpdvqd6pkehqhwnnt"
+                    .parse()
+                    .unwrap(),
+                destination: "0x9FC9C2DfBA3b6cF204C37a5F690619772b926e39"
+                    .parse()
+                    .unwrap(),
+                erc20_token: amount,
+                erc20_fee: amount,
+            }],
+            total_fee: amount,
+            token_contract: token,
+        }
+    }
+
+    #[test]
+    fn test_unobserved_withdrawals() {
+        let token: EthAddress = "0x6Bd41fCdF129297c3524395d669c0865b3CA85B2"
+            .parse()
+            .unwrap();
+        let other: EthAddress = "0x0412C7c846bb6b7DC462CF6B453f76D8440b2609"
+            .parse()
+            .unwrap();
+        let batches = vec![
+            batch(token, 4, 10),
+            batch(token, 5, 20),
+            batch(token, 6, 40),
+            batch(other, 5, 80),
+        ];
+        // Ethereum executed batches 4 and 5 before Cosmos observed either
+        assert_eq!(
+            unobserved_withdrawals(&batches, token, 5),
+            Uint256::from_u64(60)
+        );
+        assert_eq!(
+            unobserved_withdrawals(&batches, token, 3),
+            Uint256::from_u64(0)
+        );
+        assert_eq!(
+            unobserved_withdrawals(&batches, token, 6),
+            Uint256::from_u64(140)
+        );
+    }
+}
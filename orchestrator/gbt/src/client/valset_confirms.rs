@@ -0,0 +1,106 @@
+use cosmos_gravity::query::{get_gravity_params, get_valset_with_confirms};
+use ethereum_gravity::message_signatures::encode_valset_confirm_hashed;
+use gravity_utils::{
+    connection_prep::create_rpc_connections,
+    error::GravityError,
+    types::{confirm_signature_recovers, get_hashmap, GRAVITY_POWER_TO_PASS, TOTAL_GRAVITY_POWER},
+};
+use relayer::find_latest_valset::find_latest_valset;
+
+use crate::{
+    args::ValsetConfirmsOpts,
+    utils::{get_gravity_contract_address, TIMEOUT},
+};
+
+/// Shows every member of the valset currently on Ethereum, their power and if they have
+/// submitted a valid confirm of the valset with the given nonce, then if the confirmed power
+/// passes the threshold required to relay it. Updating the Ethereum valset requires signatures
+/// from the current Ethereum valset, not the new one, so that is the set that is checked
+pub async fn valset_confirms(
+    args: ValsetConfirmsOpts,
+    address_prefix: String,
+) -> Result<(), GravityError> {
+    let connections = create_rpc_connections(
+        address_prefix,
+        Some(args.cosmos_grpc),
+        Some(args.ethereum_rpc),
+        TIMEOUT,
+    )
+    .await;
+    let web3 = connections.web3.unwrap();
+    let mut grpc = connections.grpc.unwrap();
+
+    let params = get_gravity_params(&mut grpc).await?;
+    let gravity_contract_address =
+        get_gravity_contract_address(args.gravity_contract_address, &params)?;
+
+    let (valset, confirms) = match get_valset_with_confirms(&mut grpc, args.nonce).await? {
+        Some(v) => v,
+        None => {
+            return Err(GravityError::ValidationError(format!(
+                "There is no valset with nonce {}",
+                args.nonce
+            )))
+        }
+    };
+    let current_valset = find_latest_valset(&mut grpc, gravity_contract_address, &web3).await?;
+    if valset.nonce <= current_valset.nonce {
+        info!(
+            "Valset {} is not newer than valset {} on Ethereum and can not be relayed",
+            valset.nonce, current_valset.nonce
+        );
+        return Ok(());
+    }
+    let hash = encode_valset_confirm_hashed(params.gravity_id, &valset);
+    let confirms = get_hashmap(&confirms);
+
+    let mut confirmed_power = 0u64;
+    let mut confirmed = 0;
+    for member in current_valset.members.iter() {
+        match confirms.get(&member.eth_address) {
+            Some(confirm) if confirm_signature_recovers(confirm, &hash) => {
+                confirmed_power += member.power;
+                confirmed += 1;
+                info!(
+                    "{} power {:.2}% confirmed by {}",
+                    member.eth_address,
+                    to_percent(member.power),
+                    confirm.orchestrator
+                );
+            }
+            Some(confirm) => info!(
+                "{} power {:.2}% submitted an invalid signature through {}",
+                member.eth_address,
+                to_percent(member.power),
+                confirm.orchestrator
+            ),
+            None => info!(
+                "{} power {:.2}% not confirmed",
+                member.eth_address,
+                to_percent(member.power)
+            ),
+        }
+    }
+
+    info!(
+        "Valset {} has valid confirms from {}/{} members of Ethereum valset {} holding {:.2}% of the power",
+        valset.nonce,
+        confirmed,
+        current_valset.members.len(),
+        current_valset.nonce,
+        to_percent(confirmed_power)
+    );
+    if confirmed_power > GRAVITY_POWER_TO_PASS {
+        info!("The confirms pass the 66% threshold, this valset can be relayed");
+    } else {
+        info!(
+            "The confirms do not pass the 66% threshold, more than {:.2}% more is required to relay this valset",
+            to_percent(GRAVITY_POWER_TO_PASS - confirmed_power)
+        );
+    }
+    Ok(())
+}
+
+fn to_percent(power: u64) -> f64 {
+    power as f64 / TOTAL_GRAVITY_POWER as f64 * 100f64
+}
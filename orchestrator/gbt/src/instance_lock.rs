@@ -0,0 +1,108 @@
+//! Running two Orchestrators with the same keys double submits confirms and claims, wasting
+//! fees on sequence errors. An advisory lock on a file per Ethereum key and duty in the system
+//! temp directory stops a second instance running the same duty from starting on the same host,
+//! no matter which --home it is given. Instances splitting the duties between them, such as one
+//! running only the oracle and another only the signer, do not conflict. The operating system
+//! releases the lock when the process exits in any way, so a crash, signal or exit never leaves
+//! a stale lock behind.
+
+use std::{
+    env,
+    fs::{File, OpenOptions},
+    io::{self, ErrorKind, Read, Seek, SeekFrom, Write},
+    os::unix::io::AsRawFd,
+};
+
+use gravity_utils::{clarity::Address as EthAddress, error::GravityError};
+
+/// Holds the instance lock until dropped or the process exits
+pub struct InstanceLock {
+    _file: Option<File>,
+}
+
+impl InstanceLock {
+    /// Takes the lock for running `duty` with `eth_address`, failing if another running
+    /// process holds it
+    pub fn acquire(eth_address: EthAddress, duty: &str) -> Result<InstanceLock, GravityError> {
+        let path = env::temp_dir().join(format!("gbt-orchestrator-{}-{}.lock", eth_address, duty));
+        let lock = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .open(&path)
+            .and_then(|mut file| try_lock(&mut file).map(|locked| (file, locked)));
+        match lock {
+            Ok((mut file, true)) => {
+                // the pid is only informational, for the error of an instance that fails to
+                // take the lock
+                let written = file
+                    .set_len(0)
+                    .and_then(|_| write!(file, "{}", std::process::id()));
+                if let Err(e) = written {
+                    warn!("Failed to write instance lock {} {:?}", path.display(), e);
+                }
+                Ok(InstanceLock { _file: Some(file) })
+            }
+            Ok((mut file, false)) => {
+                let mut pid = String::new();
+                let _ = file
+                    .seek(SeekFrom::Start(0))
+                    .and_then(|_| file.read_to_string(&mut pid));
+                Err(GravityError::UnrecoverableError(format!(
+                    "Another Orchestrator (pid {}) is already running the {} with Ethereum key {}, \
+                    running two with the same keys wastes fees on duplicate submissions",
+                    pid.trim(),
+                    duty,
+                    eth_address,
+                )))
+            }
+            Err(e) => {
+                // the lock is a safeguard, failing to create it should not stop the Orchestrator
+                warn!(
+                    "Could not take instance lock {}, duplicate instances will not be detected {:?}",
+                    path.display(),
+                    e
+                );
+                Ok(InstanceLock { _file: None })
+            }
+        }
+    }
+}
+
+/// Takes an exclusive advisory lock on `file` without waiting, returns false if another
+/// open file description holds it
+fn try_lock(file: &mut File) -> io::Result<bool> {
+    // Safety: the fd is owned by `file` and stays open for the duration of the call
+    let res = unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) };
+    if res == 0 {
+        return Ok(true);
+    }
+    let e = io::Error::last_os_error();
+    if e.kind() == ErrorKind::WouldBlock {
+        Ok(false)
+    } else {
+        Err(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instance_lock() {
+        let address: EthAddress = "0x000000000000000000000000000000000000dEaD"
+            .parse()
+            .unwrap();
+        let lock = InstanceLock::acquire(address, "oracle").unwrap();
+        assert!(lock._file.is_some());
+        // flock locks belong to the open file, so a second open in this process conflicts
+        // the same way a second Orchestrator would
+        assert!(InstanceLock::acquire(address, "oracle").is_err());
+        // an Orchestrator running a different duty with the same keys does not
+        let signer_lock = InstanceLock::acquire(address, "signer").unwrap();
+        drop(lock);
+        InstanceLock::acquire(address, "oracle").unwrap();
+        drop(signer_lock);
+    }
+}
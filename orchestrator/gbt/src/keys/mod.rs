@@ -1 +1,3 @@
+pub mod print_addresses;
 pub mod register_orchestrator_address;
+pub mod verify_signature;
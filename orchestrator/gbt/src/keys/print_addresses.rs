@@ -0,0 +1,26 @@
+use gravity_utils::error::GravityError;
+
+use crate::args::PrintAddressesOpts;
+
+/// Prints the addresses derived from the provided keys without connecting to any node
+pub fn print_addresses(args: PrintAddressesOpts, prefix: String) -> Result<(), GravityError> {
+    if args.cosmos_phrase.is_none() && args.ethereum_key.is_none() {
+        return Err(GravityError::ValidationError(
+            "Provide a --cosmos-phrase and/or an --ethereum-key to print the addresses of".into(),
+        ));
+    }
+
+    if let Some(cosmos_key) = args.cosmos_phrase {
+        let cosmos_address = cosmos_key.to_address(&prefix).map_err(|e| {
+            GravityError::ValidationError(format!(
+                "Failed to derive a Cosmos address with prefix {} {:?}",
+                prefix, e
+            ))
+        })?;
+        println!("Cosmos Address: {}", cosmos_address);
+    }
+    if let Some(ethereum_key) = args.ethereum_key {
+        println!("Ethereum Address: {}", ethereum_key.to_address());
+    }
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use gravity_utils::{
+    clarity::utils::{get_ethereum_msg_hash, hex_str_to_bytes},
+    error::GravityError,
+};
+
+use crate::args::VerifySignatureOpts;
+
+/// Recovers the signer of a signature over a message or digest without connecting to any node
+/// and checks that it is the expected delegate Ethereum address
+pub fn verify_signature(args: VerifySignatureOpts) -> Result<(), GravityError> {
+    let digest = match (&args.message, &args.digest) {
+        (Some(message), _) => get_ethereum_msg_hash(&parse_hex("message", message)?),
+        (None, Some(digest)) => {
+            let digest = parse_hex("digest", digest)?;
+            if digest.len() != 32 {
+                return Err(GravityError::ValidationError(format!(
+                    "The digest must be 32 bytes, got {}",
+                    digest.len()
+                )));
+            }
+            digest
+        }
+        (None, None) => {
+            return Err(GravityError::ValidationError(
+                "Provide the signed --message or its --digest".into(),
+            ))
+        }
+    };
+
+    let recovered = args.signature.recover(&digest).map_err(|e| {
+        GravityError::ValidationError(format!("Could not recover a signer {:?}", e))
+    })?;
+    if recovered == args.eth_address {
+        println!("Signature is valid, it recovers to {}", recovered);
+        Ok(())
+    } else {
+        Err(GravityError::ValidationError(format!(
+            "Signature is not from {}, it recovers to {}. Check which Ethereum key is registered as the delegate",
+            args.eth_address, recovered
+        )))
+    }
+}
+
+fn parse_hex(name: &str, input: &str) -> Result<Vec<u8>, GravityError> {
+    hex_str_to_bytes(input)
+        .map_err(|e| GravityError::ValidationError(format!("Invalid {} hex {:?}", name, e)))
+}
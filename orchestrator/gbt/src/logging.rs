@@ -0,0 +1,96 @@
+//! A logger whose filter can be replaced at runtime. Operators debugging one subsystem of a
+//! running Orchestrator can edit the log_filter in the config file, for example to
+//! "info,relayer=trace", and the new filter is applied without a restart
+
+use std::{env, fs, path::PathBuf, sync::RwLock, time::Duration};
+
+use env_logger::{Builder, Logger};
+use lazy_static::lazy_static;
+use log::{Log, Metadata, Record};
+use tokio::time::sleep;
+
+use crate::config::{config_file_path, load_config_async};
+
+/// The filter used when neither RUST_LOG nor the config set one
+const DEFAULT_LOG_FILTER: &str = "info";
+
+/// How often the config file is checked for changes
+const CONFIG_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+lazy_static! {
+    static ref LOGGER: RwLock<Logger> = RwLock::new(build_logger(DEFAULT_LOG_FILTER));
+}
+
+struct ReloadableLogger;
+
+impl Log for ReloadableLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        LOGGER.read().unwrap().enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        LOGGER.read().unwrap().log(record)
+    }
+
+    fn flush(&self) {
+        LOGGER.read().unwrap().flush()
+    }
+}
+
+fn build_logger(filter: &str) -> Logger {
+    Builder::new().parse_filters(filter).build()
+}
+
+/// Installs the global logger using RUST_LOG if it is set
+pub fn init_logging() {
+    let filter = env::var("RUST_LOG").unwrap_or_else(|_| DEFAULT_LOG_FILTER.to_string());
+    set_log_filter(&filter);
+    log::set_logger(&ReloadableLogger).expect("Logger already initialized");
+}
+
+/// Replaces the filter of the global logger
+pub fn set_log_filter(filter: &str) {
+    let logger = build_logger(filter);
+    log::set_max_level(logger.filter());
+    *LOGGER.write().unwrap() = logger;
+}
+
+/// Applies the log_filter of the config unless RUST_LOG is set, then keeps applying it
+/// whenever the config file changes
+pub async fn watch_log_filter(home_dir: PathBuf, log_filter: Option<String>) {
+    if env::var("RUST_LOG").is_err() {
+        if let Some(filter) = &log_filter {
+            set_log_filter(filter);
+        }
+    }
+
+    let config_file = config_file_path(&home_dir);
+    let modified = |path: &PathBuf| fs::metadata(path).and_then(|m| m.modified()).ok();
+    let mut last_modified = modified(&config_file);
+    let mut current_filter = log_filter;
+    loop {
+        sleep(CONFIG_CHECK_INTERVAL).await;
+        let now_modified = modified(&config_file);
+        if now_modified == last_modified {
+            continue;
+        }
+        last_modified = now_modified;
+
+        let new_filter = match load_config_async(&home_dir).await {
+            Ok(config) => config.logging.log_filter,
+            Err(e) => {
+                warn!(
+                    "Config changed but could not be loaded, keeping the log filter {:?}",
+                    e
+                );
+                continue;
+            }
+        };
+        if new_filter != current_filter {
+            let filter = new_filter.as_deref().unwrap_or(DEFAULT_LOG_FILTER);
+            info!("Log filter changed in the config, now using {}", filter);
+            set_log_filter(filter);
+            current_filter = new_filter;
+        }
+    }
+}
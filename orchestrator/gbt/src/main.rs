@@ -1,52 +1,81 @@
 #[macro_use]
 extern crate log;
 
-use std::process::exit;
+use std::{path::PathBuf, process::exit, time::Duration};
 
 use args::{GovQuerySubcommand, GovSubcommand, GovSubmitSubcommand, Opts};
 use clap::Parser;
 use client::{
     cosmos_to_eth::cosmos_to_eth, deploy_erc20_representation::deploy_erc20_representation,
-    eth_to_cosmos::eth_to_cosmos,
+    estimate_relay_cost::estimate_relay_cost, eth_to_cosmos::eth_to_cosmos, reconcile::reconcile,
+    valset_confirms::valset_confirms,
 };
-use config::{get_home_dir, load_config};
-use env_logger::Env;
+use config::{export_config, get_home_dir, load_config};
+use cosmos_gravity::send::set_expected_chain_id;
 use gov::{
     proposals::{
         submit_airdrop, submit_emergency_bridge_halt, submit_ibc_metadata, submit_oracle_unhalt,
     },
     queries::query_airdrops,
 };
-use gravity_utils::error::GravityError;
-use keys::register_orchestrator_address::register_orchestrator_address;
+use gravity_utils::{
+    connection_prep::{set_exit_on_connection_failure, set_probe_timeout, set_user_agent},
+    error::GravityError,
+    pause::set_pause_file,
+    slashing_alerts::SLASHING_RISK_LOG_TARGET,
+    types::{GravityBridgeToolsConfig, RuntimeConfig, RuntimeFlavor},
+};
+use keys::{
+    print_addresses::print_addresses, register_orchestrator_address::register_orchestrator_address,
+    verify_signature::verify_signature,
+};
+
+use tokio::runtime::{Builder, Runtime};
 
 use crate::{
     args::{ClientSubcommand, KeysSubcommand, SubCommand},
     config::init_config,
+    logging::{init_logging, watch_log_filter},
+    monitor::monitor,
     orchestrator::orchestrator,
     relayer::relayer,
 };
 
+mod admin_socket;
 mod args;
 mod client;
 mod config;
 mod gov;
+mod instance_lock;
 mod keys;
+mod logging;
+mod monitor;
 mod orchestrator;
 mod relayer;
 mod utils;
 
-#[tokio::main]
-async fn main() {
-    env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
+fn main() {
+    init_logging();
     // On Linux static builds we need to probe ssl certs path to be able to
     // do TLS stuff.
     openssl_probe::init_ssl_cert_env_vars();
 
-    if let Err(gravity_error) = run_gbt().await {
+    // parse the arguments
+    let opts: Opts = Opts::parse();
+
+    // the runtime is configurable, so it can only be started once the config is loaded
+    let res = get_home_dir(opts.home.clone()).and_then(|home_dir| {
+        let config = load_config(&home_dir)?;
+        build_runtime(&config.runtime)?.block_on(run_gbt(opts, home_dir, config))
+    });
+    if let Err(gravity_error) = res {
         match gravity_error {
             // exit only on unrecoverable errors
             GravityError::UnrecoverableError(error) => {
+                error!(target: SLASHING_RISK_LOG_TARGET, "{}", error);
+                exit(1);
+            }
+            GravityError::HaltError(error) => {
                 error!("{}", error);
                 exit(1);
             }
@@ -63,14 +92,35 @@ async fn main() {
     }
 }
 
-async fn run_gbt() -> Result<(), GravityError> {
-    // parse the arguments
-    let opts: Opts = Opts::parse();
+fn build_runtime(config: &RuntimeConfig) -> Result<Runtime, GravityError> {
+    let mut builder = match config.flavor {
+        RuntimeFlavor::MultiThread => Builder::new_multi_thread(),
+        RuntimeFlavor::CurrentThread => Builder::new_current_thread(),
+    };
+    if let Some(worker_threads) = config.worker_threads {
+        builder.worker_threads(worker_threads);
+    }
+    builder.enable_all().build().map_err(|e| {
+        GravityError::UnrecoverableError(format!("Failed to start the async runtime {e:?}"))
+    })
+}
 
+async fn run_gbt(
+    opts: Opts,
+    home_dir: PathBuf,
+    config: GravityBridgeToolsConfig,
+) -> Result<(), GravityError> {
     // handle global config here
     let address_prefix = opts.address_prefix;
-    let home_dir = get_home_dir(opts.home)?;
-    let config = load_config(&home_dir)?;
+    set_exit_on_connection_failure(opts.exit_on_connection_failure);
+    set_user_agent(config.rpc.user_agent.clone());
+    set_probe_timeout(Duration::from_secs(config.rpc.probe_timeout));
+    set_expected_chain_id(config.rpc.expected_cosmos_chain_id.clone());
+    set_pause_file(config.orchestrator.pause_file.clone().map(PathBuf::from));
+    tokio::spawn(watch_log_filter(
+        home_dir.clone(),
+        config.logging.log_filter.clone(),
+    ));
 
     // control flow for the command structure
     match opts.subcmd {
@@ -84,19 +134,43 @@ async fn run_gbt() -> Result<(), GravityError> {
             ClientSubcommand::DeployErc20Representation(deploy_erc20_opts) => {
                 deploy_erc20_representation(deploy_erc20_opts, address_prefix).await
             }
+            ClientSubcommand::EstimateRelayCost(estimate_relay_cost_opts) => {
+                estimate_relay_cost(estimate_relay_cost_opts, address_prefix).await
+            }
+            ClientSubcommand::Reconcile(reconcile_opts) => {
+                reconcile(reconcile_opts, address_prefix).await
+            }
+            ClientSubcommand::ValsetConfirms(valset_confirms_opts) => {
+                valset_confirms(valset_confirms_opts, address_prefix).await
+            }
         },
         SubCommand::Keys(key_opts) => match key_opts.subcmd {
             KeysSubcommand::RegisterOrchestratorAddress(set_orchestrator_address_opts) => {
                 register_orchestrator_address(set_orchestrator_address_opts, address_prefix).await
             }
+            KeysSubcommand::PrintAddresses(print_addresses_opts) => {
+                print_addresses(print_addresses_opts, address_prefix)
+            }
+            KeysSubcommand::VerifySignature(verify_signature_opts) => {
+                verify_signature(verify_signature_opts)
+            }
         },
         SubCommand::Orchestrator(orchestrator_opts) => {
             orchestrator(orchestrator_opts, address_prefix, config).await
         }
         SubCommand::Relayer(relayer_opts) => {
-            relayer(relayer_opts, address_prefix, &config.relayer).await
+            relayer(
+                relayer_opts,
+                address_prefix,
+                &config.relayer,
+                &config.dns,
+                &config.rpc,
+            )
+            .await
         }
+        SubCommand::Monitor(monitor_opts) => monitor(monitor_opts, address_prefix, &config).await,
         SubCommand::Init(init_opts) => init_config(init_opts, home_dir),
+        SubCommand::ExportConfig(_) => export_config(&home_dir).await,
         SubCommand::Gov(gov_opts) => match gov_opts.subcmd {
             GovSubcommand::Submit(submit_opts) => match submit_opts {
                 GovSubmitSubcommand::IbcMetadata(opts) => {
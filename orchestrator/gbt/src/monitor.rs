@@ -0,0 +1,304 @@
+//! A keyless monitor for the whole bridge. It runs the status checking portions of the
+//! Orchestrator loops, block heights, valset lag, pending batches and logic calls and valset
+//! participation, without signing or relaying anything, and reports them through the metrics exporter
+
+use std::time::Duration;
+
+use cosmos_gravity::query::{
+    get_all_valset_confirms, get_current_valset, get_gravity_params,
+    get_last_event_nonce_for_validator, get_latest_logic_calls, get_latest_transaction_batches,
+    get_latest_valsets, get_transaction_batch_signatures,
+};
+use ethereum_gravity::utils::{get_event_nonce, get_valset_nonce};
+use gravity_proto::gravity::{
+    query_client::QueryClient as GravityQueryClient, QueryDelegateKeysByEthAddress,
+};
+use gravity_utils::{
+    clarity::{constants::ZERO_ADDRESS, Address as EthAddress},
+    connection_prep::{create_rpc_connections, resolve_rpc_url, wait_for_cosmos_node_ready},
+    deep_space::{address::Address as CosmosAddress, client::ChainStatus, Contact},
+    error::GravityError,
+    types::{gravity_power_to_percent, Confirm, GravityBridgeToolsConfig, Valset},
+    web30::client::Web3,
+};
+use metrics_exporter::{
+    metrics_latest, metrics_server, metrics_warnings_counter, start_statsd_sink,
+};
+use orchestrator::oracle_resync::get_block_of_event_nonce;
+use tokio::time::sleep;
+use tonic::transport::Channel;
+
+use crate::{
+    args::MonitorOpts,
+    utils::{get_gravity_contract_address, TIMEOUT},
+};
+
+/// How often the monitor checks the state of the bridge
+pub const MONITOR_LOOP_SPEED: Duration = Duration::from_secs(30);
+
+pub async fn monitor(
+    args: MonitorOpts,
+    address_prefix: String,
+    config: &GravityBridgeToolsConfig,
+) -> Result<(), GravityError> {
+    let cosmos_grpc = resolve_rpc_url(&args.cosmos_grpc, &config.dns).await;
+    let ethereum_rpc = resolve_rpc_url(&args.ethereum_rpc, &config.dns).await;
+
+    let connections = create_rpc_connections(
+        address_prefix,
+        Some(cosmos_grpc),
+        Some(ethereum_rpc),
+        TIMEOUT,
+    )
+    .await;
+    let contact = connections.contact.unwrap();
+    let web3 = connections.web3.unwrap();
+    let mut grpc = connections.grpc.unwrap();
+
+    wait_for_cosmos_node_ready(&contact).await;
+
+    let params = get_gravity_params(&mut grpc).await?;
+    let gravity_contract_address =
+        get_gravity_contract_address(args.gravity_contract_address, &params)?;
+    info!(
+        "Starting Gravity monitor for contract {}",
+        gravity_contract_address
+    );
+
+    // the metrics are the whole point of the monitor, so the exporter is always started
+    if !config.metrics.metrics_enabled {
+        info!(
+            "Metrics are disabled in the config, the monitor is still serving them on {}",
+            config.metrics.metrics_bind
+        );
+    }
+    metrics_server(&config.metrics);
+    start_statsd_sink(&config.metrics);
+
+    let mut last_oracle_nonce = None;
+    loop {
+        let _ = tokio::join!(
+            async {
+                check_bridge_status(&contact, &web3, &mut grpc, gravity_contract_address).await;
+                if let Some(orchestrator_address) = args.orchestrator_address {
+                    check_oracle_progress(
+                        &contact,
+                        &web3,
+                        &mut grpc,
+                        gravity_contract_address,
+                        orchestrator_address,
+                        &mut last_oracle_nonce,
+                    )
+                    .await;
+                }
+            },
+            sleep(MONITOR_LOOP_SPEED)
+        );
+    }
+}
+
+/// Records the current state of the bridge as metrics, failures are logged and the
+/// affected metrics are left at their previous values
+async fn check_bridge_status(
+    contact: &Contact,
+    web3: &Web3,
+    grpc_client: &mut GravityQueryClient<Channel>,
+    gravity_contract_address: EthAddress,
+) {
+    match contact.get_chain_status().await {
+        Ok(ChainStatus::Moving { block_height }) => {
+            metrics_latest(block_height, "latest_cosmos_block")
+        }
+        Ok(ChainStatus::Syncing) | Ok(ChainStatus::WaitingToStart) => {
+            warn!("Cosmos node is not producing blocks");
+            metrics_warnings_counter(2, "Cosmos node is not producing blocks");
+        }
+        Err(e) => {
+            warn!("Could not contact Cosmos grpc {:?}", e);
+            metrics_warnings_counter(2, "Could not contact Cosmos grpc");
+        }
+    }
+
+    match web3.eth_block_number().await {
+        Ok(block) => metrics_latest(block.resize_to_u64(), "latest_eth_block"),
+        Err(e) => {
+            warn!("Could not contact Eth node {:?}", e);
+            metrics_warnings_counter(1, "Could not contact Eth node");
+        }
+    }
+
+    match get_event_nonce(gravity_contract_address, ZERO_ADDRESS, web3).await {
+        Ok(nonce) => metrics_latest(nonce, "ethereum_event_nonce"),
+        Err(e) => warn!("Failed to get the Ethereum event nonce {:?}", e),
+    }
+
+    let ethereum_valset_nonce =
+        get_valset_nonce(gravity_contract_address, ZERO_ADDRESS, web3).await;
+    let latest_valset = get_latest_valsets(grpc_client)
+        .await
+        .map(|valsets| valsets.into_iter().max_by_key(|v| v.nonce));
+    match (ethereum_valset_nonce, latest_valset) {
+        (Ok(ethereum_nonce), Ok(Some(latest_valset))) => {
+            metrics_latest(ethereum_nonce, "ethereum_valset_nonce");
+            metrics_latest(latest_valset.nonce, "cosmos_valset_nonce");
+            metrics_latest(
+                latest_valset.nonce.saturating_sub(ethereum_nonce),
+                "valset_nonce_lag",
+            );
+
+            match get_all_valset_confirms(grpc_client, latest_valset.nonce).await {
+                Ok(confirms) => {
+                    info!(
+                        "Valset {} has {}/{} confirms, Ethereum is on valset {}",
+                        latest_valset.nonce,
+                        confirms.len(),
+                        latest_valset.members.len(),
+                        ethereum_nonce
+                    );
+                    metrics_latest(confirms.len() as u64, "latest_valset_confirms");
+                    metrics_latest(latest_valset.members.len() as u64, "latest_valset_members");
+                    let missing_power = report_missing_signers(
+                        grpc_client,
+                        &format!("valset {}", latest_valset.nonce),
+                        &latest_valset,
+                        &confirms,
+                    )
+                    .await;
+                    metrics_latest(missing_power, "latest_valset_missing_power");
+                }
+                Err(e) => warn!("Failed to get valset confirms {:?}", e),
+            }
+        }
+        (Ok(_), Ok(None)) => trace!("No valsets on Cosmos yet"),
+        (Err(e), _) => warn!("Failed to get the Ethereum valset nonce {:?}", e),
+        (_, Err(e)) => warn!("Failed to get the latest valsets {:?}", e),
+    }
+
+    match get_latest_transaction_batches(grpc_client).await {
+        Ok(batches) => {
+            metrics_latest(batches.len() as u64, "pending_batches");
+            // the oldest batch is the one closest to timing out for lack of signatures
+            if let Some(oldest) = batches.iter().min_by_key(|b| b.nonce) {
+                let current_valset = get_current_valset(grpc_client).await;
+                let sigs = get_transaction_batch_signatures(
+                    grpc_client,
+                    oldest.nonce,
+                    oldest.token_contract,
+                )
+                .await;
+                match (current_valset, sigs) {
+                    (Ok(current_valset), Ok(sigs)) => {
+                        let missing_power = report_missing_signers(
+                            grpc_client,
+                            &format!("batch {}:{}", oldest.token_contract, oldest.nonce),
+                            &current_valset,
+                            &sigs,
+                        )
+                        .await;
+                        metrics_latest(missing_power, "oldest_batch_missing_power");
+                    }
+                    (Err(e), _) | (_, Err(e)) => {
+                        warn!("Failed to get the signers of the oldest batch {:?}", e)
+                    }
+                }
+            }
+        }
+        Err(e) => warn!("Failed to get the latest batches {:?}", e),
+    }
+
+    match get_latest_logic_calls(grpc_client).await {
+        Ok(calls) => metrics_latest(calls.len() as u64, "pending_logic_calls"),
+        Err(e) => warn!("Failed to get the latest logic calls {:?}", e),
+    }
+}
+
+/// Logs every member of `valset` that has not submitted one of `confirms` for `item`, along
+/// with their power and validator if it can be found, so operators can contact them.
+/// Returns the power that has not signed
+async fn report_missing_signers<T: Confirm>(
+    grpc_client: &mut GravityQueryClient<Channel>,
+    item: &str,
+    valset: &Valset,
+    confirms: &[T],
+) -> u64 {
+    let missing = valset.missing_signers(confirms);
+    let missing_power: u64 = missing.iter().map(|m| m.power).sum();
+    if missing.is_empty() {
+        return 0;
+    }
+    info!(
+        "{} validators holding {:.2}% of the power have not signed {}",
+        missing.len(),
+        gravity_power_to_percent(missing_power),
+        item
+    );
+    for member in missing {
+        let validator = grpc_client
+            .get_delegate_key_by_eth(QueryDelegateKeysByEthAddress {
+                eth_address: member.eth_address.to_string(),
+            })
+            .await
+            .map(|r| r.into_inner().validator_address)
+            .unwrap_or_else(|_| "unknown validator".to_string());
+        info!(
+            "{} ({}) with {:.2}% of the power has not signed {}",
+            validator,
+            member.eth_address,
+            gravity_power_to_percent(member.power),
+            item
+        );
+    }
+    missing_power
+}
+
+/// Reports how far the oracle of the validator with `orchestrator_address` has progressed,
+/// the search for its last checked block is only repeated when its event nonce changes
+async fn check_oracle_progress(
+    contact: &Contact,
+    web3: &Web3,
+    grpc_client: &mut GravityQueryClient<Channel>,
+    gravity_contract_address: EthAddress,
+    orchestrator_address: CosmosAddress,
+    last_oracle_nonce: &mut Option<u64>,
+) {
+    let nonce = match get_last_event_nonce_for_validator(
+        grpc_client,
+        orchestrator_address,
+        contact.get_prefix(),
+    )
+    .await
+    {
+        Ok(nonce) => nonce,
+        Err(e) => {
+            warn!(
+                "Failed to get the last event nonce of {} {:?}",
+                orchestrator_address, e
+            );
+            return;
+        }
+    };
+    metrics_latest(nonce, "orchestrator_last_event_nonce");
+    if *last_oracle_nonce == Some(nonce) {
+        return;
+    }
+
+    let block =
+        match get_block_of_event_nonce(nonce, orchestrator_address, gravity_contract_address, web3)
+            .await
+        {
+            Ok(block) => block,
+            Err(e) => {
+                warn!(
+                    "Failed to find the block of event nonce {} relayed by {} {:?}",
+                    nonce, orchestrator_address, e
+                );
+                return;
+            }
+        };
+    info!(
+        "Oracle of {} has relayed event nonce {} checked up to Ethereum block {}",
+        orchestrator_address, nonce, block
+    );
+    metrics_latest(block.resize_to_u64(), "orchestrator_last_checked_block");
+    *last_oracle_nonce = Some(nonce);
+}
@@ -1,24 +1,40 @@
 use std::{cmp::min, time::Duration};
 
-use cosmos_gravity::query::get_gravity_params;
+use cosmos_gravity::{
+    query::{get_current_valset, get_gravity_params},
+    send::check_chain_id,
+};
+use ethereum_gravity::{
+    message_signatures::{encode_valset_confirm, encode_valset_confirm_hashed},
+    utils::get_gravity_id,
+};
+use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
 use gravity_utils::{
-    clarity::constants::ZERO_ADDRESS,
+    clarity::PrivateKey as EthPrivateKey,
     connection_prep::{
-        check_delegate_addresses, check_for_eth, check_for_fee, create_rpc_connections,
-        wait_for_cosmos_node_ready,
+        check_delegate_addresses, check_eth_chain_id, check_fee_denom_exists, check_for_eth,
+        check_for_fee, check_gravity_id, create_rpc_connections, enable_grpc_compression,
+        initialize_account, resolve_rpc_url, wait_for_cosmos_node_ready_with_timeout,
     },
     error::GravityError,
     get_block_delay, get_expected_block_delay,
     get_with_retry::get_net_version_with_retry,
+    slashing_alerts::set_slashing_alert_command,
     types::{BatchRequestMode, GravityBridgeToolsConfig},
     TEST_ETH_CHAIN_ID, USE_FINALIZATION,
 };
-use metrics_exporter::metrics_server;
+use metrics_exporter::{metrics_server, start_statsd_sink};
 use orchestrator::main_loop::{
     orchestrator_main_loop, ETH_ORACLE_LOOP_SPEED, ETH_SIGNER_LOOP_SPEED,
 };
+use tonic::transport::Channel;
 
-use crate::{args::OrchestratorOpts, utils::print_relaying_explanation};
+use crate::{
+    admin_socket::start_admin_socket,
+    args::OrchestratorOpts,
+    instance_lock::InstanceLock,
+    utils::{get_gravity_contract_address, print_relaying_explanation},
+};
 
 pub async fn orchestrator(
     args: OrchestratorOpts,
@@ -26,8 +42,8 @@ pub async fn orchestrator(
     config: GravityBridgeToolsConfig,
 ) -> Result<(), GravityError> {
     let fee = args.fees;
-    let cosmos_grpc = args.cosmos_grpc;
-    let ethereum_rpc = args.ethereum_rpc;
+    let cosmos_grpc = resolve_rpc_url(&args.cosmos_grpc, &config.dns).await;
+    let ethereum_rpc = resolve_rpc_url(&args.ethereum_rpc, &config.dns).await;
     let ethereum_key = args.ethereum_key;
     let cosmos_key = args.cosmos_phrase;
 
@@ -47,6 +63,9 @@ pub async fn orchestrator(
     .await;
 
     let mut grpc = connections.grpc.clone().unwrap();
+    if config.orchestrator.grpc_compression {
+        grpc = enable_grpc_compression(grpc).await;
+    }
     let contact = connections.contact.clone().unwrap();
     let web3 = connections.web3.clone().unwrap();
 
@@ -59,7 +78,20 @@ pub async fn orchestrator(
         "Ethereum Address: {} Cosmos Address {}",
         public_eth_key, public_cosmos_key
     );
+    // held for as long as the Orchestrator runs, one per duty so that the duties can be split
+    // between two Orchestrators on the same host
+    let mut instance_locks = Vec::new();
+    for (duty, enabled) in [
+        ("oracle", config.orchestrator.oracle_enabled),
+        ("signer", config.orchestrator.signer_enabled),
+        ("relayer", config.orchestrator.relayer_enabled),
+    ] {
+        if enabled {
+            instance_locks.push(InstanceLock::acquire(public_eth_key, duty)?);
+        }
+    }
 
+    check_eth_chain_id(&web3, config.rpc.expected_eth_chain_id).await?;
     // so we can double check in the logs that there is no configuration problem
     let net_version = get_net_version_with_retry(&web3).await;
     let block_delay = get_block_delay(&web3).await;
@@ -83,7 +115,12 @@ pub async fn orchestrator(
     // check if the cosmos node is syncing, if so wait for it
     // we can't move any steps above this because they may fail on an incorrect
     // historic chain state while syncing occurs
-    wait_for_cosmos_node_ready(&contact).await;
+    let not_ready_timeout = match config.orchestrator.cosmos_not_ready_timeout {
+        0 => None,
+        timeout => Some(Duration::from_secs(timeout)),
+    };
+    wait_for_cosmos_node_ready_with_timeout(&contact, not_ready_timeout).await?;
+    check_chain_id(&contact).await?;
 
     // check if the delegate addresses are correctly configured
     check_delegate_addresses(
@@ -94,7 +131,17 @@ pub async fn orchestrator(
     )
     .await?;
 
+    if fee.amount.is_zero() && config.orchestrator.auto_initialize_account {
+        match args.funding_phrase {
+            Some(funding_key) => {
+                initialize_account(public_cosmos_key, funding_key, &contact).await?
+            }
+            None => warn!("auto_initialize_account is set but no --funding-phrase was provided"),
+        }
+    }
+
     // check if we actually have the promised balance of tokens to pay fees
+    check_fee_denom_exists(&fee, &contact).await?;
     check_for_fee(&fee, public_cosmos_key, &contact).await?;
     check_for_eth(public_eth_key, &web3).await?;
 
@@ -103,27 +150,17 @@ pub async fn orchestrator(
         .await
         .expect("Failed to get Gravity Bridge module parameters!");
 
-    // get the gravity contract address, if not provided
-    let contract_address = if let Some(c) = args.gravity_contract_address {
-        c
-    } else {
-        let c = params.bridge_ethereum_address.parse();
-        match c {
-            Ok(v) => {
-                if v == ZERO_ADDRESS {
-                    return Err(GravityError::UnrecoverableError(
-                        "The Gravity address is not yet set as a chain parameter! You must specify --gravity-contract-address".into(),
-                    ));
-                }
-                c.unwrap()
-            }
-            Err(_) => {
-                return Err(GravityError::UnrecoverableError(
-                    "The Gravity address is not yet set as a chain parameter! You must specify --gravity-contract-address".into(),
-                ));
-            }
-        }
-    };
+    let contract_address = get_gravity_contract_address(args.gravity_contract_address, &params)?;
+
+    let ethereum_gravity_id = get_gravity_id(contract_address, public_eth_key, &web3).await?;
+    check_gravity_id(
+        config.orchestrator.expected_gravity_id.as_deref(),
+        &params.gravity_id,
+        &ethereum_gravity_id,
+    )?;
+    if config.orchestrator.signer_enabled && config.orchestrator.signing_self_test {
+        signing_self_test(&mut grpc, ethereum_key, params.gravity_id.clone()).await?;
+    }
 
     if config.orchestrator.relayer_enabled {
         // setup and explain relayer settings
@@ -133,19 +170,24 @@ pub async fn orchestrator(
         } else {
             print_relaying_explanation(&config.relayer, false)
         }
+        if let Some(path) = &config.relayer.admin_socket {
+            start_admin_socket(path)?;
+        }
     }
 
     // Start monitiring if enabled on config.toml
     if config.metrics.metrics_enabled {
         metrics_server(&config.metrics);
     };
+    start_statsd_sink(&config.metrics);
+    set_slashing_alert_command(config.orchestrator.slashing_alert_command.clone());
 
     orchestrator_main_loop(
         cosmos_key,
         ethereum_key,
         connections.web3.unwrap(),
         connections.contact.unwrap(),
-        connections.grpc.unwrap(),
+        grpc,
         contract_address,
         params.gravity_id,
         fee,
@@ -153,3 +195,32 @@ pub async fn orchestrator(
     )
     .await
 }
+
+/// Signs a confirm for the current valset exactly as the Eth signer would and checks that the
+/// signature recovers to our Ethereum address, catching key or encoding problems before a real
+/// confirm is submitted. Nothing is broadcast
+async fn signing_self_test(
+    grpc: &mut GravityQueryClient<Channel>,
+    ethereum_key: EthPrivateKey,
+    gravity_id: String,
+) -> Result<(), GravityError> {
+    let valset = get_current_valset(grpc).await?;
+    let message = encode_valset_confirm(gravity_id.clone(), &valset);
+    let signature = ethereum_key.sign_ethereum_msg(&message);
+    let checkpoint = encode_valset_confirm_hashed(gravity_id, &valset);
+    match signature.recover(&checkpoint) {
+        Ok(address) if address == ethereum_key.to_address() => {
+            info!("Signing self test passed for {}", address);
+            Ok(())
+        }
+        Ok(address) => Err(GravityError::UnrecoverableError(format!(
+            "Signing self test failed, a confirm signed by {} recovered to {}",
+            ethereum_key.to_address(),
+            address
+        ))),
+        Err(e) => Err(GravityError::UnrecoverableError(format!(
+            "Signing self test failed, could not recover the signer of a confirm {:?}",
+            e
+        ))),
+    }
+}
@@ -1,23 +1,30 @@
-use cosmos_gravity::query::get_gravity_params;
+use cosmos_gravity::{query::get_gravity_params, send::check_chain_id};
+use ethereum_gravity::utils::get_gravity_id;
 use gravity_utils::{
-    clarity::constants::ZERO_ADDRESS,
     connection_prep::{
-        check_for_eth, check_for_fee, create_rpc_connections, wait_for_cosmos_node_ready,
+        check_eth_chain_id, check_fee_denom_exists, check_for_eth, check_for_fee, check_gravity_id,
+        create_rpc_connections, resolve_rpc_url, wait_for_cosmos_node_ready,
     },
     error::GravityError,
-    types::{BatchRequestMode, RelayerConfig},
+    types::{BatchRequestMode, DnsConfig, RelayerConfig, RpcConfig},
 };
 use relayer::main_loop::{relayer_main_loop, TIMEOUT};
 
-use crate::{args::RelayerOpts, utils::print_relaying_explanation};
+use crate::{
+    admin_socket::start_admin_socket,
+    args::RelayerOpts,
+    utils::{get_gravity_contract_address, print_relaying_explanation},
+};
 
 pub async fn relayer(
     args: RelayerOpts,
     address_prefix: String,
     config: &RelayerConfig,
+    dns_config: &DnsConfig,
+    rpc_config: &RpcConfig,
 ) -> Result<(), GravityError> {
-    let cosmos_grpc = args.cosmos_grpc;
-    let ethereum_rpc = args.ethereum_rpc;
+    let cosmos_grpc = resolve_rpc_url(&args.cosmos_grpc, dns_config).await;
+    let ethereum_rpc = resolve_rpc_url(&args.ethereum_rpc, dns_config).await;
     let ethereum_key = args.ethereum_key;
     let cosmos_key = args.cosmos_phrase;
 
@@ -41,6 +48,8 @@ pub async fn relayer(
     // we can't move any steps above this because they may fail on an incorrect
     // historic chain state while syncing occurs
     wait_for_cosmos_node_ready(&contact).await;
+    check_chain_id(&contact).await?;
+    check_eth_chain_id(&web3, rpc_config.expected_eth_chain_id).await?;
     check_for_eth(public_eth_key, &web3).await?;
 
     // get the gravity parameters
@@ -48,33 +57,17 @@ pub async fn relayer(
         .await
         .expect("Failed to get Gravity Bridge module parameters!");
 
-    // get the gravity contract address, if not provided
-    let contract_address = if let Some(c) = args.gravity_contract_address {
-        c
-    } else {
-        let c = params.bridge_ethereum_address.parse();
-
-        match c {
-            Ok(v) => {
-                if v == ZERO_ADDRESS {
-                    return Err(GravityError::UnrecoverableError(
-                        "The Gravity address is not yet set as a chain parameter! You must specify --gravity-contract-address".into(),
-                    ));
-                }
-
-                v
-            }
-            Err(_) => {
-                return Err(GravityError::UnrecoverableError("The Gravity address is not yet set as a chain parameter! You must specify --gravity-contract-address".into()));
-            }
-        }
-    };
+    let contract_address = get_gravity_contract_address(args.gravity_contract_address, &params)?;
     info!("Gravity contract address {}", contract_address);
 
+    let ethereum_gravity_id = get_gravity_id(contract_address, public_eth_key, &web3).await?;
+    check_gravity_id(None, &params.gravity_id, &ethereum_gravity_id)?;
+
     // setup and explain relayer settings
     if let Some(fee) = args.fees.clone() {
         if config.batch_request_mode != BatchRequestMode::None {
             let public_cosmos_key = cosmos_key.to_address(&contact.get_prefix()).unwrap();
+            check_fee_denom_exists(&fee, &contact).await?;
             check_for_fee(&fee, public_cosmos_key, &contact).await?;
             print_relaying_explanation(config, true)
         } else {
@@ -83,6 +76,9 @@ pub async fn relayer(
     } else {
         print_relaying_explanation(config, false)
     }
+    if let Some(path) = &config.admin_socket {
+        start_admin_socket(path)?;
+    }
 
     relayer_main_loop(
         ethereum_key,
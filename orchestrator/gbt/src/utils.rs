@@ -1,9 +1,41 @@
 use std::time::Duration;
 
-use gravity_utils::types::{BatchRequestMode, RelayerConfig, ValsetRelayingMode};
+use gravity_proto::gravity::Params;
+use gravity_utils::{
+    clarity::{constants::ZERO_ADDRESS, Address as EthAddress},
+    error::GravityError,
+    types::{BatchRelayOrder, BatchRequestMode, RelayerConfig, ValsetRelayingMode},
+};
 
 pub const TIMEOUT: Duration = Duration::from_secs(60);
 
+/// Returns the Gravity contract address recorded in the chain params, or `override_address`
+/// if one was provided. An override that differs from the chain params is almost always a
+/// misconfiguration, it is still used but a warning is logged
+pub fn get_gravity_contract_address(
+    override_address: Option<EthAddress>,
+    params: &Params,
+) -> Result<EthAddress, GravityError> {
+    let chain_address = match params.bridge_ethereum_address.parse() {
+        Ok(v) if v != ZERO_ADDRESS => Some(v),
+        _ => None,
+    };
+    match (override_address, chain_address) {
+        (Some(o), Some(c)) if o != c => {
+            warn!(
+                "The provided Gravity contract address {} differs from the address {} in the chain params, check your configuration",
+                o, c
+            );
+            Ok(o)
+        }
+        (Some(o), _) => Ok(o),
+        (None, Some(c)) => Ok(c),
+        (None, None) => Err(GravityError::UnrecoverableError(
+            "The Gravity address is not yet set as a chain parameter! You must specify --gravity-contract-address".into(),
+        )),
+    }
+}
+
 /// Explains the relaying config to users
 pub fn print_relaying_explanation(input: &RelayerConfig, batch_requests: bool) {
     info!("Relaying from Cosmos => Ethereum is enabled, this will cost ETH");
@@ -12,7 +44,7 @@ pub fn print_relaying_explanation(input: &RelayerConfig, batch_requests: bool) {
             "This relayer will only relay validator set updates if they have a profitable reward with at least {} margin", margin
         ),
         ValsetRelayingMode::Altruistic => info!(
-            "This relayer will relay validator set updates altruistically if required by the network"
+            "This relayer will relay validator set updates altruistically if required by the network, or if their reward covers the cost"
         ),
         ValsetRelayingMode::EveryValset => warn!(
             "This relayer will relay every validator set update. This will cost a lot of ETH!"
@@ -40,4 +72,50 @@ pub fn print_relaying_explanation(input: &RelayerConfig, batch_requests: bool) {
         gravity_utils::types::BatchRelayingMode::ProfitableWithWhitelist { margin, whitelist } =>
             info!("This relayer will relay profitable matches with {} margin, and the following tokens with the provided amounts {:?}", margin, whitelist)
     }
+    if let (Some(tokens), true) = (&input.batch_request_tokens, batch_requests) {
+        info!(
+            "Batches will only be requested for the following tokens {:?}",
+            tokens
+        )
+    }
+    if let Some(tokens) = &input.batch_relay_tokens {
+        info!(
+            "Batches will only be relayed for the following tokens {:?}",
+            tokens
+        )
+    }
+    if input.batch_relay_order == BatchRelayOrder::FeePriority {
+        info!("Batches with the most valuable fees over their gas cost will be relayed first")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_gravity_contract_address() {
+        let chain: EthAddress = "0x6Bd41fCdF129297c3524395d669c0865b3CA85B2"
+            .parse()
+            .unwrap();
+        let other: EthAddress = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"
+            .parse()
+            .unwrap();
+        let unset = Params::default();
+        let set = Params {
+            bridge_ethereum_address: chain.to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(get_gravity_contract_address(None, &set).unwrap(), chain);
+        assert_eq!(
+            get_gravity_contract_address(Some(other), &set).unwrap(),
+            other
+        );
+        assert_eq!(
+            get_gravity_contract_address(Some(other), &unset).unwrap(),
+            other
+        );
+        assert!(get_gravity_contract_address(None, &unset).is_err());
+    }
 }
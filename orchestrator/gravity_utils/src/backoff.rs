@@ -0,0 +1,84 @@
+//! A shared retry policy, exponential backoff with optional jitter. Retrying paths keep one
+//! `Backoff` per operation, sleeping for `next_delay()` after each failure and calling
+//! `reset()` after a success so the next failure starts again from the base delay.
+
+use std::time::Duration;
+
+use rand::Rng;
+
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    base: Duration,
+    max: Duration,
+    multiplier: f64,
+    jitter: f64,
+    current: Duration,
+}
+
+impl Backoff {
+    /// Creates a backoff starting at `base` and doubling up to `max`, with no jitter
+    pub fn new(base: Duration, max: Duration) -> Self {
+        Backoff {
+            base,
+            max,
+            multiplier: 2.0,
+            jitter: 0.0,
+            current: base,
+        }
+    }
+
+    /// Sets how much the delay grows after each failure, 1.0 gives a constant delay
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Sets the fraction, between 0 and 1, of each delay that is randomly taken off so
+    /// that processes failing at the same moment don't all retry at the same moment
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Returns how long to wait before the next attempt and grows the delay for the one after
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.current;
+        self.current = self.current.mul_f64(self.multiplier).min(self.max);
+        if self.jitter > 0.0 {
+            let cut = rand::thread_rng().gen_range(0.0..=self.jitter);
+            delay.mul_f64(1.0 - cut)
+        } else {
+            delay
+        }
+    }
+
+    /// Starts the delays over from the base delay, call this after a success
+    pub fn reset(&mut self) {
+        self.current = self.base;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(5));
+        assert_eq!(backoff.next_delay(), Duration::from_secs(1));
+        assert_eq!(backoff.next_delay(), Duration::from_secs(2));
+        assert_eq!(backoff.next_delay(), Duration::from_secs(4));
+        assert_eq!(backoff.next_delay(), Duration::from_secs(5));
+        assert_eq!(backoff.next_delay(), Duration::from_secs(5));
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), Duration::from_secs(1));
+
+        let mut constant = Backoff::new(Duration::from_secs(3), Duration::from_secs(3))
+            .with_multiplier(1.0)
+            .with_jitter(0.5);
+        for _ in 0..20 {
+            let delay = constant.next_delay();
+            assert!(delay >= Duration::from_millis(1500) && delay <= Duration::from_secs(3));
+        }
+    }
+}
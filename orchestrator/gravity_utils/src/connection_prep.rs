@@ -2,32 +2,135 @@
 //! It's a common problem to have conflicts between ipv4 and ipv6 localhost and this module is first and foremost supposed to resolve that problem
 //! by trying more than one thing to handle potentially misconfigured inputs.
 
-use std::time::Duration;
+use std::{
+    net::IpAddr,
+    process::exit,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
 
-use clarity::Address as EthAddress;
+use clarity::{u256, Address as EthAddress, Uint256};
 use deep_space::{
     client::ChainStatus, error::CosmosGrpcError, Address as CosmosAddress, Coin, Contact,
+    PrivateKey as CosmosPrivateKey,
+};
+use gravity_proto::{
+    cosmos_sdk_proto::cosmos::bank::v1beta1::{
+        query_client::QueryClient as BankQueryClient, QuerySupplyOfRequest,
+    },
+    gravity::{
+        query_client::QueryClient as GravityQueryClient, QueryDelegateKeysByEthAddress,
+        QueryDelegateKeysByOrchestratorAddress, QueryParamsRequest,
+    },
+};
+use tokio::{
+    net::lookup_host,
+    time::{sleep as delay_for, timeout as with_timeout},
 };
-use gravity_proto::gravity::{
-    query_client::QueryClient as GravityQueryClient, QueryDelegateKeysByEthAddress,
-    QueryDelegateKeysByOrchestratorAddress,
+use tonic::{
+    transport::{Channel, Endpoint},
+    Code, Status,
 };
-use tokio::time::sleep as delay_for;
-use tonic::transport::Channel;
 use url::Url;
-use web30::client::Web3;
+use web30::{client::Web3, jsonrpc::error::Web3Error};
 
 use crate::{
     error::GravityError,
-    get_with_retry::{get_balances_with_retry, get_eth_balances_with_retry},
+    get_with_retry::{get_balances_with_retry, get_eth_balances_with_retry, RETRY_TIME},
+    types::{AddressFamily, DnsConfig},
 };
 
+/// If connection failures in create_rpc_connections should exit the process with an
+/// error message rather than panic
+static EXIT_ON_CONNECTION_FAILURE: AtomicBool = AtomicBool::new(false);
+
+/// Makes connection failures in create_rpc_connections log a clear error and exit with a
+/// non-zero code instead of panicking with a backtrace
+pub fn set_exit_on_connection_failure(enabled: bool) {
+    EXIT_ON_CONNECTION_FAILURE.store(enabled, Ordering::Relaxed);
+}
+
+/// The user-agent sent with gRPC requests, None uses the tonic default
+static USER_AGENT: Mutex<Option<String>> = Mutex::new(None);
+
+/// Sets the user-agent of the gRPC clients created by create_rpc_connections and connect_grpc
+pub fn set_user_agent(user_agent: Option<String>) {
+    *USER_AGENT.lock().unwrap() = user_agent;
+}
+
+/// Connects a Gravity gRPC client to `url`, sending the configured user-agent if there is one
+pub async fn connect_grpc(
+    url: String,
+) -> Result<GravityQueryClient<Channel>, tonic::transport::Error> {
+    let mut endpoint = Endpoint::from_shared(url)?;
+    if let Some(user_agent) = USER_AGENT.lock().unwrap().clone() {
+        endpoint = endpoint.user_agent(user_agent)?;
+    }
+    Ok(GravityQueryClient::new(endpoint.connect().await?))
+}
+
+/// The timeout in milliseconds of each fallback url tried by create_rpc_connections
+static PROBE_TIMEOUT_MS: AtomicU64 = AtomicU64::new(5000);
+
+/// Sets the timeout of the fallback urls tried by create_rpc_connections when the configured
+/// url can not be reached, the full timeout is only used for the connection that is returned
+pub fn set_probe_timeout(timeout: Duration) {
+    PROBE_TIMEOUT_MS.store(timeout.as_millis() as u64, Ordering::Relaxed);
+}
+
+/// The probe timeout, never longer than the full `timeout` of the connection
+fn probe_timeout(timeout: Duration) -> Duration {
+    Duration::from_millis(PROBE_TIMEOUT_MS.load(Ordering::Relaxed)).min(timeout)
+}
+
+/// Tries a fallback gRPC url, giving up after the probe timeout
+async fn probe_grpc(url: String, timeout: Duration) -> Result<GravityQueryClient<Channel>, String> {
+    match with_timeout(probe_timeout(timeout), connect_grpc(url)).await {
+        Ok(Ok(client)) => Ok(client),
+        Ok(Err(e)) => Err(format!("{:?}", e)),
+        Err(_) => Err("probe timed out".to_string()),
+    }
+}
+
+/// Tries a fallback Ethereum url with the probe timeout, if it responds the returned
+/// client uses the full `timeout`
+async fn probe_web3(url: &str, timeout: Duration) -> Result<Web3, Web3Error> {
+    Web3::new(url, probe_timeout(timeout))
+        .eth_block_number()
+        .await?;
+    Ok(Web3::new(url, timeout))
+}
+
+/// Handles an unrecoverable failure to connect to an RPC server
+fn connection_failure(message: String) -> ! {
+    if EXIT_ON_CONNECTION_FAILURE.load(Ordering::Relaxed) {
+        error!("{}", message);
+        exit(1);
+    }
+    panic!("{}", message)
+}
+
 pub struct Connections {
     pub web3: Option<Web3>,
     pub grpc: Option<GravityQueryClient<Channel>>,
     pub contact: Option<Contact>,
 }
 
+/// Returns the IPv6 and IPv4 loopback urls tried when connecting to `localhost` fails,
+/// keeping the scheme and port of the original url
+fn localhost_fallback_urls(url: &Url) -> (String, String) {
+    let port = url.port_or_known_default().unwrap_or(80);
+    // this should be http or https
+    let prefix = url.scheme();
+    // IPv6 addresses must be bracketed in urls to separate them from the port
+    let ipv6_url = format!("{prefix}://[::1]:{port}");
+    let ipv4_url = format!("{prefix}://127.0.0.1:{port}");
+    (ipv6_url, ipv4_url)
+}
+
 /// Returns the three major RPC connections required for Gravity
 /// operation in a error resilient manner. TODO find some way to generalize
 /// this so that it's less ugly
@@ -41,12 +144,13 @@ pub async fn create_rpc_connections(
     let mut grpc = None;
     let mut contact = None;
     if let Some(grpc_url) = grpc_url {
-        let url = Url::parse(&grpc_url)
-            .unwrap_or_else(|_| panic!("Invalid Cosmos gRPC url {}", grpc_url));
+        let url = Url::parse(&grpc_url).unwrap_or_else(|_| {
+            connection_failure(format!("Invalid Cosmos gRPC url {}", grpc_url))
+        });
         check_scheme(&url, &grpc_url);
         let cosmos_grpc_url = grpc_url.trim_end_matches('/').to_string();
         // try the base url first.
-        let try_base = GravityQueryClient::connect(cosmos_grpc_url.clone()).await;
+        let try_base = connect_grpc(cosmos_grpc_url.clone()).await;
         match try_base {
             // it worked, lets go!
             Ok(val) => {
@@ -60,13 +164,9 @@ pub async fn create_rpc_connections(
                     e
                 );
                 if grpc_url.to_lowercase().contains("localhost") {
-                    let port = url.port().unwrap_or(80);
-                    // this should be http or https
-                    let prefix = url.scheme();
-                    let ipv6_url = format!("{prefix}://::1:{port}");
-                    let ipv4_url = format!("{prefix}://127.0.0.1:{port}");
-                    let ipv6 = GravityQueryClient::connect(ipv6_url.clone()).await;
-                    let ipv4 = GravityQueryClient::connect(ipv4_url.clone()).await;
+                    let (ipv6_url, ipv4_url) = localhost_fallback_urls(&url);
+                    let ipv6 = probe_grpc(ipv6_url.clone(), timeout).await;
+                    let ipv4 = probe_grpc(ipv4_url.clone(), timeout).await;
                     warn!("Trying fallback urls {} {}", ipv6_url, ipv4_url);
                     match (ipv4, ipv6) {
                         (Ok(v), Err(_)) => {
@@ -80,17 +180,20 @@ pub async fn create_rpc_connections(
                             grpc = Some(v)
                         },
                         (Ok(_), Ok(_)) => panic!("This should never happen? Why didn't things work the first time?"),
-                        (Err(_), Err(_)) => panic!("Could not connect to Cosmos gRPC, are you sure it's running and on the specified port? {}", grpc_url)
+                        (Err(_), Err(_)) => connection_failure(format!("Could not connect to Cosmos gRPC, are you sure it's running and on the specified port? {}", grpc_url))
                     }
                 } else if url.port().is_none() || url.scheme() == "http" {
                     let body = url.host_str().unwrap_or_else(|| {
-                        panic!("Cosmos gRPC url contains no host? {}", grpc_url)
+                        connection_failure(format!(
+                            "Cosmos gRPC url contains no host? {}",
+                            grpc_url
+                        ))
                     });
                     // transparently upgrade to https if available, we can't transparently downgrade for obvious security reasons
                     let https_on_80_url = format!("https://{body}:80");
                     let https_on_443_url = format!("https://{body}:443");
-                    let https_on_80 = GravityQueryClient::connect(https_on_80_url.clone()).await;
-                    let https_on_443 = GravityQueryClient::connect(https_on_443_url.clone()).await;
+                    let https_on_80 = probe_grpc(https_on_80_url.clone(), timeout).await;
+                    let https_on_443 = probe_grpc(https_on_443_url.clone(), timeout).await;
                     warn!(
                         "Trying fallback urls {} {}",
                         https_on_443_url, https_on_80_url
@@ -107,17 +210,18 @@ pub async fn create_rpc_connections(
                             grpc = Some(v)
                         },
                         (Ok(_), Ok(_)) => panic!("This should never happen? Why didn't things work the first time?"),
-                        (Err(_), Err(_)) => panic!("Could not connect to Cosmos gRPC, are you sure it's running and on the specified port? {}", grpc_url)
+                        (Err(_), Err(_)) => connection_failure(format!("Could not connect to Cosmos gRPC, are you sure it's running and on the specified port? {}", grpc_url))
                     }
                 } else {
-                    panic!("Could not connect to Cosmos gRPC! please check your grpc url {} for errors {:?}", grpc_url, e)
+                    connection_failure(format!("Could not connect to Cosmos gRPC! please check your grpc url {} for errors {:?}", grpc_url, e))
                 }
             }
         }
     }
     if let Some(eth_rpc_url) = eth_rpc_url {
-        let url = Url::parse(&eth_rpc_url)
-            .unwrap_or_else(|_| panic!("Invalid Ethereum RPC url {}", eth_rpc_url));
+        let url = Url::parse(&eth_rpc_url).unwrap_or_else(|_| {
+            connection_failure(format!("Invalid Ethereum RPC url {}", eth_rpc_url))
+        });
         check_scheme(&url, &eth_rpc_url);
         let eth_url = eth_rpc_url.trim_end_matches('/');
         let base_web30 = Web3::new(eth_url, timeout);
@@ -132,57 +236,52 @@ pub async fn create_rpc_connections(
                     e
                 );
                 if eth_url.to_lowercase().contains("localhost") {
-                    let port = url.port().unwrap_or(80);
-                    // this should be http or https
-                    let prefix = url.scheme();
-                    let ipv6_url = format!("{prefix}://::1:{port}");
-                    let ipv4_url = format!("{prefix}://127.0.0.1:{port}");
-                    let ipv6_web3 = Web3::new(&ipv6_url, timeout);
-                    let ipv4_web3 = Web3::new(&ipv4_url, timeout);
-                    let ipv6_test = ipv6_web3.eth_block_number().await;
-                    let ipv4_test = ipv4_web3.eth_block_number().await;
+                    let (ipv6_url, ipv4_url) = localhost_fallback_urls(&url);
+                    let ipv6_test = probe_web3(&ipv6_url, timeout).await;
+                    let ipv4_test = probe_web3(&ipv4_url, timeout).await;
                     warn!("Trying fallback urls {} {}", ipv6_url, ipv4_url);
                     match (ipv4_test, ipv6_test) {
-                        (Ok(_), Err(_)) => {
+                        (Ok(v), Err(_)) => {
                             info!("Url fallback succeeded, your Ethereum rpc url {} has been corrected to {}", eth_rpc_url, ipv4_url);
-                            web3 = Some(ipv4_web3)
+                            web3 = Some(v)
                         }
-                        (Err(_), Ok(_)) => {
+                        (Err(_), Ok(v)) => {
                             info!("Url fallback succeeded, your Ethereum  rpc url {} has been corrected to {}", eth_rpc_url, ipv6_url);
-                            web3 = Some(ipv6_web3)
+                            web3 = Some(v)
                         },
                         (Ok(_), Ok(_)) => panic!("This should never happen? Why didn't things work the first time?"),
-                        (Err(_), Err(_)) => panic!("Could not connect to Ethereum rpc, are you sure it's running and on the specified port? {}", eth_rpc_url)
+                        (Err(_), Err(_)) => connection_failure(format!("Could not connect to Ethereum rpc, are you sure it's running and on the specified port? {}", eth_rpc_url))
                     }
                 } else if url.port().is_none() || url.scheme() == "http" {
                     let body = url.host_str().unwrap_or_else(|| {
-                        panic!("Ethereum rpc url contains no host? {}", eth_rpc_url)
+                        connection_failure(format!(
+                            "Ethereum rpc url contains no host? {}",
+                            eth_rpc_url
+                        ))
                     });
                     // transparently upgrade to https if available, we can't transparently downgrade for obvious security reasons
                     let https_on_80_url = format!("https://{body}:80");
                     let https_on_443_url = format!("https://{body}:443");
-                    let https_on_80_web3 = Web3::new(&https_on_80_url, timeout);
-                    let https_on_443_web3 = Web3::new(&https_on_443_url, timeout);
-                    let https_on_80_test = https_on_80_web3.eth_block_number().await;
-                    let https_on_443_test = https_on_443_web3.eth_block_number().await;
+                    let https_on_80_test = probe_web3(&https_on_80_url, timeout).await;
+                    let https_on_443_test = probe_web3(&https_on_443_url, timeout).await;
                     warn!(
                         "Trying fallback urls {} {}",
                         https_on_443_url, https_on_80_url
                     );
                     match (https_on_80_test, https_on_443_test) {
-                        (Ok(_), Err(_)) => {
+                        (Ok(v), Err(_)) => {
                             info!("Https upgrade succeeded, your Ethereum rpc url {} has been corrected to {}", eth_rpc_url, https_on_80_url);
-                            web3 = Some(https_on_80_web3)
+                            web3 = Some(v)
                         },
-                        (Err(_), Ok(_)) => {
+                        (Err(_), Ok(v)) => {
                             info!("Https upgrade succeeded, your Ethereum rpc url {} has been corrected to {}", eth_rpc_url, https_on_443_url);
-                            web3 = Some(https_on_443_web3)
+                            web3 = Some(v)
                         },
                         (Ok(_), Ok(_)) => panic!("This should never happen? Why didn't things work the first time?"),
-                        (Err(_), Err(_)) => panic!("Could not connect to Ethereum rpc, are you sure it's running and on the specified port? {}", eth_rpc_url)
+                        (Err(_), Err(_)) => connection_failure(format!("Could not connect to Ethereum rpc, are you sure it's running and on the specified port? {}", eth_rpc_url))
                     }
                 } else {
-                    panic!("Could not connect to Ethereum rpc! please check your grpc url {} for errors {:?}", eth_rpc_url, e)
+                    connection_failure(format!("Could not connect to Ethereum rpc! please check your grpc url {} for errors {:?}", eth_rpc_url, e))
                 }
             }
         }
@@ -195,13 +294,88 @@ pub async fn create_rpc_connections(
     }
 }
 
+/// Enables gzip compression of gRPC responses on the provided client, this reduces
+/// bandwidth on event heavy chains at the cost of some CPU. The Cosmos node is probed
+/// with a compressed query first, if that fails the uncompressed client is returned
+pub async fn enable_grpc_compression(
+    grpc: GravityQueryClient<Channel>,
+) -> GravityQueryClient<Channel> {
+    let mut compressed = grpc.clone().accept_gzip();
+    match compressed.params(QueryParamsRequest {}).await {
+        Ok(_) => {
+            info!("gRPC response compression enabled");
+            compressed
+        }
+        Err(e) => {
+            warn!(
+                "Cosmos node failed to respond to a compressed gRPC query {:?}, continuing without compression",
+                e
+            );
+            grpc
+        }
+    }
+}
+
+/// Resolves the host of an RPC url according to the provided DNS config, returning
+/// the url with the host replaced by the resolved address. This is done once at startup,
+/// the connections keep the address for as long as the process runs. If resolution is
+/// disabled, fails, or the url is https (which requires the hostname for TLS) the url is
+/// returned unmodified so that the default resolver is used. localhost is also left alone
+/// so that create_rpc_connections can fall back between the IPv4 and IPv6 loopback
+pub async fn resolve_rpc_url(input: &str, config: &DnsConfig) -> String {
+    if !config.dns_cache_enabled {
+        return input.to_string();
+    }
+    let mut url = match Url::parse(input) {
+        Ok(u) => u,
+        Err(_) => return input.to_string(),
+    };
+    let host = match url.host_str() {
+        Some(h) => h.to_string(),
+        None => return input.to_string(),
+    };
+    if url.scheme() != "http"
+        || host.parse::<IpAddr>().is_ok()
+        || host.eq_ignore_ascii_case("localhost")
+    {
+        return input.to_string();
+    }
+
+    let port = url.port_or_known_default().unwrap_or(80);
+    let addresses = match lookup_host((host.as_str(), port)).await {
+        Ok(a) => a.map(|a| a.ip()).collect::<Vec<IpAddr>>(),
+        Err(e) => {
+            warn!(
+                "Failed to resolve {} with {:?}, using the default resolver",
+                host, e
+            );
+            return input.to_string();
+        }
+    };
+    let preferred = addresses.iter().find(|a| match config.dns_address_family {
+        AddressFamily::Any => true,
+        AddressFamily::Ipv4 => a.is_ipv4(),
+        AddressFamily::Ipv6 => a.is_ipv6(),
+    });
+    let ip = match preferred.or_else(|| addresses.first()) {
+        Some(ip) => *ip,
+        None => return input.to_string(),
+    };
+
+    if url.set_ip_host(ip).is_err() {
+        return input.to_string();
+    }
+    info!("Resolved {} to {}", host, ip);
+    url.to_string()
+}
+
 /// Verify that a url has an http or https prefix
 fn check_scheme(input: &Url, original_string: &str) {
     if !(input.scheme() == "http" || input.scheme() == "https") {
-        panic!(
+        connection_failure(format!(
             "Your url {} has an invalid scheme, please chose http or https",
             original_string
-        )
+        ))
     }
 }
 
@@ -209,7 +383,18 @@ fn check_scheme(input: &Url, original_string: &str) {
 /// for situations such as when a node is syncing or when a node is waiting on
 /// a halted chain.
 pub async fn wait_for_cosmos_node_ready(contact: &Contact) {
+    // without a timeout this only returns once the node is ready
+    let _ = wait_for_cosmos_node_ready_with_timeout(contact, None).await;
+}
+
+/// Waits for the Cosmos node to produce blocks like `wait_for_cosmos_node_ready`, but returns
+/// an error if the node is syncing or waiting for the chain to start for longer than `timeout`
+pub async fn wait_for_cosmos_node_ready_with_timeout(
+    contact: &Contact,
+    timeout: Option<Duration>,
+) -> Result<(), GravityError> {
     const WAIT_TIME: Duration = Duration::from_secs(10);
+    let start = Instant::now();
     loop {
         let res = contact.get_chain_status().await;
         match res {
@@ -220,17 +405,41 @@ pub async fn wait_for_cosmos_node_ready(contact: &Contact) {
                 info!("Cosmos node is waiting for the chain to start, Standing by")
             }
             Ok(ChainStatus::Moving { .. }) => {
-                break;
+                return Ok(());
             }
             Err(e) => warn!(
                 "Could not get syncing status, is your Cosmos node up? {:?}",
                 e
             ),
         }
+        if let Some(timeout) = timeout {
+            if start.elapsed() > timeout {
+                return Err(GravityError::UnrecoverableError(format!(
+                    "Cosmos node was not ready within {} seconds",
+                    timeout.as_secs()
+                )));
+            }
+        }
         delay_for(WAIT_TIME).await;
     }
 }
 
+/// How many times the delegate key queries are tried when the Cosmos node can't be reached
+const DELEGATE_QUERY_ATTEMPTS: u32 = 5;
+
+/// Returns true for gRPC errors caused by the connection or an overloaded node, which are
+/// worth retrying, rather than by the query itself
+fn is_transient_grpc_error(status: &Status) -> bool {
+    matches!(
+        status.code(),
+        Code::Unavailable
+            | Code::DeadlineExceeded
+            | Code::Cancelled
+            | Code::ResourceExhausted
+            | Code::Aborted
+    )
+}
+
 /// This function checks the orchestrator delegate addresses
 /// for consistency what this means is that it takes the Ethereum
 /// address and Orchestrator address from the Orchestrator and checks
@@ -241,17 +450,45 @@ pub async fn check_delegate_addresses(
     delegate_orchestrator_address: CosmosAddress,
     prefix: &str,
 ) -> Result<(), GravityError> {
-    let eth_response = client
-        .get_delegate_key_by_eth(QueryDelegateKeysByEthAddress {
-            eth_address: delegate_eth_address.to_string(),
-        })
-        .await;
-    let orchestrator_response = client
-        .get_delegate_key_by_orchestrator(QueryDelegateKeysByOrchestratorAddress {
-            orchestrator_address: delegate_orchestrator_address.to_bech32(prefix).unwrap(),
-        })
-        .await;
-    trace!("{:?} {:?}", eth_response, orchestrator_response);
+    let mut attempts = 0;
+    let (eth_response, orchestrator_response) = loop {
+        let eth_response = client
+            .get_delegate_key_by_eth(QueryDelegateKeysByEthAddress {
+                eth_address: delegate_eth_address.to_string(),
+            })
+            .await;
+        let orchestrator_response = client
+            .get_delegate_key_by_orchestrator(QueryDelegateKeysByOrchestratorAddress {
+                orchestrator_address: delegate_orchestrator_address.to_bech32(prefix).unwrap(),
+            })
+            .await;
+        trace!("{:?} {:?}", eth_response, orchestrator_response);
+        // a node that could not answer says nothing about the keys, only an answer that the
+        // key is not registered should be reported as a key problem
+        let transient = match (&eth_response, &orchestrator_response) {
+            (Err(status), _) | (_, Err(status)) if is_transient_grpc_error(status) => {
+                Some(format!("{status:?}"))
+            }
+            _ => None,
+        };
+        match transient {
+            None => break (eth_response, orchestrator_response),
+            Some(error) => {
+                attempts += 1;
+                if attempts >= DELEGATE_QUERY_ATTEMPTS {
+                    return Err(GravityError::UnrecoverableError(format!(
+                        "Could not query the Gravity delegate keys after {attempts} attempts, this is a problem \
+                        with the connection to your Cosmos node and not with your keys {error}"
+                    )));
+                }
+                warn!(
+                    "Failed to query the Gravity delegate keys, retrying {}",
+                    error
+                );
+                delay_for(RETRY_TIME).await;
+            }
+        }
+    };
     match (eth_response, orchestrator_response) {
         (Ok(e), Ok(o)) => {
             let e = e.into_inner();
@@ -304,6 +541,46 @@ pub async fn check_delegate_addresses(
     }
 }
 
+/// Checks that the denom of a given Coin, used for fees, exists on the chain. This
+/// distinguishes a typo in the fee denom from an account that simply has no balance
+pub async fn check_fee_denom_exists(fee: &Coin, contact: &Contact) -> Result<(), GravityError> {
+    // if we decide to pay no fees the denom is never used
+    if fee.amount.is_zero() {
+        return Ok(());
+    }
+    if denom_has_supply(&fee.denom, contact).await? {
+        Ok(())
+    } else {
+        Err(GravityError::UnrecoverableError(format!(
+            "You have specified that fees should be paid in {} but no such denom exists on the chain! Check your fees argument for typos",
+            fee.denom
+        )))
+    }
+}
+
+/// Returns true if any amount of `denom` exists on the chain
+pub async fn denom_has_supply(denom: &str, contact: &Contact) -> Result<bool, GravityError> {
+    Ok(!get_denom_supply(denom, contact).await?.is_zero())
+}
+
+/// Returns the total supply of `denom` on the chain
+pub async fn get_denom_supply(denom: &str, contact: &Contact) -> Result<Uint256, GravityError> {
+    let mut bank = BankQueryClient::connect(contact.get_url())
+        .await
+        .map_err(|e| GravityError::RpcError(Box::new(e)))?;
+    let supply = bank
+        .supply_of(QuerySupplyOfRequest {
+            denom: denom.to_string(),
+        })
+        .await?
+        .into_inner()
+        .amount;
+    match supply {
+        Some(supply) => Ok(Uint256::from_dec_or_hex_str_restricted(&supply.amount)?),
+        None => Ok(u256!(0)),
+    }
+}
+
 /// Checks if a given Coin, used for fees is in the provided address in a sufficient quantity
 pub async fn check_for_fee(
     fee: &Coin,
@@ -339,6 +616,70 @@ pub async fn check_for_fee(
     ))
 }
 
+/// Initializes `address` by sending it the smallest unit of a token held by `funding_key`,
+/// this is only done if the account has no tokens of any kind. check_for_fee requires the
+/// account to be initialized even when paying zero fees
+pub async fn initialize_account(
+    address: CosmosAddress,
+    funding_key: CosmosPrivateKey,
+    contact: &Contact,
+) -> Result<(), GravityError> {
+    const INITIALIZE_TIMEOUT: Duration = Duration::from_secs(60);
+    if !matches!(
+        contact.get_account_info(address).await,
+        Err(CosmosGrpcError::NoToken)
+    ) {
+        return Ok(());
+    }
+
+    let funding_address = funding_key.to_address(&contact.get_prefix()).unwrap();
+    let balances = get_balances_with_retry(funding_address, contact).await;
+    let denom = match balances.first() {
+        Some(balance) => balance.denom.clone(),
+        None => {
+            return Err(GravityError::ValidationError(format!(
+                "The funding account {funding_address} has no tokens to initialize {address} with"
+            )))
+        }
+    };
+    info!(
+        "Initializing {} by sending it 1{} from {}",
+        address, denom, funding_address
+    );
+    contact
+        .send_coins(
+            Coin {
+                amount: u256!(1),
+                denom,
+            },
+            None,
+            address,
+            Some(INITIALIZE_TIMEOUT),
+            funding_key,
+        )
+        .await?;
+    Ok(())
+}
+
+/// Checks that the Ethereum node reports `expected` as its chain-id, None skips the check
+pub async fn check_eth_chain_id(web3: &Web3, expected: Option<u64>) -> Result<(), GravityError> {
+    let expected = match expected {
+        Some(expected) => expected,
+        None => return Ok(()),
+    };
+    let chain_id = web3.eth_chainid().await?;
+    if chain_id != Uint256::from_u64(expected) {
+        return Err(GravityError::UnrecoverableError(format!(
+            "The Ethereum node {} reports chain-id {} but {} is expected, check your Ethereum RPC url",
+            web3.get_url(),
+            chain_id,
+            expected
+        )));
+    }
+    info!("Ethereum node reports the expected chain-id {}", expected);
+    Ok(())
+}
+
 /// Checks the user has some Ethereum in their address to pay for things
 pub async fn check_for_eth(address: EthAddress, web3: &Web3) -> Result<(), GravityError> {
     let balance = get_eth_balances_with_retry(address, web3).await;
@@ -352,3 +693,229 @@ pub async fn check_for_eth(address: EthAddress, web3: &Web3) -> Result<(), Gravi
         Ok(())
     }
 }
+
+/// Checks that the gravity_id reported by the Cosmos chain matches the one in the Gravity
+/// contract and, if set, the expected value from the config. A mismatch almost always means
+/// one chain's config or node is being used with another, so the error lists every value side
+/// by side and hints at the likely mix up. The contract stores the id as a NUL padded bytes32,
+/// the padding is ignored
+pub fn check_gravity_id(
+    expected: Option<&str>,
+    cosmos: &str,
+    ethereum: &str,
+) -> Result<(), GravityError> {
+    let ethereum = ethereum.trim_end_matches('\0');
+    let mut ids = Vec::new();
+    if let Some(expected) = expected {
+        ids.push(("config", expected));
+    }
+    ids.push(("cosmos chain", cosmos));
+    ids.push(("gravity contract", ethereum));
+
+    if ids.iter().all(|(_, id)| *id == cosmos) {
+        return Ok(());
+    }
+
+    let mut message = String::from("gravity_id mismatch!\n");
+    for (source, id) in ids.iter() {
+        let marker = if *id == cosmos { " " } else { "*" };
+        message += &format!("  {marker} {source:<16} {id:?}\n");
+    }
+    for (i, (a_source, a)) in ids.iter().enumerate() {
+        for (b_source, b) in ids.iter().skip(i + 1) {
+            if let Some(hint) = gravity_id_mixup_hint(a, b) {
+                message += &format!("The {a_source} and {b_source} values {hint}\n");
+            }
+        }
+    }
+    message +=
+        "Check that your config, Cosmos node and Ethereum contract all belong to the same bridge";
+    Err(GravityError::UnrecoverableError(message))
+}
+
+/// Explains why two different gravity_ids were likely confused, if they look like a known pair
+fn gravity_id_mixup_hint(a: &str, b: &str) -> Option<&'static str> {
+    const NETWORK_MARKERS: [&str; 6] = ["mainnet", "testnet", "devnet", "main", "test", "dev"];
+    fn strip(id: &str) -> String {
+        let mut id = id.to_lowercase();
+        for marker in NETWORK_MARKERS {
+            id = id.replace(marker, "");
+        }
+        id.replace(['-', '_', '.'], "")
+    }
+
+    if a == b {
+        None
+    } else if a.eq_ignore_ascii_case(b) {
+        Some("only differ in case, gravity_id is case sensitive")
+    } else if a.trim() == b.trim() {
+        Some("only differ in surrounding whitespace")
+    } else if strip(a) == strip(b) {
+        Some("look like the same bridge on different networks, such as testnet and mainnet")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::{TcpListener, TcpStream},
+        sync::Arc,
+        thread,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_is_transient_grpc_error() {
+        assert!(is_transient_grpc_error(&Status::unavailable("node down")));
+        assert!(is_transient_grpc_error(&Status::deadline_exceeded(
+            "timeout"
+        )));
+        // the Gravity module reports unregistered keys as a failed query
+        assert!(!is_transient_grpc_error(&Status::unknown("No validator")));
+        assert!(!is_transient_grpc_error(&Status::not_found("not found")));
+    }
+
+    #[test]
+    fn test_localhost_fallback_urls() {
+        // both the Cosmos gRPC and Ethereum RPC connections fall back to these urls
+        for (input, port) in [
+            ("http://localhost:9090", 9090),
+            ("https://localhost:8545/", 8545),
+            ("http://LOCALHOST", 80),
+            ("https://localhost", 443),
+        ] {
+            let url = Url::parse(input).unwrap();
+            let (ipv6_url, ipv4_url) = localhost_fallback_urls(&url);
+
+            let ipv6 = Url::parse(&ipv6_url)
+                .unwrap_or_else(|e| panic!("{ipv6_url} from {input} does not parse {e:?}"));
+            assert_eq!(ipv6.scheme(), url.scheme());
+            assert_eq!(ipv6.host_str(), Some("[::1]"));
+            assert_eq!(ipv6.port_or_known_default(), Some(port));
+
+            let ipv4 = Url::parse(&ipv4_url).unwrap();
+            assert_eq!(ipv4.scheme(), url.scheme());
+            assert_eq!(ipv4.host_str(), Some("127.0.0.1"));
+            assert_eq!(ipv4.port_or_known_default(), Some(port));
+        }
+    }
+
+    /// Test that an Ethereum RPC on localhost that fails is retried on the loopback addresses,
+    /// the server here only answers on 127.0.0.1 and not at the path of the configured url
+    #[tokio::test]
+    async fn test_localhost_fallback_is_attempted() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let paths = Arc::new(Mutex::new(Vec::new()));
+        let server_paths = paths.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = match stream {
+                    Ok(s) => s,
+                    Err(_) => continue,
+                };
+                let (path, body) = read_http_request(&mut stream);
+                server_paths.lock().unwrap().push(path.clone());
+                let response = if path == "/" {
+                    let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                    let body = serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": request["id"],
+                        "result": "0x10"
+                    })
+                    .to_string();
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                } else {
+                    "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                        .to_string()
+                };
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        let connections = create_rpc_connections(
+            "onomy".to_string(),
+            None,
+            Some(format!("http://localhost:{port}/not-found")),
+            Duration::from_secs(5),
+        )
+        .await;
+        assert!(connections.web3.is_some());
+        let paths = paths.lock().unwrap();
+        assert_eq!(paths.first().map(String::as_str), Some("/not-found"));
+        assert_eq!(paths.last().map(String::as_str), Some("/"));
+    }
+
+    /// Reads one HTTP request, returning its path and body
+    fn read_http_request(stream: &mut TcpStream) -> (String, Vec<u8>) {
+        let mut data = Vec::new();
+        let mut buf = [0u8; 1024];
+        let header_end = loop {
+            let read = stream.read(&mut buf).unwrap();
+            if read == 0 {
+                return (String::new(), Vec::new());
+            }
+            data.extend_from_slice(&buf[..read]);
+            if let Some(i) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+                break i + 4;
+            }
+        };
+        let headers = String::from_utf8_lossy(&data[..header_end]).to_string();
+        let path = headers
+            .split_whitespace()
+            .nth(1)
+            .unwrap_or_default()
+            .to_string();
+        let content_length = headers
+            .lines()
+            .find_map(|l| {
+                let (name, value) = l.split_once(':')?;
+                name.eq_ignore_ascii_case("content-length")
+                    .then(|| value.trim().parse::<usize>().ok())?
+            })
+            .unwrap_or(0);
+        while data.len() < header_end + content_length {
+            let read = stream.read(&mut buf).unwrap();
+            if read == 0 {
+                break;
+            }
+            data.extend_from_slice(&buf[..read]);
+        }
+        (path, data[header_end..].to_vec())
+    }
+
+    #[test]
+    fn test_check_gravity_id() {
+        assert!(check_gravity_id(None, "gravity-test", "gravity-test").is_ok());
+        assert!(check_gravity_id(Some("gravity-test"), "gravity-test", "gravity-test").is_ok());
+
+        match check_gravity_id(
+            Some("gravity-testnet"),
+            "gravity-mainnet",
+            "gravity-mainnet",
+        ) {
+            Err(GravityError::UnrecoverableError(message)) => {
+                assert!(message.contains("different networks"))
+            }
+            res => panic!("Expected a mismatch, got {res:?}"),
+        }
+        match check_gravity_id(None, "Gravity", "gravity") {
+            Err(GravityError::UnrecoverableError(message)) => assert!(message.contains("case")),
+            res => panic!("Expected a mismatch, got {res:?}"),
+        }
+        assert!(check_gravity_id(None, "foo", "bar").is_err());
+
+        // get_gravity_id returns the bytes32 from the contract including it's padding
+        let padded = format!("{:\0<32}", "gravity-test");
+        assert_eq!(padded.len(), 32);
+        assert!(check_gravity_id(Some("gravity-test"), "gravity-test", &padded).is_ok());
+    }
+}
@@ -20,6 +20,11 @@ pub enum GravityError {
     #[error("{0}")]
     UnrecoverableError(String),
 
+    /// A deliberate halt asked for by the config, such as unknown_erc20_deposit_mode, the
+    /// Orchestrator exits so that an operator can review what caused it
+    #[error("{0}")]
+    HaltError(String),
+
     // we can pass String info here as well if we need more context/details
     #[error(transparent)]
     RpcError(#[from] Box<dyn std::error::Error + Send>),
@@ -6,54 +6,69 @@ use deep_space::{address::Address as CosmosAddress, Coin, Contact};
 use tokio::time::sleep;
 use web30::client::Web3;
 
+use crate::backoff::Backoff;
+
 pub const RETRY_TIME: Duration = Duration::from_secs(5);
 
+/// The retry policy of the functions in this file, a constant delay of about RETRY_TIME
+/// with jitter so that processes that lost a node at the same time don't retry in lockstep
+fn retry_backoff() -> Backoff {
+    Backoff::new(RETRY_TIME, RETRY_TIME)
+        .with_multiplier(1.0)
+        .with_jitter(0.2)
+}
+
 /// gets the current Ethereum finalized block number, no matter how long it takes
 pub async fn get_finalized_block_number_with_retry(web3: &Web3) -> Uint256 {
+    let mut backoff = retry_backoff();
     loop {
         match web3.eth_finalized_block_number().await {
             Ok(res) => return res,
-            _ => sleep(RETRY_TIME).await,
+            _ => sleep(backoff.next_delay()).await,
         }
     }
 }
 
 // gets the latest block number
 pub async fn get_latest_block_number_with_retry(web3: &Web3) -> Uint256 {
+    let mut backoff = retry_backoff();
     loop {
         match web3.eth_block_number().await {
             Ok(res) => return res,
-            _ => sleep(RETRY_TIME).await,
+            _ => sleep(backoff.next_delay()).await,
         }
     }
 }
 
 /// gets the current Ethereum block number, no matter how long it takes
 pub async fn get_eth_balances_with_retry(address: EthAddress, web3: &Web3) -> Uint256 {
+    let mut backoff = retry_backoff();
     loop {
         match web3.eth_get_balance(address).await {
             Ok(res) => return res,
-            _ => sleep(RETRY_TIME).await,
+            _ => sleep(backoff.next_delay()).await,
         }
     }
 }
 
 /// gets Cosmos balances, no matter how long it takes
 pub async fn get_balances_with_retry(address: CosmosAddress, contact: &Contact) -> Vec<Coin> {
+    let mut backoff = retry_backoff();
     loop {
         match contact.get_balances(address).await {
             Ok(res) => return res,
-            _ => sleep(RETRY_TIME).await,
+            _ => sleep(backoff.next_delay()).await,
         }
     }
 }
 
 /// gets the net version, no matter how long it takes
 pub async fn get_net_version_with_retry(web3: &Web3) -> u64 {
+    let mut backoff = retry_backoff();
     loop {
         match web3.net_version().await {
             Ok(res) => return res,
-            _ => sleep(RETRY_TIME).await,
+            _ => sleep(backoff.next_delay()).await,
         }
     }
 }
@@ -3,12 +3,17 @@
 #[macro_use]
 extern crate log;
 
+pub mod backoff;
 pub mod connection_prep;
 pub mod error;
 pub mod get_with_retry;
 pub mod num_conversion;
+pub mod pause;
 pub mod prices;
+pub mod signer_status;
+pub mod slashing_alerts;
 pub mod types;
+pub mod watchdog;
 
 pub use clarity;
 use clarity::{u256, Uint256};
@@ -0,0 +1,21 @@
+//! An emergency pause for incidents such as a suspected key compromise. While the configured
+//! pause file exists the Orchestrator and Relayer loops skip all signing and submissions but
+//! keep running, observing both chains and reporting metrics. Deleting the file resumes them.
+
+use std::{path::PathBuf, sync::Mutex};
+
+static PAUSE_FILE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Sets the file whose existence pauses submissions, None disables the pause
+pub fn set_pause_file(path: Option<PathBuf>) {
+    *PAUSE_FILE.lock().unwrap() = path;
+}
+
+/// Returns true if submissions are paused, this is checked at the top of every loop
+/// iteration so creating or removing the file takes effect without a restart
+pub fn submissions_paused() -> bool {
+    match &*PAUSE_FILE.lock().unwrap() {
+        Some(path) => path.exists(),
+        None => false,
+    }
+}
@@ -1,3 +1,5 @@
+use std::sync::Mutex;
+
 use clarity::{address::Address as EthAddress, u256, Uint256};
 use web30::{
     amm::{DAI_CONTRACT_ADDRESS, WETH_CONTRACT_ADDRESS},
@@ -12,23 +14,111 @@ pub async fn get_weth_price(
     pubkey: EthAddress,
     web3: &Web3,
 ) -> Result<Uint256, Web3Error> {
-    if token == *WETH_CONTRACT_ADDRESS {
-        return Ok(amount);
-    } else if amount.is_zero() {
+    get_base_token_price(*WETH_CONTRACT_ADDRESS, token, amount, pubkey, web3).await
+}
+
+/// utility function, gets the price of a given ERC20 token in uniswap in terms of the provided
+/// base token. The base token should be the wrapped native token of the chain (WETH on Ethereum,
+/// WMATIC on Polygon etc) so that the result can be compared against gas costs. The result is
+/// in units of 18 decimals regardless of the decimals of the base token
+pub async fn get_base_token_price(
+    base_token: EthAddress,
+    token: EthAddress,
+    amount: Uint256,
+    pubkey: EthAddress,
+    web3: &Web3,
+) -> Result<Uint256, Web3Error> {
+    if amount.is_zero() {
         return Ok(u256!(0));
+    } else if token == base_token {
+        return Ok(to_native_decimals(base_token, amount, pubkey, web3).await);
     }
 
     // TODO: Make sure the market is not too thin
-    web3.get_uniswap_price(
-        pubkey,
-        token,
-        *WETH_CONTRACT_ADDRESS,
-        None,
-        amount,
-        None,
-        None,
-    )
-    .await
+    let price = web3
+        .get_uniswap_price(pubkey, token, base_token, None, amount, None, None)
+        .await?;
+    Ok(to_native_decimals(base_token, price, pubkey, web3).await)
+}
+
+/// The decimals of the native token gas is paid in, prices are returned in this unit so that
+/// they can be compared directly against gas costs
+pub const NATIVE_DECIMALS: u8 = 18;
+
+/// Token decimals never change so every token is only queried once
+static DECIMALS_CACHE: Mutex<Vec<(EthAddress, u8)>> = Mutex::new(Vec::new());
+
+/// Gets the decimals of an ERC20, caching the result for the life of the process
+pub async fn get_erc20_decimals_cached(
+    token: EthAddress,
+    pubkey: EthAddress,
+    web3: &Web3,
+) -> Result<u8, Web3Error> {
+    if let Some((_, decimals)) = DECIMALS_CACHE
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|(t, _)| *t == token)
+    {
+        return Ok(*decimals);
+    }
+    let decimals = web3.get_erc20_decimals(token, pubkey).await?;
+    let decimals = match decimals.to_string().parse() {
+        Ok(d) => d,
+        Err(_) => {
+            return Err(Web3Error::BadResponse(format!(
+                "ERC20 {} reports {} decimals",
+                token, decimals
+            )))
+        }
+    };
+    DECIMALS_CACHE.lock().unwrap().push((token, decimals));
+    Ok(decimals)
+}
+
+/// Rescales an amount of `base_token` to the decimals of the native token. Uniswap quotes
+/// are in the smallest unit of each token so the decimals of the priced token are already
+/// accounted for, but a base token without 18 decimals would otherwise be mis-valued
+/// against gas costs. If the decimals can not be read 18 is assumed
+async fn to_native_decimals(
+    base_token: EthAddress,
+    amount: Uint256,
+    pubkey: EthAddress,
+    web3: &Web3,
+) -> Uint256 {
+    match get_erc20_decimals_cached(base_token, pubkey, web3).await {
+        Ok(decimals) => rescale_decimals(amount, decimals, NATIVE_DECIMALS),
+        Err(e) => {
+            debug!(
+                "Could not get decimals of price base token {}, assuming {} {:?}",
+                base_token, NATIVE_DECIMALS, e
+            );
+            amount
+        }
+    }
+}
+
+/// Converts `amount` from a token with `from` decimals to the same value with `to` decimals,
+/// saturating on overflow and rounding down
+pub fn rescale_decimals(amount: Uint256, from: u8, to: u8) -> Uint256 {
+    if from < to {
+        let factor = ten_pow(to - from);
+        amount
+            .checked_mul(factor)
+            .unwrap_or_else(Uint256::max_value)
+    } else {
+        amount.checked_div(ten_pow(from - to)).unwrap()
+    }
+}
+
+fn ten_pow(exp: u8) -> Uint256 {
+    let mut res = u256!(1);
+    for _ in 0..exp {
+        res = res
+            .checked_mul(u256!(10))
+            .unwrap_or_else(Uint256::max_value);
+    }
+    res
 }
 
 /// utility function, gets the price of a given ER20 token in uniswap in DAI given the erc20 address and amount
@@ -56,3 +146,19 @@ pub async fn get_dai_price(
     )
     .await
 }
+
+#[test]
+fn test_rescale_decimals() {
+    // 1.5 of a 6 decimal token is 1.5 * 10^18 in native decimals
+    assert_eq!(
+        rescale_decimals(u256!(1_500_000), 6, 18),
+        u256!(1_500_000_000_000_000_000)
+    );
+    assert_eq!(rescale_decimals(u256!(1_500_000), 6, 6), u256!(1_500_000));
+    assert_eq!(
+        rescale_decimals(u256!(1_500_000_000_000_000_000), 18, 8),
+        u256!(150_000_000)
+    );
+    // precision beyond the target decimals is dropped
+    assert_eq!(rescale_decimals(u256!(1_999), 3, 0), u256!(1));
+}
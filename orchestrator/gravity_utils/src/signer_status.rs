@@ -0,0 +1,18 @@
+//! Shares whether the Eth signer is behind on confirms with the other Orchestrator loops, so
+//! that profit driven work such as relaying can yield to slashing critical confirms when
+//! resources are constrained. Outside of the Orchestrator no signer runs and it is never behind.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static SIGNER_BEHIND: AtomicBool = AtomicBool::new(false);
+
+/// Records if the last Eth signer iteration left confirms unsubmitted, either because it
+/// found unsigned items or because it could not check for them
+pub fn set_signer_behind(behind: bool) {
+    SIGNER_BEHIND.store(behind, Ordering::Relaxed);
+}
+
+/// Returns true if the Eth signer is behind on confirms
+pub fn signer_behind() -> bool {
+    SIGNER_BEHIND.load(Ordering::Relaxed)
+}
@@ -0,0 +1,109 @@
+//! Alerts for conditions that put a validator at risk of being slashed. When an alert command
+//! is configured it is run through the shell whenever the Orchestrator detects such a condition,
+//! with a JSON description of the condition on stdin, so that operators can integrate paging
+//! or chat notifications in one place instead of parsing logs.
+
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+
+/// The log target of messages about conditions that risk slashing and of unrecoverable errors,
+/// so that operators can filter them out of the routine output, for example with the log
+/// filter "warn,slashing_risk=info", or grep for them
+pub const SLASHING_RISK_LOG_TARGET: &str = "slashing_risk";
+
+/// The same condition is alerted at most once per this interval
+const ALERT_REPEAT_INTERVAL: Duration = Duration::from_secs(300);
+
+/// The command run for every alert, None disables alerting
+static ALERT_COMMAND: Mutex<Option<String>> = Mutex::new(None);
+
+/// The last time each condition was alerted
+static LAST_ALERTS: Mutex<Vec<(&'static str, Instant)>> = Mutex::new(Vec::new());
+
+/// The payload passed to the alert command
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct SlashingRiskAlert {
+    /// A short, stable identifier of the condition
+    pub condition: &'static str,
+    /// A human readable description of the problem
+    pub details: String,
+    /// The slashing window in blocks if known, the validator is slashed if the
+    /// condition persists for this long
+    pub blocks_until_slashing: Option<u64>,
+}
+
+/// Sets the shell command that is run on every slashing risk alert
+pub fn set_slashing_alert_command(command: Option<String>) {
+    *ALERT_COMMAND.lock().unwrap() = command;
+}
+
+/// Returns true if `condition` has not been alerted within `ALERT_REPEAT_INTERVAL`
+/// and records that it is being alerted now
+fn should_alert(condition: &'static str) -> bool {
+    let mut last_alerts = LAST_ALERTS.lock().unwrap();
+    match last_alerts.iter_mut().find(|(c, _)| *c == condition) {
+        Some((_, last)) if last.elapsed() < ALERT_REPEAT_INTERVAL => false,
+        Some((_, last)) => {
+            *last = Instant::now();
+            true
+        }
+        None => {
+            last_alerts.push((condition, Instant::now()));
+            true
+        }
+    }
+}
+
+/// Fires a slashing risk alert if an alert command is configured. The command runs in the
+/// background so a slow or hanging alert integration never blocks the Orchestrator
+pub fn alert_slashing_risk(
+    condition: &'static str,
+    details: String,
+    blocks_until_slashing: Option<u64>,
+) {
+    let command = match &*ALERT_COMMAND.lock().unwrap() {
+        Some(command) => command.clone(),
+        None => return,
+    };
+    if !should_alert(condition) {
+        return;
+    }
+
+    let alert = SlashingRiskAlert {
+        condition,
+        details,
+        blocks_until_slashing,
+    };
+    let payload = serde_json::to_vec(&alert).unwrap();
+    // the command is started and given the payload before returning, so that the alert is
+    // delivered even if the Orchestrator exits right after, only the wait is in the background
+    let child = Command::new("sh")
+        .arg("-c")
+        .arg(&command)
+        .stdin(Stdio::piped())
+        .spawn();
+    let mut child = match child {
+        Ok(c) => c,
+        Err(e) => {
+            error!("Failed to run slashing alert command {} {:?}", command, e);
+            return;
+        }
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(&payload);
+    }
+    thread::spawn(move || match child.wait() {
+        Ok(status) if !status.success() => {
+            error!("Slashing alert command {} failed with {}", command, status)
+        }
+        Err(e) => error!("Failed to run slashing alert command {} {:?}", command, e),
+        _ => {}
+    });
+}
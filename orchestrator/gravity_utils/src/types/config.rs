@@ -1,7 +1,15 @@
 //! contains configuration structs that need to be accessed across crates.
 
 use clarity::{Address as EthAddress, Uint256};
+use deep_space::Address as CosmosAddress;
 use serde::{Deserialize, Serialize};
+use web30::amm::WETH_CONTRACT_ADDRESS;
+
+use crate::{error::GravityError, types::TOTAL_GRAVITY_POWER};
+
+/// The loop speed in seconds of the slowest Orchestrator loop, the Ethereum oracle, see
+/// ETH_ORACLE_LOOP_SPEED in the orchestrator crate
+const SLOWEST_ORCHESTRATOR_LOOP_SPEED: u64 = 13;
 
 /// Global configuration struct for Gravity bridge tools
 #[derive(Serialize, Deserialize, Debug, PartialEq, Default, Clone)]
@@ -9,6 +17,10 @@ pub struct GravityBridgeToolsConfig {
     pub relayer: RelayerConfig,
     pub orchestrator: OrchestratorConfig,
     pub metrics: MetricsConfig,
+    pub dns: DnsConfig,
+    pub logging: LoggingConfig,
+    pub rpc: RpcConfig,
+    pub runtime: RuntimeConfig,
 }
 
 /// Toml serializable configuration struct for Gravity bridge tools
@@ -20,6 +32,40 @@ pub struct TomlGravityBridgeToolsConfig {
     pub orchestrator: OrchestratorConfig,
     #[serde(default = "MetricsConfig::default")]
     pub metrics: MetricsConfig,
+    #[serde(default = "DnsConfig::default")]
+    pub dns: DnsConfig,
+    #[serde(default = "LoggingConfig::default")]
+    pub logging: LoggingConfig,
+    #[serde(default = "RpcConfig::default")]
+    pub rpc: RpcConfig,
+    #[serde(default = "RuntimeConfig::default")]
+    pub runtime: RuntimeConfig,
+}
+
+impl TomlGravityBridgeToolsConfig {
+    /// Checks the parsed config for problems that would otherwise cause a panic
+    /// or misbehavior once the config is converted and used
+    pub fn validate(&self) -> Result<(), GravityError> {
+        self.relayer.validate()?;
+        self.orchestrator.validate()?;
+        self.validate_loop_watchdog_timeout()?;
+        self.runtime.validate()
+    }
+
+    /// A watchdog timeout no longer than a loop's speed would restart a healthy Orchestrator
+    fn validate_loop_watchdog_timeout(&self) -> Result<(), GravityError> {
+        let timeout = self.orchestrator.loop_watchdog_timeout;
+        let mut slowest_loop = SLOWEST_ORCHESTRATOR_LOOP_SPEED;
+        if self.orchestrator.relayer_enabled {
+            slowest_loop = slowest_loop.max(self.relayer.relayer_loop_speed);
+        }
+        if timeout != 0 && timeout <= slowest_loop {
+            return Err(GravityError::ValidationError(format!(
+                "Invalid orchestrator config:\nloop_watchdog_timeout {timeout} must be 0 or larger than the slowest loop speed {slowest_loop}"
+            )));
+        }
+        Ok(())
+    }
 }
 
 impl From<TomlGravityBridgeToolsConfig> for GravityBridgeToolsConfig {
@@ -28,6 +74,10 @@ impl From<TomlGravityBridgeToolsConfig> for GravityBridgeToolsConfig {
             relayer: input.relayer.into(),
             orchestrator: input.orchestrator,
             metrics: input.metrics,
+            dns: input.dns,
+            logging: input.logging,
+            rpc: input.rpc,
+            runtime: input.runtime,
         }
     }
 }
@@ -42,6 +92,84 @@ pub struct RelayerConfig {
     /// the speed at which the relayer loop runs, in seconds
     /// higher values reduce the chances of money lost to a collision
     pub relayer_loop_speed: u64,
+    /// the token fees and rewards are priced in when determining profitability,
+    /// this should be the wrapped native token of the chain, WETH on Ethereum
+    pub price_base_token: EthAddress,
+    /// if set batches will only be requested for these tokens, requesting a batch
+    /// costs Cosmos fees so this is independent of which batches are relayed
+    pub batch_request_tokens: Option<Vec<EthAddress>>,
+    /// if set only batches of these tokens will be relayed to Ethereum
+    pub batch_relay_tokens: Option<Vec<EthAddress>>,
+    /// how nonces are assigned to relay transactions on Ethereum
+    pub nonce_management: NonceManagement,
+    /// the order in which batches of different tokens are relayed
+    pub batch_relay_order: BatchRelayOrder,
+    /// if set profitable batch requests are abandoned for the rest of the iteration once the
+    /// gas price rises above this multiple of the price their profitability was judged at
+    pub max_gas_price_increase: Option<f64>,
+    /// if set and running inside the Orchestrator the relayer skips batch and logic call
+    /// relaying and batch requests while the Eth signer is behind on confirms, so that
+    /// slashing avoidance always comes first. Valsets are still relayed
+    pub confirm_first: bool,
+    /// if set each batch submission is simulated with an `eth_call` right before it is sent
+    /// and skipped if it would revert, at the cost of an extra RPC call per relay
+    pub simulate_batch_relays: bool,
+    /// if set batches and logic calls are only relayed once their signatures hold at least this
+    /// fraction of the power, on top of the contract's own requirement of just over 66%
+    pub min_relay_power_fraction: Option<f64>,
+    /// if set a valset update is relayed regardless of the valset relaying mode when pending
+    /// batches can only be verified by a valset newer than the one on Ethereum
+    pub relay_prerequisite_valsets: bool,
+    /// batches of these tokens are requested and relayed even when unprofitable, letting
+    /// operators subsidize specific assets while every other token stays profit gated
+    pub always_relay_tokens: Vec<EthAddress>,
+    /// if set with local nonce management the next nonce is persisted to this file and
+    /// restored on startup, so transactions in flight across a restart are not replaced
+    pub nonce_state_file: Option<String>,
+    /// the minimum time in seconds between batch requests for the same token in any batch
+    /// request mode, zero requests every iteration
+    pub batch_request_cooldown: u64,
+    /// if set a unix socket is served at this path that dumps the recent relay transactions
+    /// and their status on request
+    pub admin_socket: Option<String>,
+    /// if set every round of batch requests logs, for each token with pending fees, the fees,
+    /// denom, gas price, relay cost, fee value and the reason for the decision made
+    pub batch_request_diagnostics: bool,
+    /// the maximum number of relay transactions in flight at once, further relays wait for one
+    /// to be mined before they are sent, zero does not limit them
+    pub max_in_flight_relays: usize,
+}
+
+impl RelayerConfig {
+    /// The power signatures must hold before a batch or logic call is relayed, zero when
+    /// only the contract's own requirement applies
+    pub fn min_relay_power(&self) -> u64 {
+        match self.min_relay_power_fraction {
+            Some(fraction) => (fraction * TOTAL_GRAVITY_POWER as f64).ceil() as u64,
+            None => 0,
+        }
+    }
+
+    /// Returns true if batches for this token should be requested
+    pub fn should_request_token(&self, token: EthAddress) -> bool {
+        match &self.batch_request_tokens {
+            Some(tokens) => tokens.contains(&token),
+            None => true,
+        }
+    }
+
+    /// Returns true if batches for this token should be relayed
+    pub fn should_relay_token(&self, token: EthAddress) -> bool {
+        match &self.batch_relay_tokens {
+            Some(tokens) => tokens.contains(&token),
+            None => true,
+        }
+    }
+
+    /// Returns true if batches for this token bypass the profitability checks
+    pub fn always_relays_token(&self, token: EthAddress) -> bool {
+        self.always_relay_tokens.contains(&token)
+    }
 }
 
 /// Relayer configuration that's is more easily parsable with toml
@@ -57,6 +185,95 @@ pub struct TomlRelayerConfig {
     pub logic_call_market_enabled: bool,
     #[serde(default = "default_relayer_loop_speed")]
     pub relayer_loop_speed: u64,
+    #[serde(default = "default_price_base_token")]
+    pub price_base_token: EthAddress,
+    #[serde(default = "default_batch_request_tokens")]
+    pub batch_request_tokens: Option<Vec<EthAddress>>,
+    #[serde(default = "default_batch_relay_tokens")]
+    pub batch_relay_tokens: Option<Vec<EthAddress>>,
+    #[serde(default = "default_nonce_management")]
+    pub nonce_management: NonceManagement,
+    #[serde(default = "default_batch_relay_order")]
+    pub batch_relay_order: BatchRelayOrder,
+    #[serde(default = "default_max_gas_price_increase")]
+    pub max_gas_price_increase: Option<f64>,
+    #[serde(default = "default_confirm_first")]
+    pub confirm_first: bool,
+    #[serde(default = "default_simulate_batch_relays")]
+    pub simulate_batch_relays: bool,
+    #[serde(default = "default_min_relay_power_fraction")]
+    pub min_relay_power_fraction: Option<f64>,
+    #[serde(default = "default_relay_prerequisite_valsets")]
+    pub relay_prerequisite_valsets: bool,
+    #[serde(default = "default_always_relay_tokens")]
+    pub always_relay_tokens: Vec<EthAddress>,
+    #[serde(default = "default_nonce_state_file")]
+    pub nonce_state_file: Option<String>,
+    #[serde(default = "default_batch_request_cooldown")]
+    pub batch_request_cooldown: u64,
+    #[serde(default = "default_admin_socket")]
+    pub admin_socket: Option<String>,
+    #[serde(default = "default_batch_request_diagnostics")]
+    pub batch_request_diagnostics: bool,
+    #[serde(default = "default_max_in_flight_relays")]
+    pub max_in_flight_relays: usize,
+}
+
+impl TomlRelayerConfig {
+    /// Checks the relayer config for problems, all problems found are returned
+    /// at once in a single ValidationError so that they can be corrected together
+    pub fn validate(&self) -> Result<(), GravityError> {
+        let mut problems = Vec::new();
+        self.valset_relaying_mode.validate(&mut problems);
+        self.batch_relaying_mode.validate(&mut problems);
+        if self.relayer_loop_speed == 0 {
+            problems.push("relayer_loop_speed must be greater than zero".to_string());
+        }
+        if let Some(increase) = self.max_gas_price_increase {
+            if !increase.is_finite() || increase < 1f64 {
+                problems.push(format!(
+                    "max_gas_price_increase {increase} must be a number of at least 1"
+                ));
+            }
+        }
+        if let Some(fraction) = self.min_relay_power_fraction {
+            if !(0f64..=1f64).contains(&fraction) {
+                problems.push(format!(
+                    "min_relay_power_fraction {fraction} must be between 0 and 1"
+                ));
+            }
+        }
+        if self.nonce_state_file.is_some() && self.nonce_management != NonceManagement::Local {
+            problems.push("nonce_state_file requires nonce_management = \"Local\"".to_string());
+        }
+        for token in self.always_relay_tokens.iter() {
+            if !self.should_request_token(*token) || !self.should_relay_token(*token) {
+                problems.push(format!(
+                    "always_relay_tokens contains {token} which is excluded by batch_request_tokens or batch_relay_tokens"
+                ));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(GravityError::ValidationError(format!(
+                "Invalid relayer config:\n{}",
+                problems.join("\n")
+            )))
+        }
+    }
+}
+
+/// Checks that an optional margin is present and usable, recording any problem
+fn validate_margin(name: &str, margin: Option<f64>, problems: &mut Vec<String>) {
+    match margin {
+        None => problems.push(format!("{name} requires a margin")),
+        Some(m) if !m.is_finite() || m < 0f64 => {
+            problems.push(format!("{name} margin {m} must be a non negative number"))
+        }
+        Some(_) => {}
+    }
 }
 
 impl From<TomlRelayerConfig> for RelayerConfig {
@@ -67,6 +284,22 @@ impl From<TomlRelayerConfig> for RelayerConfig {
             batch_request_mode: input.batch_request_mode,
             logic_call_market_enabled: input.logic_call_market_enabled,
             relayer_loop_speed: input.relayer_loop_speed,
+            price_base_token: input.price_base_token,
+            batch_request_tokens: input.batch_request_tokens,
+            batch_relay_tokens: input.batch_relay_tokens,
+            nonce_management: input.nonce_management,
+            batch_relay_order: input.batch_relay_order,
+            max_gas_price_increase: input.max_gas_price_increase,
+            confirm_first: input.confirm_first,
+            simulate_batch_relays: input.simulate_batch_relays,
+            min_relay_power_fraction: input.min_relay_power_fraction,
+            relay_prerequisite_valsets: input.relay_prerequisite_valsets,
+            always_relay_tokens: input.always_relay_tokens,
+            nonce_state_file: input.nonce_state_file,
+            batch_request_cooldown: input.batch_request_cooldown,
+            admin_socket: input.admin_socket,
+            batch_request_diagnostics: input.batch_request_diagnostics,
+            max_in_flight_relays: input.max_in_flight_relays,
         }
     }
 }
@@ -80,7 +313,8 @@ pub enum ValsetRelayingMode {
     /// relaying * margin
     ProfitableOnly { margin: f64 },
     /// Relay validator sets when continued operation of the chain
-    /// requires it, this will cost some ETH
+    /// requires it, this will cost some ETH. Valsets with a reward
+    /// worth more than their gas cost are also relayed
     Altruistic,
     /// Relay every validator set update, mostly for developer use
     EveryValset,
@@ -93,6 +327,19 @@ pub struct TomlValsetRelayingMode {
     margin: Option<f64>,
 }
 
+impl TomlValsetRelayingMode {
+    fn validate(&self, problems: &mut Vec<String>) {
+        match self.mode.as_str() {
+            "ProfitableOnly" | "profitableonly" | "PROFITABLEONLY" => {
+                validate_margin("valset_relaying_mode", self.margin, problems)
+            }
+            "Altruistic" | "altruistic" | "ALTRUISTIC" => {}
+            "EveryValset" | "everyvalset" | "EVERYVALSET" => {}
+            m => problems.push(format!("Unknown valset_relaying_mode {m}")),
+        }
+    }
+}
+
 impl From<TomlValsetRelayingMode> for ValsetRelayingMode {
     fn from(input: TomlValsetRelayingMode) -> Self {
         match input.mode.as_str() {
@@ -108,6 +355,29 @@ impl From<TomlValsetRelayingMode> for ValsetRelayingMode {
     }
 }
 
+/// How the relayer assigns nonces to the Ethereum transactions it submits
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum NonceManagement {
+    /// The Ethereum node is asked for the account nonce on every submission,
+    /// concurrent submissions may be given the same nonce
+    Node,
+    /// The next nonce is tracked locally so that concurrent and still pending
+    /// submissions get consecutive nonces, it is only moved up to the node's nonce
+    /// when a submission fails with a nonce error
+    Local,
+}
+
+/// The order in which batches of different tokens are relayed, batches of the same token
+/// are always relayed oldest first
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BatchRelayOrder {
+    /// No particular order
+    Unordered,
+    /// The batches with the highest fee value minus estimated gas cost are relayed
+    /// first, so that a limited ETH balance goes to the best opportunities
+    FeePriority,
+}
+
 /// The various possible modes for automatic requests of batches
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
 pub enum BatchRequestMode {
@@ -151,12 +421,66 @@ pub enum BatchRelayingMode {
     },
 }
 
+/// A version of WhitelistToken that is easy to serialize as toml, the
+/// values are left as strings so that they can be validated all at once
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct TomlWhitelistToken {
+    amount: String,
+    token: String,
+}
+
+impl From<TomlWhitelistToken> for WhitelistToken {
+    fn from(input: TomlWhitelistToken) -> Self {
+        WhitelistToken {
+            amount: Uint256::from_dec_or_hex_str_restricted(&input.amount).unwrap(),
+            token: input.token.parse().unwrap(),
+        }
+    }
+}
+
 /// A version of BatchRelaying mode that is easy to serialize as toml
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct TomlBatchRelayingMode {
     mode: String,
     margin: Option<f64>,
-    whitelist: Option<Vec<WhitelistToken>>,
+    whitelist: Option<Vec<TomlWhitelistToken>>,
+}
+
+impl TomlBatchRelayingMode {
+    fn validate(&self, problems: &mut Vec<String>) {
+        match self.mode.as_str() {
+            "EveryBatch" | "everybatch" | "EVERYBATCH" => {}
+            "ProfitableOnly" | "profitableonly" | "PROFITABLEONLY" => {
+                validate_margin("batch_relaying_mode", self.margin, problems)
+            }
+            "ProfitableWithWhitelist" | "profitablewithwhitelist" | "PROFITABLEWITHWHITELIST" => {
+                validate_margin("batch_relaying_mode", self.margin, problems);
+                match &self.whitelist {
+                    None => problems.push(
+                        "batch_relaying_mode ProfitableWithWhitelist requires a whitelist"
+                            .to_string(),
+                    ),
+                    Some(whitelist) => {
+                        for item in whitelist {
+                            if item.token.parse::<EthAddress>().is_err() {
+                                problems.push(format!(
+                                    "Whitelist token {} is not a valid Ethereum address",
+                                    item.token
+                                ));
+                            }
+                            if Uint256::from_dec_or_hex_str_restricted(&item.amount).is_err() {
+                                problems.push(format!(
+                                    "Whitelist amount {} for token {} is not a valid amount",
+                                    item.amount, item.token
+                                ));
+                            }
+                        }
+                    }
+                }
+            }
+            m => problems.push(format!("Unknown batch_relaying_mode {m}")),
+        }
+    }
 }
 
 impl From<TomlBatchRelayingMode> for BatchRelayingMode {
@@ -171,7 +495,12 @@ impl From<TomlBatchRelayingMode> for BatchRelayingMode {
             "ProfitableWithWhitelist" | "profitablewithwhitelist" | "PROFITABLEWITHWHITELIST" => {
                 BatchRelayingMode::ProfitableWithWhitelist {
                     margin: input.margin.unwrap(),
-                    whitelist: input.whitelist.unwrap(),
+                    whitelist: input
+                        .whitelist
+                        .unwrap()
+                        .into_iter()
+                        .map(|w| w.into())
+                        .collect(),
                 }
             }
             _ => panic!("Bad TomlBatchRelayingMode"),
@@ -206,6 +535,70 @@ fn default_relayer_loop_speed() -> u64 {
     600
 }
 
+fn default_price_base_token() -> EthAddress {
+    *WETH_CONTRACT_ADDRESS
+}
+
+fn default_batch_request_tokens() -> Option<Vec<EthAddress>> {
+    None
+}
+
+fn default_batch_relay_tokens() -> Option<Vec<EthAddress>> {
+    None
+}
+
+fn default_nonce_management() -> NonceManagement {
+    NonceManagement::Node
+}
+
+fn default_batch_relay_order() -> BatchRelayOrder {
+    BatchRelayOrder::Unordered
+}
+
+fn default_max_gas_price_increase() -> Option<f64> {
+    None
+}
+
+fn default_confirm_first() -> bool {
+    false
+}
+
+fn default_min_relay_power_fraction() -> Option<f64> {
+    None
+}
+
+fn default_relay_prerequisite_valsets() -> bool {
+    false
+}
+
+fn default_always_relay_tokens() -> Vec<EthAddress> {
+    Vec::new()
+}
+
+fn default_nonce_state_file() -> Option<String> {
+    None
+}
+
+fn default_batch_request_cooldown() -> u64 {
+    0
+}
+
+fn default_admin_socket() -> Option<String> {
+    None
+}
+
+fn default_batch_request_diagnostics() -> bool {
+    false
+}
+
+fn default_max_in_flight_relays() -> usize {
+    4
+}
+
+fn default_simulate_batch_relays() -> bool {
+    false
+}
+
 impl Default for RelayerConfig {
     fn default() -> Self {
         RelayerConfig {
@@ -214,6 +607,22 @@ impl Default for RelayerConfig {
             batch_relaying_mode: default_batch_relaying_mode().into(),
             logic_call_market_enabled: default_logic_call_market_enabled(),
             relayer_loop_speed: default_relayer_loop_speed(),
+            price_base_token: default_price_base_token(),
+            batch_request_tokens: default_batch_request_tokens(),
+            batch_relay_tokens: default_batch_relay_tokens(),
+            nonce_management: default_nonce_management(),
+            batch_relay_order: default_batch_relay_order(),
+            max_gas_price_increase: default_max_gas_price_increase(),
+            confirm_first: default_confirm_first(),
+            simulate_batch_relays: default_simulate_batch_relays(),
+            min_relay_power_fraction: default_min_relay_power_fraction(),
+            relay_prerequisite_valsets: default_relay_prerequisite_valsets(),
+            always_relay_tokens: default_always_relay_tokens(),
+            nonce_state_file: default_nonce_state_file(),
+            batch_request_cooldown: default_batch_request_cooldown(),
+            admin_socket: default_admin_socket(),
+            batch_request_diagnostics: default_batch_request_diagnostics(),
+            max_in_flight_relays: default_max_in_flight_relays(),
         }
     }
 }
@@ -226,26 +635,396 @@ impl Default for TomlRelayerConfig {
             batch_relaying_mode: default_batch_relaying_mode(),
             logic_call_market_enabled: default_logic_call_market_enabled(),
             relayer_loop_speed: default_relayer_loop_speed(),
+            price_base_token: default_price_base_token(),
+            batch_request_tokens: default_batch_request_tokens(),
+            batch_relay_tokens: default_batch_relay_tokens(),
+            nonce_management: default_nonce_management(),
+            batch_relay_order: default_batch_relay_order(),
+            max_gas_price_increase: default_max_gas_price_increase(),
+            confirm_first: default_confirm_first(),
+            simulate_batch_relays: default_simulate_batch_relays(),
+            min_relay_power_fraction: default_min_relay_power_fraction(),
+            relay_prerequisite_valsets: default_relay_prerequisite_valsets(),
+            always_relay_tokens: default_always_relay_tokens(),
+            nonce_state_file: default_nonce_state_file(),
+            batch_request_cooldown: default_batch_request_cooldown(),
+            admin_socket: default_admin_socket(),
+            batch_request_diagnostics: default_batch_request_diagnostics(),
+            max_in_flight_relays: default_max_in_flight_relays(),
         }
     }
 }
 
 /// Orchestrator configuration options
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct OrchestratorConfig {
     /// If this Orchestrator should run an integrated relayer or not
     #[serde(default = "default_relayer_enabled")]
     pub relayer_enabled: bool,
+    /// If this Orchestrator should run the Ethereum oracle or not, when disabled events must
+    /// be posted to Cosmos by a separate Orchestrator running only the oracle
+    #[serde(default = "default_oracle_enabled")]
+    pub oracle_enabled: bool,
+    /// If this Orchestrator should run the Eth signer or not, when disabled confirms must be
+    /// signed by a separate Orchestrator running only the signer
+    #[serde(default = "default_signer_enabled")]
+    pub signer_enabled: bool,
+    /// If the Eth signer should log insufficient fee errors on confirm submission and
+    /// retry with the minimum fee reported by the node instead of exiting. Intended for
+    /// testing, in production a fee error should be corrected by the operator
+    #[serde(default = "default_retry_on_insufficient_fees")]
+    pub retry_on_insufficient_fees: bool,
+    /// The address of a previous Gravity contract deployment, during a contract migration
+    /// the oracle will relay events from both contracts until the legacy contract's events
+    /// have all been observed on Cosmos
+    #[serde(default = "default_legacy_gravity_contract_address")]
+    pub legacy_gravity_contract_address: Option<EthAddress>,
+    /// If any of the Orchestrator loops fails to make progress for this many seconds
+    /// the Orchestrator exits with an error so that it can be restarted, zero disables
+    /// the watchdog. Otherwise this must be larger than the 13 second oracle loop and, when
+    /// the relayer is enabled, than relayer_loop_speed
+    #[serde(default = "default_loop_watchdog_timeout")]
+    pub loop_watchdog_timeout: u64,
+    /// If gzip compression of Cosmos gRPC responses should be requested, this reduces
+    /// bandwidth on event heavy chains at the cost of some CPU
+    #[serde(default = "default_grpc_compression")]
+    pub grpc_compression: bool,
+    /// Deposits from these Ethereum senders are credited to the provided Cosmos
+    /// receiver instead of the destination in the deposit event. Every validator
+    /// must use the same overrides or the deposit claims will never reach consensus
+    #[serde(default = "default_deposit_recipient_overrides")]
+    pub deposit_recipient_overrides: Vec<DepositRecipientOverride>,
+    /// If the Cosmos node reports a block height lower than one it previously reported
+    /// the oracle and signer pause until the height passes the previous maximum instead
+    /// of only logging the rollback
+    #[serde(default = "default_pause_on_cosmos_rollback")]
+    pub pause_on_cosmos_rollback: bool,
+    /// After submitting confirms the Eth signer waits up to this many Cosmos blocks for them
+    /// to appear in chain state before moving on, confirms that do not land are signed again
+    /// on the next iteration. Zero trusts broadcast success
+    #[serde(default = "default_confirm_landing_blocks")]
+    pub confirm_landing_blocks: u64,
+    /// A shell command run whenever the Orchestrator detects a condition that risks slashing,
+    /// it receives a JSON description of the condition on stdin
+    #[serde(default = "default_slashing_alert_command")]
+    pub slashing_alert_command: Option<String>,
+    /// When paying zero fees and the Orchestrator account has no tokens of any kind, initialize
+    /// it by sending it a single unit of a token from the key given with --funding-phrase
+    #[serde(default = "default_auto_initialize_account")]
+    pub auto_initialize_account: bool,
+    /// The gravity_id of the bridge this Orchestrator is meant to run against, if set startup
+    /// fails when the Cosmos chain or Gravity contract report a different one
+    #[serde(default = "default_expected_gravity_id")]
+    pub expected_gravity_id: Option<String>,
+    /// If set the oracle appends a JSON line to this file for every Ethereum event it submits
+    /// a claim for, recording the event nonce, block, type and the Cosmos txhash
+    #[serde(default = "default_event_history_path")]
+    pub event_history_path: Option<String>,
+    /// What the oracle does when it observes a deposit of an ERC20 that has never been
+    /// bridged before and is not a Cosmos originated asset
+    #[serde(default = "default_unknown_erc20_deposit_mode")]
+    pub unknown_erc20_deposit_mode: UnknownErc20DepositMode,
+    /// Additional Cosmos gRPC endpoints the Eth signer broadcasts confirms to alongside the
+    /// primary node, a confirm is submitted if any one of them accepts it
+    #[serde(default = "default_confirm_submission_endpoints")]
+    pub confirm_submission_endpoints: Vec<String>,
+    /// If the Cosmos node reports the same block height for this many Eth signer iterations
+    /// it is treated as stalled even though it reports it is moving, zero disables the check
+    #[serde(default = "default_stalled_height_iterations")]
+    pub stalled_height_iterations: u64,
+    /// When the Cosmos node is stalled the Eth signer switches to the next of the
+    /// confirm_submission_endpoints for queries and submissions
+    #[serde(default = "default_stalled_height_failover")]
+    pub stalled_height_failover: bool,
+    /// Sign a valset confirm at startup and check that it recovers to our Ethereum address,
+    /// refusing to start if it does not. Nothing is broadcast
+    #[serde(default = "default_signing_self_test")]
+    pub signing_self_test: bool,
+    /// Every this many oracle iterations the last event nonce the oracle is tracking is
+    /// compared with the one recorded for us on Cosmos and corrected, zero disables this
+    #[serde(default = "default_event_nonce_reconcile_iterations")]
+    pub event_nonce_reconcile_iterations: u64,
+    /// The oracle stops scanning for events this many blocks before the block it would otherwise
+    /// stop at, so that events from blocks that may still be reorged are not relayed
+    #[serde(default = "default_event_scan_lag")]
+    pub event_scan_lag: u64,
+    /// While this file exists the Orchestrator and Relayer skip all signing and submissions but
+    /// keep observing and reporting metrics, this also applies to the standalone relayer
+    #[serde(default = "default_pause_file")]
+    pub pause_file: Option<String>,
+    /// If the Cosmos node is syncing or waiting for the chain to start for longer than this
+    /// many seconds the Orchestrator exits with an error instead of waiting, zero waits forever
+    #[serde(default = "default_cosmos_not_ready_timeout")]
+    pub cosmos_not_ready_timeout: u64,
+    /// How many times the Eth signer tries to get the Gravity parameters at startup, backing off
+    /// between attempts, before exiting with an error. Zero retries forever
+    #[serde(default = "default_signer_startup_params_retries")]
+    pub signer_startup_params_retries: u64,
+    /// The most Ethereum events the oracle submits claims for in one iteration, the rest are
+    /// left for the following iterations. Zero removes the limit
+    #[serde(default = "default_max_events_per_iteration")]
+    pub max_events_per_iteration: u64,
+    /// The order the Eth signer looks for unsigned items in, valsets must come first since the
+    /// contract needs them to verify everything else
+    #[serde(default = "default_confirm_order")]
+    pub confirm_order: Vec<ConfirmKind>,
+    /// On startup the oracle always rescans this many blocks before the last block it had checked,
+    /// so that a crash while a block's events were being submitted can not skip them. Events Cosmos
+    /// has already seen are filtered out, so the rescan only costs some redundant log queries
+    #[serde(default = "default_startup_rewind_blocks")]
+    pub startup_rewind_blocks: u64,
+    /// What the oracle does when it observes the execution of a logic call to a contract that is
+    /// not on logic_call_contract_allowlist
+    #[serde(default = "default_logic_call_contract_mode")]
+    pub logic_call_contract_mode: LogicCallContractMode,
+    /// The logic contracts the oracle expects logic calls to, only checked when
+    /// logic_call_contract_mode is not Process
+    #[serde(default = "default_logic_call_contract_allowlist")]
+    pub logic_call_contract_allowlist: Vec<EthAddress>,
+}
+
+impl OrchestratorConfig {
+    /// Checks the orchestrator config for problems, all problems found are returned
+    /// at once in a single ValidationError so that they can be corrected together
+    pub fn validate(&self) -> Result<(), GravityError> {
+        let mut problems = Vec::new();
+        for kind in [
+            ConfirmKind::Valset,
+            ConfirmKind::Batch,
+            ConfirmKind::LogicCall,
+        ]
+        .iter()
+        {
+            if self.confirm_order.iter().filter(|k| *k == kind).count() != 1 {
+                problems.push(format!("confirm_order must contain {kind:?} exactly once"));
+            }
+        }
+        if !self.oracle_enabled && !self.signer_enabled {
+            problems
+                .push("at least one of oracle_enabled and signer_enabled must be set".to_string());
+        }
+        if self.confirm_order.first() != Some(&ConfirmKind::Valset) {
+            problems.push("confirm_order must start with Valset".to_string());
+        }
+        if self.logic_call_contract_mode != LogicCallContractMode::Process
+            && self.logic_call_contract_allowlist.is_empty()
+        {
+            problems.push(format!(
+                "logic_call_contract_mode {:?} requires a logic_call_contract_allowlist",
+                self.logic_call_contract_mode
+            ));
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(GravityError::ValidationError(format!(
+                "Invalid orchestrator config:\n{}",
+                problems.join("\n")
+            )))
+        }
+    }
+}
+
+/// The kinds of items the Eth signer submits confirms for
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ConfirmKind {
+    Valset,
+    Batch,
+    LogicCall,
+}
+
+/// The handling of deposits of ERC20s that have never been bridged before. Claims must be
+/// submitted in event nonce order, so a deposit can not be skipped without stalling the
+/// oracle, instead the claim is always submitted unless the oracle halts
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum UnknownErc20DepositMode {
+    /// Submit the claim, the Gravity module creates a new voucher denom for the token
+    Register,
+    /// Submit the claim as with Register, but log a warning and increment a metric
+    Warn,
+    /// Stop the Orchestrator before submitting the claim so an operator can review the token
+    Halt,
+}
+
+/// The handling of logic calls to contracts that are not on the allowlist. The Eth signer checks
+/// every call before signing it and in Halt mode stops instead of signing. The oracle checks
+/// executed calls again, as with deposits the claim can not be skipped without stalling the
+/// oracle, so it is submitted unless the oracle halts. An executed call whose contract can not
+/// be determined because it is no longer on Cosmos is only warned about in every mode, halting
+/// on it could never be resolved
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LogicCallContractMode {
+    /// Sign every call and submit every claim without checking the contract
+    Process,
+    /// Sign the call and submit the claim, but log a warning and increment a metric
+    Warn,
+    /// Stop the Orchestrator before signing the call or submitting the claim so an operator
+    /// can review the call
+    Halt,
+}
+
+/// Overrides the Cosmos receiver of deposits from a given Ethereum sender
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct DepositRecipientOverride {
+    /// The Ethereum address that sent the deposit
+    pub ethereum_sender: EthAddress,
+    /// The Cosmos address that will be credited with the deposit
+    pub cosmos_receiver: CosmosAddress,
 }
 
 fn default_relayer_enabled() -> bool {
     false
 }
 
+fn default_oracle_enabled() -> bool {
+    true
+}
+
+fn default_signer_enabled() -> bool {
+    true
+}
+
+fn default_retry_on_insufficient_fees() -> bool {
+    false
+}
+
+fn default_legacy_gravity_contract_address() -> Option<EthAddress> {
+    None
+}
+
+fn default_loop_watchdog_timeout() -> u64 {
+    0
+}
+
+fn default_grpc_compression() -> bool {
+    false
+}
+
+fn default_deposit_recipient_overrides() -> Vec<DepositRecipientOverride> {
+    Vec::new()
+}
+
+fn default_pause_on_cosmos_rollback() -> bool {
+    false
+}
+
+fn default_confirm_landing_blocks() -> u64 {
+    0
+}
+
+fn default_slashing_alert_command() -> Option<String> {
+    None
+}
+
+fn default_auto_initialize_account() -> bool {
+    false
+}
+
+fn default_expected_gravity_id() -> Option<String> {
+    None
+}
+
+fn default_event_history_path() -> Option<String> {
+    None
+}
+
+fn default_unknown_erc20_deposit_mode() -> UnknownErc20DepositMode {
+    UnknownErc20DepositMode::Warn
+}
+
+fn default_confirm_submission_endpoints() -> Vec<String> {
+    Vec::new()
+}
+
+fn default_stalled_height_iterations() -> u64 {
+    10
+}
+
+fn default_stalled_height_failover() -> bool {
+    false
+}
+
+fn default_signing_self_test() -> bool {
+    false
+}
+
+fn default_event_nonce_reconcile_iterations() -> u64 {
+    10
+}
+
+fn default_event_scan_lag() -> u64 {
+    0
+}
+
+fn default_pause_file() -> Option<String> {
+    None
+}
+
+fn default_cosmos_not_ready_timeout() -> u64 {
+    0
+}
+
+fn default_signer_startup_params_retries() -> u64 {
+    10
+}
+
+fn default_max_events_per_iteration() -> u64 {
+    500
+}
+
+fn default_confirm_order() -> Vec<ConfirmKind> {
+    vec![
+        ConfirmKind::Valset,
+        ConfirmKind::Batch,
+        ConfirmKind::LogicCall,
+    ]
+}
+
+fn default_startup_rewind_blocks() -> u64 {
+    10
+}
+
+fn default_logic_call_contract_mode() -> LogicCallContractMode {
+    LogicCallContractMode::Process
+}
+
+fn default_logic_call_contract_allowlist() -> Vec<EthAddress> {
+    Vec::new()
+}
+
 impl Default for OrchestratorConfig {
     fn default() -> Self {
         OrchestratorConfig {
             relayer_enabled: default_relayer_enabled(),
+            oracle_enabled: default_oracle_enabled(),
+            signer_enabled: default_signer_enabled(),
+            retry_on_insufficient_fees: default_retry_on_insufficient_fees(),
+            legacy_gravity_contract_address: default_legacy_gravity_contract_address(),
+            loop_watchdog_timeout: default_loop_watchdog_timeout(),
+            grpc_compression: default_grpc_compression(),
+            deposit_recipient_overrides: default_deposit_recipient_overrides(),
+            pause_on_cosmos_rollback: default_pause_on_cosmos_rollback(),
+            confirm_landing_blocks: default_confirm_landing_blocks(),
+            slashing_alert_command: default_slashing_alert_command(),
+            auto_initialize_account: default_auto_initialize_account(),
+            expected_gravity_id: default_expected_gravity_id(),
+            event_history_path: default_event_history_path(),
+            unknown_erc20_deposit_mode: default_unknown_erc20_deposit_mode(),
+            confirm_submission_endpoints: default_confirm_submission_endpoints(),
+            stalled_height_iterations: default_stalled_height_iterations(),
+            stalled_height_failover: default_stalled_height_failover(),
+            signing_self_test: default_signing_self_test(),
+            event_nonce_reconcile_iterations: default_event_nonce_reconcile_iterations(),
+            event_scan_lag: default_event_scan_lag(),
+            pause_file: default_pause_file(),
+            cosmos_not_ready_timeout: default_cosmos_not_ready_timeout(),
+            signer_startup_params_retries: default_signer_startup_params_retries(),
+            max_events_per_iteration: default_max_events_per_iteration(),
+            confirm_order: default_confirm_order(),
+            startup_rewind_blocks: default_startup_rewind_blocks(),
+            logic_call_contract_mode: default_logic_call_contract_mode(),
+            logic_call_contract_allowlist: default_logic_call_contract_allowlist(),
         }
     }
 }
@@ -259,6 +1038,23 @@ pub struct MetricsConfig {
     /// Bind to specified ip:port
     #[serde(default = "default_metrics_bind")]
     pub metrics_bind: String,
+    /// Also push metrics to a statsd or dogstatsd endpoint
+    #[serde(default = "default_statsd_sink")]
+    pub statsd_sink: StatsdSink,
+    /// The ip:port of the statsd endpoint
+    #[serde(default = "default_statsd_endpoint")]
+    pub statsd_endpoint: String,
+}
+
+/// The statsd protocol flavor to push metrics with, if any
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum StatsdSink {
+    /// Do not push metrics to statsd
+    None,
+    /// Plain statsd, labels are appended to the metric name
+    Statsd,
+    /// Dogstatsd, labels are sent as tags
+    Dogstatsd,
 }
 
 fn default_metrics_enabled() -> bool {
@@ -269,11 +1065,180 @@ fn default_metrics_bind() -> String {
     "127.0.0.1:6631".to_string()
 }
 
+fn default_statsd_sink() -> StatsdSink {
+    StatsdSink::None
+}
+
+fn default_statsd_endpoint() -> String {
+    "127.0.0.1:8125".to_string()
+}
+
 impl Default for MetricsConfig {
     fn default() -> Self {
         MetricsConfig {
             metrics_enabled: default_metrics_enabled(),
             metrics_bind: default_metrics_bind(),
+            statsd_sink: default_statsd_sink(),
+            statsd_endpoint: default_statsd_endpoint(),
+        }
+    }
+}
+
+/// The address family to prefer when resolving RPC hosts
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum AddressFamily {
+    /// Use whichever address the resolver returns first
+    Any,
+    Ipv4,
+    Ipv6,
+}
+
+/// DNS resolution options for the RPC hosts
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct DnsConfig {
+    /// If RPC hosts should be resolved once at startup and connected to by address,
+    /// only applies to http urls since https requires the hostname for TLS
+    #[serde(default = "default_dns_cache_enabled")]
+    pub dns_cache_enabled: bool,
+    /// The address family to prefer when a host resolves to more than one address
+    #[serde(default = "default_dns_address_family")]
+    pub dns_address_family: AddressFamily,
+}
+
+fn default_dns_cache_enabled() -> bool {
+    false
+}
+
+fn default_dns_address_family() -> AddressFamily {
+    AddressFamily::Any
+}
+
+impl Default for DnsConfig {
+    fn default() -> Self {
+        DnsConfig {
+            dns_cache_enabled: default_dns_cache_enabled(),
+            dns_address_family: default_dns_address_family(),
+        }
+    }
+}
+
+/// Logging options
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct LoggingConfig {
+    /// A log filter in the RUST_LOG format, for example "info,relayer=trace", used when
+    /// RUST_LOG is not set. Changes to it are applied while running without a restart
+    #[serde(default = "default_log_filter")]
+    pub log_filter: Option<String>,
+}
+
+fn default_log_filter() -> Option<String> {
+    None
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        LoggingConfig {
+            log_filter: default_log_filter(),
+        }
+    }
+}
+
+/// Options for the connections to the RPC servers
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct RpcConfig {
+    /// The user-agent sent with gRPC requests so that the Orchestrator's traffic can be
+    /// identified by shared RPC providers, None uses the default of the gRPC library
+    #[serde(default = "default_user_agent")]
+    pub user_agent: Option<String>,
+    /// How long in seconds each fallback url is given to respond when the configured
+    /// RPC url can not be reached, capped at the full RPC timeout
+    #[serde(default = "default_probe_timeout")]
+    pub probe_timeout: u64,
+    /// If set every Cosmos transaction is only signed after checking that the node reports this
+    /// chain-id, None trusts the chain-id reported by the node
+    #[serde(default = "default_expected_cosmos_chain_id")]
+    pub expected_cosmos_chain_id: Option<String>,
+    /// If set the Orchestrator and Relayer refuse to start unless the Ethereum node reports
+    /// this chain-id from eth_chainId, None trusts the node
+    #[serde(default = "default_expected_eth_chain_id")]
+    pub expected_eth_chain_id: Option<u64>,
+}
+
+fn default_user_agent() -> Option<String> {
+    None
+}
+
+fn default_probe_timeout() -> u64 {
+    5
+}
+
+fn default_expected_cosmos_chain_id() -> Option<String> {
+    None
+}
+
+fn default_expected_eth_chain_id() -> Option<u64> {
+    None
+}
+
+impl Default for RpcConfig {
+    fn default() -> Self {
+        RpcConfig {
+            user_agent: default_user_agent(),
+            probe_timeout: default_probe_timeout(),
+            expected_cosmos_chain_id: default_expected_cosmos_chain_id(),
+            expected_eth_chain_id: default_expected_eth_chain_id(),
+        }
+    }
+}
+
+/// The kind of async runtime gbt runs on
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RuntimeFlavor {
+    /// Tasks are spread over a pool of worker threads
+    MultiThread,
+    /// Every task runs on the main thread, the loops are I/O bound so this is enough for
+    /// them and uses the fewest resources on small hosts
+    CurrentThread,
+}
+
+/// Options for the async runtime, changes require a restart
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct RuntimeConfig {
+    #[serde(default = "default_runtime_flavor")]
+    pub flavor: RuntimeFlavor,
+    /// The number of worker threads of the multi thread runtime, None uses one per CPU core
+    #[serde(default = "default_worker_threads")]
+    pub worker_threads: Option<usize>,
+}
+
+impl RuntimeConfig {
+    pub fn validate(&self) -> Result<(), GravityError> {
+        match (self.flavor, self.worker_threads) {
+            (_, Some(0)) => Err(GravityError::ValidationError(
+                "Invalid runtime config:\nworker_threads must be greater than zero".to_string(),
+            )),
+            (RuntimeFlavor::CurrentThread, Some(_)) => Err(GravityError::ValidationError(
+                "Invalid runtime config:\nworker_threads can not be set with the CurrentThread flavor"
+                    .to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn default_runtime_flavor() -> RuntimeFlavor {
+    RuntimeFlavor::MultiThread
+}
+
+fn default_worker_threads() -> Option<usize> {
+    None
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        RuntimeConfig {
+            flavor: default_runtime_flavor(),
+            worker_threads: default_worker_threads(),
         }
     }
 }
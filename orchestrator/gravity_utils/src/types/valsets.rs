@@ -23,7 +23,7 @@ pub const GRAVITY_POWER_TO_PASS: u64 = 2863311530;
 
 /// takes in an amount of power in the gravity bridge, returns a percentage of total
 /// use this for printing values to users only, for accurate calculations use GRAVITY_POWER_TO_PASS
-fn gravity_power_to_percent(input: u64) -> f32 {
+pub fn gravity_power_to_percent(input: u64) -> f32 {
     (input as f32 / TOTAL_GRAVITY_POWER as f32) * 100f32
 }
 /// This trait implements an overarching interface for signature confirmations
@@ -33,6 +33,13 @@ pub trait Confirm {
     fn get_signature(&self) -> EthSignature;
 }
 
+/// Returns true if the signature of `confirm` is valid and recovers to its Ethereum address
+pub fn confirm_signature_recovers<T: Confirm>(confirm: &T, signed_message: &[u8]) -> bool {
+    let signature = confirm.get_signature();
+    signature.is_valid()
+        && signature.recover(signed_message).ok() == Some(confirm.get_eth_address())
+}
+
 pub fn get_hashmap<T: Confirm + Clone>(input: &[T]) -> HashMap<EthAddress, T> {
     let mut out = HashMap::new();
     for i in input.iter() {
@@ -135,31 +142,33 @@ impl Valset {
         let mut power_of_nonvoters = 0;
         let mut number_of_nonvoters = 0;
         for member in self.members.iter() {
-            if let Some(sig) = signatures_hashmap.get(&member.eth_address) {
+            let good_sig = signatures_hashmap.get(&member.eth_address).filter(|sig| {
                 assert_eq!(sig.get_eth_address(), member.eth_address);
-                assert!(sig.get_signature().is_valid());
-                let recover_key = sig.get_signature().recover(signed_message).unwrap();
-                if recover_key == sig.get_eth_address() {
-                    out.push(GravitySignature {
-                        power: member.power,
-                        eth_address: sig.get_eth_address(),
-                        v: sig.get_signature().v,
-                        r: sig.get_signature().r,
-                        s: sig.get_signature().s,
-                    });
-                    power_of_good_sigs += member.power;
-                } else {
+                let recovers = confirm_signature_recovers(*sig, signed_message);
+                if !recovers {
                     // the go code verifies signatures, if we ever see this it means
                     // that something has gone horribly wrong with our parsing or ordering
                     // in the orchestrator
-                    // this has been observed to occur spuriously, not currently root caused
-                    let err = format!(
+                    // this has been observed to occur spuriously, not currently root caused.
+                    // The contract only needs the threshold of power, so the signature is
+                    // left out rather than holding up a set that passes without it
+                    warn!(
                         "Found invalid signature from {} {:?}",
                         sig.get_eth_address(),
                         sig.get_signature()
                     );
-                    return Err(GravityError::ValidationError(err));
                 }
+                recovers
+            });
+            if let Some(sig) = good_sig {
+                out.push(GravitySignature {
+                    power: member.power,
+                    eth_address: sig.get_eth_address(),
+                    v: sig.get_signature().v,
+                    r: sig.get_signature().r,
+                    s: sig.get_signature().s,
+                });
+                power_of_good_sigs += member.power;
             } else {
                 out.push(GravitySignature {
                     power: member.power,
@@ -187,12 +196,29 @@ impl Valset {
         &self,
         signed_message: &[u8],
         signatures: &[T],
+    ) -> Result<Vec<GravitySignature>, GravityError> {
+        self.order_sigs_with_min_power(signed_message, signatures, 0)
+    }
+
+    /// The same as `order_sigs` but also requires the good signatures to hold at least
+    /// `min_power`, for relayers that want a larger margin than the contract requires
+    pub fn order_sigs_with_min_power<T: Confirm + Clone + Debug>(
+        &self,
+        signed_message: &[u8],
+        signatures: &[T],
+        min_power: u64,
     ) -> Result<Vec<GravitySignature>, GravityError> {
         let status = self.get_signature_status(signed_message, signatures)?;
         // now that we have collected the signatures we can determine if the measure has the votes to pass
         // and error early if it does not, otherwise the user will pay fees for a transaction that will
         // just throw
-        if status.power_of_good_sigs <= GRAVITY_POWER_TO_PASS {
+        if status.power_of_good_sigs < min_power {
+            Err(GravityError::ValidationError(format!(
+                "has {:.2}% power voting, below the configured minimum of {:.2}% required to relay",
+                gravity_power_to_percent(status.power_of_good_sigs),
+                gravity_power_to_percent(min_power),
+            )))
+        } else if status.power_of_good_sigs <= GRAVITY_POWER_TO_PASS {
             let message = format!(
                 "
                 has {}/{} or {:.2}% power voting! Can not execute on Ethereum!
@@ -213,6 +239,21 @@ impl Valset {
         }
     }
 
+    /// Returns the members that have not submitted any of `confirms`, most powerful first.
+    /// Signatures are not checked, this is for reporting who is not signing
+    pub fn missing_signers<T: Confirm>(&self, confirms: &[T]) -> Vec<ValsetMember> {
+        let signed: HashSet<EthAddress> = confirms.iter().map(|c| c.get_eth_address()).collect();
+        let mut missing: Vec<ValsetMember> = self
+            .members
+            .iter()
+            .filter(|m| !signed.contains(&m.eth_address))
+            .cloned()
+            .collect();
+        missing.sort();
+        missing.reverse();
+        missing
+    }
+
     /// A utility function to provide a HashMap of members for easy lookups
     pub fn to_hashmap(&self) -> HashMap<EthAddress, u64> {
         let mut res = HashMap::new();
@@ -444,3 +485,111 @@ impl Into<gravity_proto::gravity::BridgeValidator> for ValsetMember {
         r.into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use clarity::{utils::get_ethereum_msg_hash, PrivateKey as EthPrivateKey};
+    use rand::Rng;
+
+    use super::*;
+
+    #[derive(Debug, Clone)]
+    struct TestConfirm {
+        eth_address: EthAddress,
+        eth_signature: EthSignature,
+    }
+
+    impl Confirm for TestConfirm {
+        fn get_eth_address(&self) -> EthAddress {
+            self.eth_address
+        }
+        fn get_signature(&self) -> EthSignature {
+            self.eth_signature.clone()
+        }
+    }
+
+    #[test]
+    fn test_order_sigs_ignores_invalid_signature() {
+        let mut rng = rand::thread_rng();
+        let keys: Vec<EthPrivateKey> = (0..3)
+            .map(|_| EthPrivateKey::from_slice(&rng.gen::<[u8; 32]>()).unwrap())
+            .collect();
+        // the first two members hold enough power to pass without the third
+        let powers = [
+            1_500_000_000u64,
+            1_500_000_000,
+            TOTAL_GRAVITY_POWER - 3_000_000_000,
+        ];
+        let valset = Valset {
+            nonce: 1,
+            members: keys
+                .iter()
+                .zip(powers.iter())
+                .map(|(k, p)| ValsetMember {
+                    power: *p,
+                    eth_address: k.to_address(),
+                })
+                .collect(),
+            reward_amount: u256!(0),
+            reward_token: None,
+        };
+        let hash = get_ethereum_msg_hash(b"checkpoint");
+        let confirm = |key: &EthPrivateKey, hash: &[u8]| TestConfirm {
+            eth_address: key.to_address(),
+            eth_signature: key.sign_hash(hash),
+        };
+
+        // the third signature is over the wrong message
+        let sigs = vec![
+            confirm(&keys[0], &hash),
+            confirm(&keys[1], &hash),
+            confirm(&keys[2], &get_ethereum_msg_hash(b"other")),
+        ];
+        let ordered = valset.order_sigs(&hash, &sigs).unwrap();
+        assert_eq!(ordered[2].v, u256!(0));
+        // the good signatures pass the contract but not a 90% minimum
+        let min_power = (TOTAL_GRAVITY_POWER as f64 * 0.9) as u64;
+        assert!(valset
+            .order_sigs_with_min_power(&hash, &sigs, min_power)
+            .is_err());
+
+        // without the second member the remaining good power is below the threshold
+        let sigs = vec![
+            confirm(&keys[0], &hash),
+            confirm(&keys[2], &get_ethereum_msg_hash(b"other")),
+        ];
+        assert!(valset.order_sigs(&hash, &sigs).is_err());
+    }
+
+    #[test]
+    fn test_missing_signers() {
+        let mut rng = rand::thread_rng();
+        let keys: Vec<EthPrivateKey> = (0..3)
+            .map(|_| EthPrivateKey::from_slice(&rng.gen::<[u8; 32]>()).unwrap())
+            .collect();
+        let valset = Valset {
+            nonce: 1,
+            members: keys
+                .iter()
+                .zip([100u64, 300, 200].iter())
+                .map(|(k, p)| ValsetMember {
+                    power: *p,
+                    eth_address: k.to_address(),
+                })
+                .collect(),
+            reward_amount: u256!(0),
+            reward_token: None,
+        };
+        let hash = get_ethereum_msg_hash(b"checkpoint");
+        let sigs = vec![TestConfirm {
+            eth_address: keys[1].to_address(),
+            eth_signature: keys[1].sign_hash(&hash),
+        }];
+
+        let missing = valset.missing_signers(&sigs);
+        assert_eq!(missing.len(), 2);
+        assert_eq!(missing[0].eth_address, keys[2].to_address());
+        assert_eq!(missing[1].eth_address, keys[0].to_address());
+        assert!(valset.missing_signers::<TestConfirm>(&[]).len() == 3);
+    }
+}
@@ -0,0 +1,61 @@
+//! A watchdog for the long running loops of the Orchestrator and Relayer. Each loop reports
+//! progress once per iteration, if any loop stops reporting (for example because of a hung
+//! RPC call) the watchdog returns an error so that the process can exit and be restarted
+//! by an external supervisor.
+
+use std::{
+    cmp::min,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tokio::time::sleep;
+
+use crate::error::GravityError;
+
+/// The longest the watchdog will wait between checks
+const WATCHDOG_CHECK_SPEED: Duration = Duration::from_secs(10);
+
+/// The last time each named loop reported progress
+static HEARTBEATS: Mutex<Vec<(&'static str, Instant)>> = Mutex::new(Vec::new());
+
+/// Records that the named loop has made progress, this should be called once
+/// at the start of every loop iteration
+pub fn heartbeat(name: &'static str) {
+    let mut heartbeats = HEARTBEATS.lock().unwrap();
+    match heartbeats.iter_mut().find(|(n, _)| *n == name) {
+        Some((_, last)) => *last = Instant::now(),
+        None => heartbeats.push((name, Instant::now())),
+    }
+}
+
+/// Returns the name of a loop that has not reported progress within `timeout`
+fn find_stalled_loop(timeout: Duration) -> Option<&'static str> {
+    let heartbeats = HEARTBEATS.lock().unwrap();
+    heartbeats
+        .iter()
+        .find(|(_, last)| last.elapsed() > timeout)
+        .map(|(name, _)| *name)
+}
+
+/// Monitors all loops that have reported a heartbeat, returning an error naming the
+/// stalled loop if any has not progressed within `timeout`. Loops are only monitored
+/// after their first heartbeat so that long startup operations such as the oracle
+/// resync do not trigger the watchdog. This never returns Ok, so it can be joined with
+/// the loops it monitors, a zero `timeout` disables the watchdog
+pub async fn loop_watchdog(timeout: Duration) -> Result<(), GravityError> {
+    if timeout.is_zero() {
+        std::future::pending::<()>().await;
+    }
+
+    loop {
+        sleep(min(timeout, WATCHDOG_CHECK_SPEED)).await;
+        if let Some(name) = find_stalled_loop(timeout) {
+            return Err(GravityError::UnrecoverableError(format!(
+                "The {} loop has not made progress in {} seconds, exiting so that it can be restarted",
+                name,
+                timeout.as_secs()
+            )));
+        }
+    }
+}
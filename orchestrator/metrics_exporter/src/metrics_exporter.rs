@@ -1,11 +1,28 @@
-use std::net::SocketAddr;
+#[macro_use]
+extern crate log;
+
+mod statsd;
+
+use std::{
+    net::SocketAddr,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 use gravity_utils::types::MetricsConfig;
 use lazy_static::lazy_static;
 use prometheus_exporter::prometheus::{
-    register_int_counter, register_int_counter_vec, register_int_gauge_vec, IntCounter,
-    IntCounterVec, IntGaugeVec,
+    register_gauge, register_int_counter, register_int_counter_vec, register_int_gauge_vec, Gauge,
+    IntCounter, IntCounterVec, IntGaugeVec,
 };
+pub use statsd::start_statsd_sink;
+
+/// Set if the Prometheus exporter failed to start, recording metrics is then a no-op so
+/// that monitoring problems never affect the bridge itself
+static EXPORTER_FAILED: AtomicBool = AtomicBool::new(false);
+
+fn exporter_failed() -> bool {
+    EXPORTER_FAILED.load(Ordering::Relaxed)
+}
 
 lazy_static! {
 
@@ -36,43 +53,105 @@ lazy_static! {
     // Information gauges
     pub static ref LATEST_INFO: IntGaugeVec =
         register_int_gauge_vec!("orchestrator_information", "Latest orchestrator information", &["gauge"]).unwrap();
+    pub static ref PENDING_TRANSFERS: IntGaugeVec =
+        register_int_gauge_vec!("pending_transfers", "Transfers to Ethereum waiting to be batched", &["token"]).unwrap();
+    pub static ref RELAYER_NET_PNL: Gauge =
+        register_gauge!("relayer_net_pnl_eth", "Relay rewards minus gas spent since startup, in ETH").unwrap();
 }
 
 pub fn metrics_errors_counter(s: i32, e: &str) {
-    match s {
-        0 => ERROR.with_label_values(&[e]).inc(),
-        1 => ERROR_ETH.with_label_values(&[e]).inc(),
-        2 => ERROR_COSMOS.with_label_values(&[e]).inc(),
-        _ => ERROR_UNCLASSIFIED.with_label_values(&[e]).inc(),
+    let (counter, name) = match s {
+        0 => (&*ERROR, "orchestrator_errors_count_cosmos_eth"),
+        1 => (&*ERROR_ETH, "orchestrator_errors_count_eth"),
+        2 => (&*ERROR_COSMOS, "orchestrator_errors_count_cosmos"),
+        _ => (
+            &*ERROR_UNCLASSIFIED,
+            "orchestrator_errors_count_unclassified",
+        ),
+    };
+    if !exporter_failed() {
+        counter.with_label_values(&[e]).inc();
+        ERRORS_TOTAL.inc();
     }
-    ERRORS_TOTAL.inc()
+    statsd::send(name, 1, "c", Some(("error_message", e)));
+    statsd::send("orchestrator_errors_count_total", 1, "c", None);
 }
 
 pub fn metrics_warnings_counter(s: i32, e: &str) {
-    match s {
-        0 => WARNING.with_label_values(&[e]).inc(),
-        1 => WARNING_ETH.with_label_values(&[e]).inc(),
-        2 => WARNING_COSMOS.with_label_values(&[e]).inc(),
-        _ => WARNING_UNCLASSIFIED.with_label_values(&[e]).inc(),
+    let (counter, name) = match s {
+        0 => (&*WARNING, "orchestrator_warnings_count_cosmos_eth"),
+        1 => (&*WARNING_ETH, "orchestrator_warnings_count_eth"),
+        2 => (&*WARNING_COSMOS, "orchestrator_warnings_count_cosmos"),
+        _ => (
+            &*WARNING_UNCLASSIFIED,
+            "orchestrator_warnings_count_unclassified",
+        ),
+    };
+    if !exporter_failed() {
+        counter.with_label_values(&[e]).inc();
+        WARNINGS_TOTAL.inc();
     }
-    WARNINGS_TOTAL.inc()
+    statsd::send(name, 1, "c", Some(("warn_message", e)));
+    statsd::send("orchestrator_warnings_count_total", 1, "c", None);
 }
 
 pub fn metrics_latest(u: u64, e: &str) {
     match i64::try_from(u).is_ok() {
         true => {
-            LATEST_INFO.with_label_values(&[e]).set(u as i64);
+            if !exporter_failed() {
+                LATEST_INFO.with_label_values(&[e]).set(u as i64);
+            }
+            statsd::send(
+                "orchestrator_information",
+                u as i64,
+                "g",
+                Some(("gauge", e)),
+            );
         }
         false => {}
     }
 }
 
+pub fn metrics_pending_transfers(token: &str, count: u64) {
+    if let Ok(count) = i64::try_from(count) {
+        if !exporter_failed() {
+            PENDING_TRANSFERS.with_label_values(&[token]).set(count);
+        }
+        statsd::send("pending_transfers", count, "g", Some(("token", token)));
+    }
+}
+
+pub fn metrics_relayer_net_pnl(eth: f64) {
+    if !exporter_failed() {
+        RELAYER_NET_PNL.set(eth);
+    }
+    statsd::send("relayer_net_pnl_eth", eth, "g", None);
+}
+
+/// Starts the Prometheus exporter, if it can not be started a warning is logged and
+/// metrics are disabled rather than stopping the caller
 pub fn metrics_server(config: &MetricsConfig) {
     // Parse address used to bind exporter to.
     let addr_raw = &config.metrics_bind;
-    let addr: SocketAddr = addr_raw.parse().expect("can not parse listen addr");
+    let addr: SocketAddr = match addr_raw.parse() {
+        Ok(addr) => addr,
+        Err(e) => {
+            warn!(
+                "Can not parse metrics listen address {} {:?}, metrics are disabled",
+                addr_raw, e
+            );
+            EXPORTER_FAILED.store(true, Ordering::Relaxed);
+            return;
+        }
+    };
     // Start exporter
-    prometheus_exporter::start(addr).expect("can not start exporter");
+    if let Err(e) = prometheus_exporter::start(addr) {
+        warn!(
+            "Can not start metrics exporter on {} {:?}, metrics are disabled",
+            addr, e
+        );
+        EXPORTER_FAILED.store(true, Ordering::Relaxed);
+    }
 }
 
 /// Test overflowing bigint
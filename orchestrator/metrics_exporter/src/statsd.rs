@@ -0,0 +1,86 @@
+//! A minimal statsd / dogstatsd sink, when enabled every metric recorded through this crate
+//! is also pushed over UDP to the configured endpoint so that operators on statsd based
+//! monitoring stacks can consume the same counters and gauges as the Prometheus exporter
+
+use std::{
+    fmt::Display,
+    io,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+    sync::Mutex,
+};
+
+use gravity_utils::types::{MetricsConfig, StatsdSink};
+use lazy_static::lazy_static;
+
+struct Sink {
+    socket: UdpSocket,
+    dogstatsd: bool,
+}
+
+lazy_static! {
+    static ref SINK: Mutex<Option<Sink>> = Mutex::new(None);
+}
+
+pub fn start_statsd_sink(config: &MetricsConfig) {
+    let dogstatsd = match config.statsd_sink {
+        StatsdSink::None => return,
+        StatsdSink::Statsd => false,
+        StatsdSink::Dogstatsd => true,
+    };
+    match connect(&config.statsd_endpoint) {
+        Ok(socket) => *SINK.lock().unwrap() = Some(Sink { socket, dogstatsd }),
+        // metrics are best effort, without a sink sending is a no-op
+        Err(e) => warn!(
+            "Can not start statsd sink for {} {:?}, statsd metrics are disabled",
+            config.statsd_endpoint, e
+        ),
+    }
+}
+
+fn connect(endpoint: &str) -> io::Result<UdpSocket> {
+    let addr: SocketAddr = endpoint.to_socket_addrs()?.next().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::NotFound, "statsd endpoint did not resolve")
+    })?;
+    let bind = if addr.is_ipv6() {
+        "[::]:0"
+    } else {
+        "0.0.0.0:0"
+    };
+    let socket = UdpSocket::bind(bind)?;
+    socket.connect(addr)?;
+    socket.set_nonblocking(true)?;
+    Ok(socket)
+}
+
+/// Sends a metric to the statsd sink if one is running, dogstatsd receives the
+/// tag as a tag while plain statsd has the tag value appended to the metric name
+pub(crate) fn send(name: &str, value: impl Display, kind: &str, tag: Option<(&str, &str)>) {
+    let sink = SINK.lock().unwrap();
+    if let Some(sink) = &*sink {
+        let line = match (tag, sink.dogstatsd) {
+            (Some((key, tag_value)), true) => {
+                format!("{name}:{value}|{kind}|#{key}:{}", sanitize(tag_value))
+            }
+            (Some((_, tag_value)), false) => {
+                format!("{name}.{}:{value}|{kind}", sanitize(tag_value))
+            }
+            (None, _) => format!("{name}:{value}|{kind}"),
+        };
+        // metrics are best effort, a dropped packet is not worth reporting
+        let _ = sink.socket.send(line.as_bytes());
+    }
+}
+
+/// Replaces characters that have meaning in the statsd protocol
+fn sanitize(input: &str) -> String {
+    input
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
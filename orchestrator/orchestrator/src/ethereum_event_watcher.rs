@@ -1,28 +1,41 @@
 //! Ethereum Event watcher watches for events such as a deposit to the Gravity Ethereum contract or a validator set update
 //! or a transaction batch update. It then responds to these events by performing actions on the Cosmos chain if required
 
-use cosmos_gravity::{query::get_last_event_nonce_for_validator, send::send_ethereum_claims};
+use std::{collections::HashSet, future::Future};
+
+use cosmos_gravity::{
+    query::{get_erc20_to_denom, get_last_event_nonce_for_validator, get_latest_logic_calls},
+    send::send_ethereum_claims,
+};
 use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
 use gravity_utils::{
     clarity::{utils::bytes_to_hex_str, Address as EthAddress, Uint256},
+    connection_prep::denom_has_supply,
     deep_space::{coin::Coin, private_key::PrivateKey as CosmosPrivateKey, Contact},
     error::GravityError,
     get_block_delay, get_expected_block_delay,
     get_with_retry::{get_finalized_block_number_with_retry, get_latest_block_number_with_retry},
     types::{
-        event_signatures::*, Erc20DeployedEvent, LogicCallExecutedEvent, SendToCosmosEvent,
-        TransactionBatchExecutedEvent, ValsetUpdatedEvent,
+        event_signatures::*, DepositRecipientOverride, Erc20DeployedEvent, LogicCallContractMode,
+        LogicCallExecutedEvent, SendToCosmosEvent, TransactionBatchExecutedEvent,
+        UnknownErc20DepositMode, ValsetUpdatedEvent,
     },
     web30::{client::Web3, jsonrpc::error::Web3Error},
     USE_FINALIZATION,
 };
-use metrics_exporter::metrics_errors_counter;
+use metrics_exporter::{metrics_errors_counter, metrics_warnings_counter};
 use tonic::transport::Channel;
 
+use crate::event_history::{EventHistory, SubmittedEvents};
+
 #[derive(Clone, Copy)]
 pub struct CheckedNonces {
     pub block_number: Uint256,
+    /// the last event nonce Cosmos has for this validator
     pub event_nonce: Uint256,
+    /// the highest event nonce of the contract's own events in the checked blocks, whether or
+    /// not Cosmos had already seen them, None if the blocks contained no events
+    pub observed_event_nonce: Option<u64>,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -34,6 +47,14 @@ pub async fn check_for_events(
     our_private_key: CosmosPrivateKey,
     fee: Coin,
     starting_block: Uint256,
+    deposit_recipient_overrides: &[DepositRecipientOverride],
+    event_history: Option<&EventHistory>,
+    unknown_erc20_deposit_mode: UnknownErc20DepositMode,
+    logic_call_contract_mode: LogicCallContractMode,
+    logic_call_contract_allowlist: &[EthAddress],
+    scan_lag: u64,
+    max_events: u64,
+    known_erc20s: &mut HashSet<EthAddress>,
 ) -> Result<CheckedNonces, GravityError> {
     let our_cosmos_address = our_private_key.to_address(&contact.get_prefix()).unwrap();
 
@@ -69,6 +90,12 @@ pub async fn check_for_events(
                 )
             })?
     };
+    // the lag never moves the scan before where the last one ended, that block was already
+    // scanned so scanning it again adds no risk
+    let ending_block = match ending_block.checked_sub(Uint256::from_u64(scan_lag)) {
+        Some(block) if block > starting_block => block,
+        _ => starting_block,
+    };
 
     let deposits = web3
         .check_for_events(
@@ -137,6 +164,14 @@ pub async fn check_for_events(
         trace!("parsed erc20 deploys {:?}", erc20_deploys);
         let logic_calls = LogicCallExecutedEvent::from_logs(&logic_calls)?;
         trace!("logic call executions {:?}", logic_calls);
+        let mut observed_event_nonce = valsets
+            .iter()
+            .map(|e| e.event_nonce)
+            .chain(deposits.iter().map(|e| e.event_nonce))
+            .chain(withdraws.iter().map(|e| e.event_nonce))
+            .chain(erc20_deploys.iter().map(|e| e.event_nonce))
+            .chain(logic_calls.iter().map(|e| e.event_nonce))
+            .max();
 
         // note that starting block overlaps with our last checked block, because we have to deal with
         // the possibility that the relayer was killed after relaying only one of multiple events in a single
@@ -149,14 +184,89 @@ pub async fn check_for_events(
             contact.get_prefix(),
         )
         .await?;
-        let valsets = ValsetUpdatedEvent::filter_by_event_nonce(last_event_nonce, &valsets);
+        let mut valsets = ValsetUpdatedEvent::filter_by_event_nonce(last_event_nonce, &valsets);
         let deposits = SendToCosmosEvent::filter_by_event_nonce(last_event_nonce, &deposits);
-        let withdraws =
+        let mut deposits = apply_deposit_recipient_overrides(deposits, deposit_recipient_overrides);
+        if unknown_erc20_deposit_mode != UnknownErc20DepositMode::Register {
+            check_for_unknown_erc20_deposits(
+                contact,
+                grpc_client,
+                &deposits,
+                unknown_erc20_deposit_mode,
+                known_erc20s,
+            )
+            .await?;
+        }
+        let mut withdraws =
             TransactionBatchExecutedEvent::filter_by_event_nonce(last_event_nonce, &withdraws);
-        let erc20_deploys =
+        let mut erc20_deploys =
             Erc20DeployedEvent::filter_by_event_nonce(last_event_nonce, &erc20_deploys);
-        let logic_calls =
+        let mut logic_calls =
             LogicCallExecutedEvent::filter_by_event_nonce(last_event_nonce, &logic_calls);
+        if logic_call_contract_mode != LogicCallContractMode::Process && !logic_calls.is_empty() {
+            check_logic_call_contracts(
+                grpc_client,
+                &logic_calls,
+                logic_call_contract_mode,
+                logic_call_contract_allowlist,
+            )
+            .await?;
+        }
+
+        // logs are not guaranteed to come back in order if the node fetches block ranges in
+        // parallel, everything below expects each event type in ascending event nonce order
+        valsets.sort_by_key(|e| e.event_nonce);
+        deposits.sort_by_key(|e| e.event_nonce);
+        withdraws.sort_by_key(|e| e.event_nonce);
+        erc20_deploys.sort_by_key(|e| e.event_nonce);
+        logic_calls.sort_by_key(|e| e.event_nonce);
+        debug_assert!(
+            event_nonces_contiguous(
+                last_event_nonce,
+                valsets
+                    .iter()
+                    .map(|e| e.event_nonce)
+                    .chain(deposits.iter().map(|e| e.event_nonce))
+                    .chain(withdraws.iter().map(|e| e.event_nonce))
+                    .chain(erc20_deploys.iter().map(|e| e.event_nonce))
+                    .chain(logic_calls.iter().map(|e| e.event_nonce))
+                    .collect(),
+            ),
+            "Observed events are not contiguous after event nonce {}",
+            last_event_nonce
+        );
+
+        // events past the cap are carried over, the next scan starts from the block of the last
+        // event processed here and already processed events in that block are filtered out
+        let total_events = valsets.len()
+            + deposits.len()
+            + withdraws.len()
+            + erc20_deploys.len()
+            + logic_calls.len();
+        let ending_block = if max_events != 0 && total_events as u64 > max_events {
+            let cap = last_event_nonce + max_events;
+            valsets.retain(|e| e.event_nonce <= cap);
+            deposits.retain(|e| e.event_nonce <= cap);
+            withdraws.retain(|e| e.event_nonce <= cap);
+            erc20_deploys.retain(|e| e.event_nonce <= cap);
+            logic_calls.retain(|e| e.event_nonce <= cap);
+            observed_event_nonce = observed_event_nonce.map(|n| n.min(cap));
+            info!(
+                "Oracle observed {} events, submitting the first {} this iteration",
+                total_events, max_events
+            );
+            valsets
+                .iter()
+                .map(|e| e.block_height)
+                .chain(deposits.iter().map(|e| e.block_height))
+                .chain(withdraws.iter().map(|e| e.block_height))
+                .chain(erc20_deploys.iter().map(|e| e.block_height))
+                .chain(logic_calls.iter().map(|e| e.block_height))
+                .max()
+                .unwrap_or(starting_block)
+        } else {
+            ending_block
+        };
 
         if !valsets.is_empty() {
             info!(
@@ -199,48 +309,44 @@ pub async fn check_for_events(
             )
         }
 
-        let new_event_nonce = Uint256::from_u64(last_event_nonce);
-        if !deposits.is_empty()
-            || !withdraws.is_empty()
-            || !erc20_deploys.is_empty()
-            || !logic_calls.is_empty()
-            || !valsets.is_empty()
-        {
-            let res = send_ethereum_claims(
-                contact,
-                our_private_key,
+        let observed = ObservedEvents {
+            events: SubmittedEvents {
                 deposits,
                 withdraws,
                 erc20_deploys,
                 logic_calls,
                 valsets,
+            },
+            ending_block,
+            last_event_nonce,
+            observed_event_nonce,
+        };
+        submit_observed_events(observed, |events| async move {
+            // the events are moved into the claims, so keep a copy for the history if needed
+            let submitted = event_history.map(|_| events.clone());
+            let res = send_ethereum_claims(
+                contact,
+                our_private_key,
+                events.deposits,
+                events.withdraws,
+                events.erc20_deploys,
+                events.logic_calls,
+                events.valsets,
                 fee,
             )
             .await?;
-
-            let new_event_nonce = get_last_event_nonce_for_validator(
+            if let (Some(history), Some(submitted)) = (event_history, submitted) {
+                history.record(&res.txhash, &submitted);
+            }
+            let nonce = get_last_event_nonce_for_validator(
                 grpc_client,
                 our_cosmos_address,
                 contact.get_prefix(),
             )
             .await?;
-
-            info!("Current event nonce is {}", new_event_nonce);
-
-            // since we can't actually trust that the above txresponse is correct we have to check here
-            // we may be able to trust the tx response post grpc
-            if new_event_nonce == last_event_nonce {
-                return Err(GravityError::ValidationError(
-                    format!("Claims did not process, trying to update but still on {}, trying again in a moment, check txhash {} for errors", last_event_nonce, res.txhash),
-                ));
-            } else {
-                info!("Claims processed, new nonce {}", new_event_nonce);
-            }
-        }
-        Ok(CheckedNonces {
-            block_number: ending_block,
-            event_nonce: new_event_nonce,
+            Ok::<_, GravityError>((nonce, res.txhash))
         })
+        .await
     } else {
         error!("Failed to get events");
         metrics_errors_counter(1, "Failed to get events");
@@ -249,3 +355,389 @@ pub async fn check_for_events(
         ))))
     }
 }
+
+/// The events found by one scan of the Gravity contract that Cosmos has not seen from us yet
+struct ObservedEvents {
+    events: SubmittedEvents,
+    /// the last block of the scan
+    ending_block: Uint256,
+    /// the last event nonce Cosmos had for us when the scan was made
+    last_event_nonce: u64,
+    observed_event_nonce: Option<u64>,
+}
+
+/// Submits the claims for `observed` with `submit`, which returns the event nonce Cosmos
+/// reports for us afterwards and the txhash, and decides where the oracle continues from.
+/// check_for_events submits with send_ethereum_claims, it is passed in so that the handling
+/// of failed submissions can be tested
+async fn submit_observed_events<F, Fut>(
+    observed: ObservedEvents,
+    submit: F,
+) -> Result<CheckedNonces, GravityError>
+where
+    F: FnOnce(SubmittedEvents) -> Fut,
+    Fut: Future<Output = Result<(u64, String), GravityError>>,
+{
+    let ObservedEvents {
+        events,
+        ending_block,
+        last_event_nonce,
+        observed_event_nonce,
+    } = observed;
+    if events.deposits.is_empty()
+        && events.withdraws.is_empty()
+        && events.erc20_deploys.is_empty()
+        && events.logic_calls.is_empty()
+        && events.valsets.is_empty()
+    {
+        return Ok(CheckedNonces {
+            block_number: ending_block,
+            event_nonce: Uint256::from_u64(last_event_nonce),
+            observed_event_nonce,
+        });
+    }
+    checked_nonces_after_submission(
+        ending_block,
+        last_event_nonce,
+        observed_event_nonce,
+        submit(events).await,
+    )
+}
+
+/// Decides where the oracle continues from after submitting claims for the events up to
+/// `ending_block`, given the event nonce Cosmos reports for us afterwards and the txhash.
+/// Only claims Cosmos shows as processed advance the checked block, a failed submission or
+/// one that did not process is an error so that the caller keeps its last checked block
+/// and the same events are submitted again, they are never silently skipped
+fn checked_nonces_after_submission(
+    ending_block: Uint256,
+    last_event_nonce: u64,
+    observed_event_nonce: Option<u64>,
+    submission: Result<(u64, String), GravityError>,
+) -> Result<CheckedNonces, GravityError> {
+    let (current_event_nonce, txhash) = submission?;
+    info!("Current event nonce is {}", current_event_nonce);
+
+    // since we can't actually trust that the above txresponse is correct we have to check here
+    // we may be able to trust the tx response post grpc
+    if current_event_nonce == last_event_nonce {
+        return Err(GravityError::ValidationError(
+            format!("Claims did not process, trying to update but still on {}, trying again in a moment, check txhash {} for errors", last_event_nonce, txhash),
+        ));
+    }
+    info!("Claims processed, new nonce {}", current_event_nonce);
+    Ok(CheckedNonces {
+        block_number: ending_block,
+        event_nonce: Uint256::from_u64(current_event_nonce),
+        observed_event_nonce,
+    })
+}
+
+/// Returns true if `nonces` are exactly the event nonces following `last_event_nonce`
+fn event_nonces_contiguous(last_event_nonce: u64, mut nonces: Vec<u64>) -> bool {
+    nonces.sort_unstable();
+    nonces
+        .iter()
+        .enumerate()
+        .all(|(i, nonce)| *nonce == last_event_nonce + 1 + i as u64)
+}
+
+/// Looks for deposits of ERC20s that are not Cosmos originated and have no supply on Cosmos,
+/// meaning they have never been bridged before, and warns or halts depending on `mode`. ERC20s
+/// found to be bridged before are remembered in `known_erc20s` and not queried again. If Cosmos
+/// can't be queried the deposit is only warned about, unless `mode` is Halt
+async fn check_for_unknown_erc20_deposits(
+    contact: &Contact,
+    grpc_client: &mut GravityQueryClient<Channel>,
+    deposits: &[SendToCosmosEvent],
+    mode: UnknownErc20DepositMode,
+    known_erc20s: &mut HashSet<EthAddress>,
+) -> Result<(), GravityError> {
+    let mut checked = Vec::new();
+    for deposit in deposits {
+        if checked.contains(&deposit.erc20) || known_erc20s.contains(&deposit.erc20) {
+            continue;
+        }
+        checked.push(deposit.erc20);
+
+        let denom = match get_erc20_to_denom(grpc_client, deposit.erc20).await {
+            Ok(denom) => denom,
+            Err(e) => {
+                unknown_erc20_query_failed(mode, deposit.erc20, e)?;
+                continue;
+            }
+        };
+        let known = denom.cosmos_originated
+            || match denom_has_supply(&denom.denom, contact).await {
+                Ok(has_supply) => has_supply,
+                Err(e) => {
+                    unknown_erc20_query_failed(mode, deposit.erc20, e)?;
+                    continue;
+                }
+            };
+        if known {
+            known_erc20s.insert(deposit.erc20);
+            continue;
+        }
+        match mode {
+            UnknownErc20DepositMode::Register => {}
+            UnknownErc20DepositMode::Warn => {
+                warn!(
+                    "Deposit with event nonce {} is for ERC20 {} which has never been bridged, it will be credited as the new denom {}",
+                    deposit.event_nonce, deposit.erc20, denom.denom
+                );
+                metrics_warnings_counter(1, "Deposit of an unknown ERC20");
+            }
+            UnknownErc20DepositMode::Halt => {
+                return Err(GravityError::HaltError(format!(
+                    "Deposit with event nonce {} is for ERC20 {} which has never been bridged, halting as configured by unknown_erc20_deposit_mode",
+                    deposit.event_nonce, deposit.erc20
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Handles a failure to query whether `erc20` has been bridged before, the deposit must not be
+/// claimed unchecked in Halt mode so the error is returned and the events are retried, other
+/// modes only warn
+fn unknown_erc20_query_failed(
+    mode: UnknownErc20DepositMode,
+    erc20: EthAddress,
+    e: GravityError,
+) -> Result<(), GravityError> {
+    if mode == UnknownErc20DepositMode::Halt {
+        return Err(e);
+    }
+    warn!(
+        "Could not check if ERC20 {} has been bridged before, continuing {:?}",
+        erc20, e
+    );
+    metrics_warnings_counter(1, "Could not check a deposit for an unknown ERC20");
+    Ok(())
+}
+
+/// The result of checking the contract of an executed logic call against the allowlist
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogicCallContractCheck {
+    Allowed,
+    NotAllowed(EthAddress),
+    /// the call is no longer pending on Cosmos, because it timed out and was cleaned up or is
+    /// past the query limit, so its contract can't be determined
+    NotFound,
+}
+
+pub fn check_logic_call_contract(
+    contract: Option<EthAddress>,
+    allowlist: &[EthAddress],
+) -> LogicCallContractCheck {
+    match contract {
+        Some(c) if allowlist.contains(&c) => LogicCallContractCheck::Allowed,
+        Some(c) => LogicCallContractCheck::NotAllowed(c),
+        None => LogicCallContractCheck::NotFound,
+    }
+}
+
+/// Checks the contract of each executed logic call against `allowlist` and warns or halts
+/// depending on `mode`. The event does not include the contract, so it is looked up from the
+/// calls still pending on Cosmos. Once other validators have observed the call it is no
+/// longer there, which is the common case, so the Eth signer enforces the allowlist before
+/// signing and this is only a second check. A call that is no longer there is only warned
+/// about, even in Halt mode, since it will never reappear and halting would halt again on
+/// every restart
+async fn check_logic_call_contracts(
+    grpc_client: &mut GravityQueryClient<Channel>,
+    logic_calls: &[LogicCallExecutedEvent],
+    mode: LogicCallContractMode,
+    allowlist: &[EthAddress],
+) -> Result<(), GravityError> {
+    let pending = get_latest_logic_calls(grpc_client).await?;
+    for event in logic_calls {
+        let contract = pending
+            .iter()
+            .find(|c| {
+                c.invalidation_id == event.invalidation_id
+                    && c.invalidation_nonce == event.invalidation_nonce
+            })
+            .map(|c| c.logic_contract_address);
+        let contract = match check_logic_call_contract(contract, allowlist) {
+            LogicCallContractCheck::Allowed => continue,
+            LogicCallContractCheck::NotFound => {
+                warn!(
+                    "Logic call {}:{} with event nonce {} is no longer on Cosmos, its contract can not be checked against the logic_call_contract_allowlist",
+                    bytes_to_hex_str(&event.invalidation_id), event.invalidation_nonce, event.event_nonce
+                );
+                metrics_warnings_counter(1, "Logic call contract could not be checked");
+                continue;
+            }
+            LogicCallContractCheck::NotAllowed(contract) => contract,
+        };
+        match mode {
+            LogicCallContractMode::Process => {}
+            LogicCallContractMode::Warn => {
+                warn!(
+                    "Logic call {}:{} with event nonce {} executed {} which is not on the logic_call_contract_allowlist",
+                    bytes_to_hex_str(&event.invalidation_id), event.invalidation_nonce, event.event_nonce, contract
+                );
+                metrics_warnings_counter(1, "Logic call to a contract not on the allowlist");
+            }
+            LogicCallContractMode::Halt => {
+                return Err(GravityError::HaltError(format!(
+                    "Logic call {}:{} with event nonce {} executed {} which is not on the logic_call_contract_allowlist, halting as configured by logic_call_contract_mode",
+                    bytes_to_hex_str(&event.invalidation_id), event.invalidation_nonce, event.event_nonce, contract
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the Cosmos destination of deposits from senders with a configured override,
+/// this changes where funds land so every override applied is logged
+fn apply_deposit_recipient_overrides(
+    deposits: Vec<SendToCosmosEvent>,
+    overrides: &[DepositRecipientOverride],
+) -> Vec<SendToCosmosEvent> {
+    deposits
+        .into_iter()
+        .map(|mut deposit| {
+            if let Some(o) = overrides
+                .iter()
+                .find(|o| o.ethereum_sender == deposit.sender)
+            {
+                warn!(
+                    "Overriding the destination of deposit with event nonce {} from {}, {} will be credited instead of {}",
+                    deposit.event_nonce, deposit.sender, o.cosmos_receiver, deposit.destination
+                );
+                deposit.destination = o.cosmos_receiver.to_string();
+                deposit.validated_destination = Some(o.cosmos_receiver);
+            }
+            deposit
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use gravity_utils::clarity::constants::ZERO_ADDRESS;
+
+    use super::*;
+
+    #[test]
+    fn test_event_nonces_contiguous() {
+        assert!(event_nonces_contiguous(5, vec![]));
+        assert!(event_nonces_contiguous(5, vec![8, 6, 7]));
+        assert!(!event_nonces_contiguous(5, vec![6, 8]));
+        assert!(!event_nonces_contiguous(5, vec![7]));
+        assert!(!event_nonces_contiguous(5, vec![6, 6]));
+    }
+
+    #[test]
+    fn test_unknown_erc20_query_failed() {
+        let error = || GravityError::UnrecoverableError("bank query failed".to_string());
+        // a failed query must not stop claims from being submitted unless halting
+        unknown_erc20_query_failed(UnknownErc20DepositMode::Warn, ZERO_ADDRESS, error()).unwrap();
+        assert!(matches!(
+            unknown_erc20_query_failed(UnknownErc20DepositMode::Halt, ZERO_ADDRESS, error()),
+            Err(GravityError::UnrecoverableError(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_logic_call_contract() {
+        let allowed: EthAddress = "0x6Bd41fCdF129297c3524395d669c0865b3CA85B2"
+            .parse()
+            .unwrap();
+        let other: EthAddress = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            check_logic_call_contract(Some(allowed), &[allowed]),
+            LogicCallContractCheck::Allowed
+        );
+        assert_eq!(
+            check_logic_call_contract(Some(other), &[allowed]),
+            LogicCallContractCheck::NotAllowed(other)
+        );
+        // a call cleaned up on Cosmos is not the same as one off the allowlist
+        assert_eq!(
+            check_logic_call_contract(None, &[allowed]),
+            LogicCallContractCheck::NotFound
+        );
+    }
+
+    /// Runs the oracle's scan and submit cycle as the main loop does, keeping the last checked
+    /// block on errors, with a submission that fails, then one Cosmos does not process and then
+    /// one that succeeds
+    #[tokio::test]
+    async fn test_failed_submission_is_retried() {
+        let deposit = |event_nonce| SendToCosmosEvent {
+            erc20: ZERO_ADDRESS,
+            sender: ZERO_ADDRESS,
+            destination: String::new(),
+            validated_destination: None,
+            amount: Uint256::from_u64(1),
+            event_nonce,
+            block_height: Uint256::from_u64(150),
+        };
+        // Cosmos has event nonce 5 for us, so a scan from before block 150 finds 6 to 8
+        let scan = |from: Uint256| ObservedEvents {
+            events: SubmittedEvents {
+                deposits: if from <= Uint256::from_u64(150) {
+                    vec![deposit(6), deposit(7), deposit(8)]
+                } else {
+                    Vec::new()
+                },
+                ..Default::default()
+            },
+            ending_block: Uint256::from_u64(200),
+            last_event_nonce: 5,
+            observed_event_nonce: Some(8),
+        };
+        let responses = vec![
+            Err(GravityError::RecoverableError(
+                "insufficient fees".to_string(),
+            )),
+            Ok((5, "ABCD".to_string())),
+            Ok((8, "ABCD".to_string())),
+        ];
+
+        let mut last_checked_block = Uint256::from_u64(100);
+        let mut submitted = Vec::new();
+        for response in responses {
+            let res = submit_observed_events(scan(last_checked_block), |events| {
+                submitted.push(
+                    events
+                        .deposits
+                        .iter()
+                        .map(|d| d.event_nonce)
+                        .collect::<Vec<_>>(),
+                );
+                async move { response }
+            })
+            .await;
+            match res {
+                Ok(nonces) => {
+                    last_checked_block = nonces.block_number;
+                    assert_eq!(nonces.event_nonce, Uint256::from_u64(8));
+                }
+                // neither a failed nor an unprocessed submission may move past the events
+                Err(_) => assert_eq!(last_checked_block, Uint256::from_u64(100)),
+            }
+        }
+        // the same events were submitted every time until one went through
+        assert_eq!(submitted, vec![vec![6, 7, 8]; 3]);
+        assert_eq!(last_checked_block, Uint256::from_u64(200));
+
+        // the next scan starts past the events and has nothing to submit
+        let res = submit_observed_events(scan(last_checked_block), |_| async {
+            Err(GravityError::ValidationError(
+                "Nothing should be submitted".to_string(),
+            ))
+        })
+        .await
+        .unwrap();
+        assert_eq!(res.block_number, Uint256::from_u64(200));
+    }
+}
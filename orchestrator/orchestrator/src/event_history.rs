@@ -0,0 +1,126 @@
+//! An optional append only record of every Ethereum event the oracle has submitted a claim for.
+//! Each line of the file is a JSON object with the event type, event nonce, Ethereum block and
+//! the hash of the Cosmos transaction that carried the claim, so that operators can answer
+//! "did we relay event nonce X" after the fact without digging through logs.
+
+use std::{fs::OpenOptions, io::Write, path::PathBuf};
+
+use gravity_utils::{
+    clarity::Uint256,
+    types::{
+        Erc20DeployedEvent, LogicCallExecutedEvent, SendToCosmosEvent,
+        TransactionBatchExecutedEvent, ValsetUpdatedEvent,
+    },
+};
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct ProcessedEvent<'a, T: Serialize> {
+    event_type: &'static str,
+    event_nonce: u64,
+    block_height: Uint256,
+    cosmos_txhash: &'a str,
+    event: &'a T,
+}
+
+/// The events submitted together in a single claims transaction
+#[derive(Debug, Clone, Default)]
+pub struct SubmittedEvents {
+    pub deposits: Vec<SendToCosmosEvent>,
+    pub withdraws: Vec<TransactionBatchExecutedEvent>,
+    pub erc20_deploys: Vec<Erc20DeployedEvent>,
+    pub logic_calls: Vec<LogicCallExecutedEvent>,
+    pub valsets: Vec<ValsetUpdatedEvent>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EventHistory {
+    path: PathBuf,
+}
+
+impl EventHistory {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        EventHistory { path: path.into() }
+    }
+
+    /// Appends `events` to the history file. The history is an audit aid and not needed for
+    /// correct operation, so failures are logged rather than returned
+    pub fn record(&self, cosmos_txhash: &str, events: &SubmittedEvents) {
+        let mut lines = String::new();
+        for e in events.deposits.iter() {
+            lines += &line(
+                "SendToCosmos",
+                e.event_nonce,
+                e.block_height,
+                cosmos_txhash,
+                e,
+            );
+        }
+        for e in events.withdraws.iter() {
+            lines += &line(
+                "TransactionBatchExecuted",
+                e.event_nonce,
+                e.block_height,
+                cosmos_txhash,
+                e,
+            );
+        }
+        for e in events.erc20_deploys.iter() {
+            lines += &line(
+                "Erc20Deployed",
+                e.event_nonce,
+                e.block_height,
+                cosmos_txhash,
+                e,
+            );
+        }
+        for e in events.logic_calls.iter() {
+            lines += &line(
+                "LogicCallExecuted",
+                e.event_nonce,
+                e.block_height,
+                cosmos_txhash,
+                e,
+            );
+        }
+        for e in events.valsets.iter() {
+            lines += &line(
+                "ValsetUpdated",
+                e.event_nonce,
+                e.block_height,
+                cosmos_txhash,
+                e,
+            );
+        }
+
+        let res = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(lines.as_bytes()));
+        if let Err(e) = res {
+            error!(
+                "Failed to write event history to {} {:?}",
+                self.path.display(),
+                e
+            );
+        }
+    }
+}
+
+fn line<T: Serialize>(
+    event_type: &'static str,
+    event_nonce: u64,
+    block_height: Uint256,
+    cosmos_txhash: &str,
+    event: &T,
+) -> String {
+    let record = ProcessedEvent {
+        event_type,
+        event_nonce,
+        block_height,
+        cosmos_txhash,
+        event,
+    };
+    serde_json::to_string(&record).unwrap() + "\n"
+}
@@ -5,5 +5,7 @@
 extern crate log;
 
 pub mod ethereum_event_watcher;
+pub mod event_history;
 pub mod main_loop;
 pub mod oracle_resync;
+pub mod slashing_windows;
@@ -2,29 +2,49 @@
 //! that can only be run by a validator. This single binary the 'Orchestrator' runs not only these two rules but also the untrusted role of a relayer, that does not need any permissions and has it's
 //! own crate and binary so that anyone may run it.
 
-use std::{cmp::min, time::Duration};
+use std::{
+    cmp::min,
+    collections::HashSet,
+    future::Future,
+    time::{Duration, Instant},
+};
 
 use cosmos_gravity::{
     query::{
-        get_gravity_params, get_oldest_unsigned_logic_calls,
+        get_gravity_params, get_last_event_nonce_for_validator, get_oldest_unsigned_logic_calls,
         get_oldest_unsigned_transaction_batches, get_oldest_unsigned_valsets,
     },
-    send::{send_batch_confirm, send_logic_call_confirm, send_valset_confirms},
+    send::{send_batch_confirm, send_logic_call_confirm, send_valset_confirms, TIMEOUT},
 };
-use futures::future::{try_join, try_join3};
+use ethereum_gravity::utils::get_event_nonce;
+use futures::future::{select_ok, try_join3, try_join4};
 use gravity_proto::{
     cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse,
     gravity::query_client::QueryClient as GravityQueryClient,
 };
 use gravity_utils::{
-    clarity::{address::Address as EthAddress, u256, PrivateKey as EthPrivateKey, Uint256},
+    backoff::Backoff,
+    clarity::{
+        address::Address as EthAddress, constants::ZERO_ADDRESS, u256, utils::bytes_to_hex_str,
+        PrivateKey as EthPrivateKey, Uint256,
+    },
+    connection_prep::connect_grpc,
     deep_space::{
         client::ChainStatus, coin::Coin, error::CosmosGrpcError,
-        private_key::PrivateKey as CosmosPrivateKey, utils::FeeInfo, Contact,
+        private_key::PrivateKey as CosmosPrivateKey, utils::FeeInfo, Address as CosmosAddress,
+        Contact,
     },
     error::GravityError,
-    types::GravityBridgeToolsConfig,
+    get_with_retry::RETRY_TIME,
+    pause::submissions_paused,
+    signer_status::set_signer_behind,
+    slashing_alerts::{alert_slashing_risk, SLASHING_RISK_LOG_TARGET},
+    types::{
+        ConfirmKind, DepositRecipientOverride, GravityBridgeToolsConfig, LogicCall,
+        LogicCallContractMode, UnknownErc20DepositMode,
+    },
     u64_array_bigints,
+    watchdog::{heartbeat, loop_watchdog},
     web30::client::Web3,
 };
 use metrics_exporter::{metrics_errors_counter, metrics_latest, metrics_warnings_counter};
@@ -32,7 +52,12 @@ use relayer::main_loop::relayer_main_loop;
 use tokio::time::sleep;
 use tonic::transport::Channel;
 
-use crate::{ethereum_event_watcher::check_for_events, oracle_resync::get_last_checked_block};
+use crate::{
+    ethereum_event_watcher::{check_for_events, check_logic_call_contract, LogicCallContractCheck},
+    event_history::EventHistory,
+    oracle_resync::get_last_checked_block,
+    slashing_windows::log_slashing_windows,
+};
 
 /// The execution speed governing all loops in this file
 /// which is to say all loops started by Orchestrator main
@@ -45,6 +70,8 @@ pub const ETH_ORACLE_LOOP_SPEED: Duration = Duration::from_secs(13);
 /// meaning they will occupy the same thread, but since they do
 /// very little actual cpu bound work and spend the vast majority
 /// of all execution time sleeping this shouldn't be an issue at all.
+/// The relayer is optional and so are the oracle and the Eth signer, a validator that
+/// disables one of them is still responsible for running it elsewhere with the same keys
 #[allow(clippy::too_many_arguments)]
 pub async fn orchestrator_main_loop(
     cosmos_key: CosmosPrivateKey,
@@ -66,16 +93,71 @@ pub async fn orchestrator_main_loop(
         grpc_client.clone(),
         gravity_contract_address,
         fee.clone(),
+        config.orchestrator.legacy_gravity_contract_address,
+        config.orchestrator.deposit_recipient_overrides.clone(),
+        config.orchestrator.pause_on_cosmos_rollback,
+        config
+            .orchestrator
+            .event_history_path
+            .as_ref()
+            .map(EventHistory::new),
+        config.orchestrator.unknown_erc20_deposit_mode,
+        config.orchestrator.logic_call_contract_mode,
+        config.orchestrator.logic_call_contract_allowlist.clone(),
+        config.orchestrator.event_nonce_reconcile_iterations,
+        config.orchestrator.event_scan_lag,
+        config.orchestrator.max_events_per_iteration,
+        config.orchestrator.startup_rewind_blocks,
+        config.orchestrator.cosmos_not_ready_timeout,
     );
 
+    // when the events are posted elsewhere the oracle is never started, see oracle_enabled
+    let a = async {
+        if config.orchestrator.oracle_enabled {
+            a.await
+        } else {
+            warn!("The Ethereum oracle is disabled, events must be posted by a separate oracle to avoid slashing");
+            Ok(())
+        }
+    };
+
+    let mut confirm_contacts = Vec::new();
+    for url in config.orchestrator.confirm_submission_endpoints.iter() {
+        match Contact::new(url, TIMEOUT, &contact.get_prefix()) {
+            Ok(c) => confirm_contacts.push(c),
+            Err(e) => error!("Invalid confirm submission endpoint {} {:?}", url, e),
+        }
+    }
+
     let b = eth_signer_main_loop(
         cosmos_key,
         ethereum_key,
         contact.clone(),
         grpc_client.clone(),
         fee.clone(),
+        config.orchestrator.retry_on_insufficient_fees,
+        config.orchestrator.pause_on_cosmos_rollback,
+        config.orchestrator.confirm_landing_blocks,
+        confirm_contacts,
+        config.orchestrator.stalled_height_iterations,
+        config.orchestrator.stalled_height_failover,
+        config.orchestrator.cosmos_not_ready_timeout,
+        config.orchestrator.signer_startup_params_retries,
+        config.orchestrator.confirm_order.clone(),
+        config.orchestrator.logic_call_contract_mode,
+        config.orchestrator.logic_call_contract_allowlist.clone(),
     );
 
+    // when the confirms are signed elsewhere the signer is never started, see signer_enabled
+    let b = async {
+        if config.orchestrator.signer_enabled {
+            b.await
+        } else {
+            warn!("The Eth signer is disabled, confirms must be signed by a separate signer to avoid slashing");
+            Ok(())
+        }
+    };
+
     let c = relayer_main_loop(
         ethereum_key,
         Some(cosmos_key),
@@ -88,12 +170,18 @@ pub async fn orchestrator_main_loop(
         &config.relayer,
     );
 
+    // the loops only exit on error, the watchdog makes sure that a loop that hangs
+    // also results in an error rather than silently stalling
+    let d = loop_watchdog(Duration::from_secs(
+        config.orchestrator.loop_watchdog_timeout,
+    ));
+
     // if the relayer is not enabled we just don't start the future
     if config.orchestrator.relayer_enabled {
-        if let Err(e) = try_join3(a, b, c).await {
+        if let Err(e) = try_join4(a, b, c, d).await {
             return Err(e);
         }
-    } else if let Err(e) = try_join(a, b).await {
+    } else if let Err(e) = try_join3(a, b, d).await {
         return Err(e);
     }
 
@@ -101,9 +189,24 @@ pub async fn orchestrator_main_loop(
 }
 
 const DELAY: Duration = Duration::from_secs(5);
+/// The longest wait between attempts to get the Gravity parameters at startup
+const MAX_PARAMS_RETRY_TIME: Duration = Duration::from_secs(60);
+/// How long to wait for each block while checking that confirms have landed
+const CONFIRM_LANDING_BLOCK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A legacy Gravity contract watched during a contract migration. Its events have their own
+/// event nonces, so its progress is tracked separately from the primary contract
+#[derive(Clone, Copy)]
+struct LegacyWatcher {
+    address: EthAddress,
+    last_checked_block: Uint256,
+    /// the highest event nonce of the legacy contract's events scanned so far
+    last_observed_nonce: u64,
+}
 
 /// This function is responsible for making sure that Ethereum events are retrieved from the Ethereum blockchain
 /// and ferried over to Cosmos where they will be used to issue tokens or process batches.
+#[allow(clippy::too_many_arguments)]
 pub async fn eth_oracle_main_loop(
     cosmos_key: CosmosPrivateKey,
     web3: Web3,
@@ -111,10 +214,47 @@ pub async fn eth_oracle_main_loop(
     grpc_client: GravityQueryClient<Channel>,
     gravity_contract_address: EthAddress,
     fee: Coin,
+    legacy_gravity_contract_address: Option<EthAddress>,
+    deposit_recipient_overrides: Vec<DepositRecipientOverride>,
+    pause_on_cosmos_rollback: bool,
+    event_history: Option<EventHistory>,
+    unknown_erc20_deposit_mode: UnknownErc20DepositMode,
+    logic_call_contract_mode: LogicCallContractMode,
+    logic_call_contract_allowlist: Vec<EthAddress>,
+    event_nonce_reconcile_iterations: u64,
+    event_scan_lag: u64,
+    max_events_per_iteration: u64,
+    startup_rewind_blocks: u64,
+    cosmos_not_ready_timeout: u64,
 ) -> Result<(), GravityError> {
     let our_cosmos_address = cosmos_key.to_address(&contact.get_prefix()).unwrap();
     let long_timeout_web30 = Web3::new(&web3.get_url(), Duration::from_secs(120));
 
+    // during a contract migration we also watch the legacy contract, tracking its
+    // last checked block and event nonce separately from the primary contract
+    let mut legacy_watcher: Option<LegacyWatcher> = None;
+    if let Some(legacy_address) = legacy_gravity_contract_address {
+        info!(
+            "Watching legacy Gravity contract {} until all of its events are observed",
+            legacy_address
+        );
+        let legacy_last_checked_block = get_last_checked_block(
+            grpc_client.clone(),
+            our_cosmos_address,
+            contact.get_prefix(),
+            legacy_address,
+            &long_timeout_web30,
+        )
+        .await;
+        let legacy_last_checked_block =
+            rewind_start_block(legacy_last_checked_block, startup_rewind_blocks);
+        legacy_watcher = Some(LegacyWatcher {
+            address: legacy_address,
+            last_checked_block: legacy_last_checked_block,
+            last_observed_nonce: 0,
+        });
+    }
+
     let mut last_checked_block: Uint256 = get_last_checked_block(
         grpc_client.clone(),
         our_cosmos_address,
@@ -123,15 +263,26 @@ pub async fn eth_oracle_main_loop(
         &long_timeout_web30,
     )
     .await;
+    last_checked_block = rewind_start_block(last_checked_block, startup_rewind_blocks);
 
     // In case of governance vote to unhalt bridge, need to replay old events. Keep track of the
     // last checked event nonce to detect when this happens
     let mut last_checked_event = u256!(0);
-    info!("Oracle resync complete, Oracle now operational");
+    // ERC20s known to have been bridged before, see unknown_erc20_deposit_mode
+    let mut known_erc20s = HashSet::new();
+    let mut iterations = 0u64;
+    let mut cosmos_height = CosmosHeightTracker::default();
+    // the oracle is only operational once it has processed a block range end to end, until
+    // then a failing Ethereum or Cosmos path just keeps it retrying
+    let mut operational = false;
+    metrics_latest(0, "oracle_operational");
+    info!("Oracle resync complete, waiting for the first successful event check");
     let mut grpc_client = grpc_client;
+    let mut not_ready_since = None;
 
     loop {
-        let _ = tokio::join!(
+        heartbeat("Ethereum oracle");
+        let (halt, _) = tokio::join!(
             async {
                 let latest_eth_block = web3.eth_block_number().await;
                 let latest_cosmos_block = contact.get_chain_status().await;
@@ -147,16 +298,34 @@ pub async fn eth_oracle_main_loop(
                         metrics_latest(block_height, "latest_cosmos_block");
                         // Converting into u64
                         metrics_latest(latest_eth_block.resize_to_u64(), "latest_eth_block");
+                        not_ready_since = None;
+
+                        if !cosmos_height.check(block_height, "Eth oracle")
+                            && pause_on_cosmos_rollback
+                        {
+                            sleep(DELAY).await;
+                            return None;
+                        }
                     }
                     (Ok(_latest_eth_block), Ok(ChainStatus::Syncing)) => {
                         warn!("Cosmos node syncing, Eth oracle paused");
                         metrics_warnings_counter(2, "Cosmos node syncing");
+                        if let Err(e) =
+                            check_not_ready_timeout(&mut not_ready_since, cosmos_not_ready_timeout)
+                        {
+                            return Some(e);
+                        }
                         sleep(DELAY).await;
                         return None;
                     }
                     (Ok(_latest_eth_block), Ok(ChainStatus::WaitingToStart)) => {
                         warn!("Cosmos node syncing waiting for chain start, Eth oracle paused");
                         metrics_warnings_counter(2, "Cosmos node syncing waiting for chain start");
+                        if let Err(e) =
+                            check_not_ready_timeout(&mut not_ready_since, cosmos_not_ready_timeout)
+                        {
+                            return Some(e);
+                        }
                         sleep(DELAY).await;
                         return None;
                     }
@@ -180,6 +349,41 @@ pub async fn eth_oracle_main_loop(
                     }
                 }
 
+                iterations += 1;
+                if event_nonce_reconcile_iterations != 0
+                    && iterations % event_nonce_reconcile_iterations == 0
+                {
+                    match get_last_event_nonce_for_validator(
+                        &mut grpc_client,
+                        our_cosmos_address,
+                        contact.get_prefix(),
+                    )
+                    .await
+                    {
+                        Ok(nonce) => {
+                            if reconcile_last_checked_event(
+                                &mut last_checked_event,
+                                Uint256::from_u64(nonce),
+                            ) {
+                                last_checked_block = get_last_checked_block(
+                                    grpc_client.clone(),
+                                    our_cosmos_address,
+                                    contact.get_prefix(),
+                                    gravity_contract_address,
+                                    &long_timeout_web30,
+                                )
+                                .await;
+                            }
+                        }
+                        Err(e) => warn!("Failed to get our last event nonce to reconcile {:?}", e),
+                    }
+                }
+
+                if submissions_paused() {
+                    warn!("Submissions are paused, the oracle is not submitting claims");
+                    return None;
+                }
+
                 // Relays events from Ethereum -> Cosmos
                 match check_for_events(
                     &web3,
@@ -189,10 +393,23 @@ pub async fn eth_oracle_main_loop(
                     cosmos_key,
                     fee.clone(),
                     last_checked_block,
+                    &deposit_recipient_overrides,
+                    event_history.as_ref(),
+                    unknown_erc20_deposit_mode,
+                    logic_call_contract_mode,
+                    &logic_call_contract_allowlist,
+                    event_scan_lag,
+                    max_events_per_iteration,
+                    &mut known_erc20s,
                 )
                 .await
                 {
                     Ok(nonces) => {
+                        if !operational {
+                            info!("Oracle now operational");
+                            metrics_latest(1, "oracle_operational");
+                            operational = true;
+                        }
                         // this output CheckedNonces is accurate unless a governance vote happens
                         last_checked_block = nonces.block_number;
                         if last_checked_event > nonces.event_nonce {
@@ -200,28 +417,98 @@ pub async fn eth_oracle_main_loop(
                             info!(
                                 "Governance unhalt vote must have happened, resetting the block to check!"
                             );
+                            // the replay may search far back in history, so this uses the same
+                            // long timeout client as the resync at startup
                             last_checked_block = get_last_checked_block(
                                 grpc_client.clone(),
                                 our_cosmos_address,
                                 contact.get_prefix(),
                                 gravity_contract_address,
-                                &web3,
+                                &long_timeout_web30,
                             )
                             .await;
                         }
                         last_checked_event = nonces.event_nonce;
                         metrics_latest(last_checked_event.resize_to_u64(), "last_checked_event");
                     }
+                    Err(GravityError::HaltError(e)) => {
+                        // the config asked us to halt, for example unknown_erc20_deposit_mode,
+                        // so exit and let the operator intervene. Other errors, even
+                        // unrecoverable ones such as a lagging finalized block, are retried
+                        return Some(GravityError::HaltError(e));
+                    }
                     Err(e) => {
+                        // last_checked_block is left where it was, so events whose claims failed
+                        // to submit are scanned and submitted again next iteration
                         error!("Failed to get events for block range, Check your Eth node and Cosmos gRPC {:?}", e);
                         metrics_errors_counter(0, "Failed to get events for block range");
                     }
                 }
 
-                Some(())
+                // Relays events from the legacy contract during a migration, once the legacy
+                // contract has no events that have not been observed on Cosmos we stop watching it
+                if let Some(mut legacy) = legacy_watcher {
+                    match check_for_events(
+                        &web3,
+                        &contact,
+                        &mut grpc_client,
+                        legacy.address,
+                        cosmos_key,
+                        fee.clone(),
+                        legacy.last_checked_block,
+                        &deposit_recipient_overrides,
+                        event_history.as_ref(),
+                        unknown_erc20_deposit_mode,
+                        logic_call_contract_mode,
+                        &logic_call_contract_allowlist,
+                        event_scan_lag,
+                        max_events_per_iteration,
+                        &mut known_erc20s,
+                    )
+                    .await
+                    {
+                        Ok(nonces) => {
+                            // the event nonce in `nonces` is the one Cosmos has for this
+                            // validator, which counts the primary contract's events, so the
+                            // legacy contract is compared against its own observed events
+                            legacy.last_checked_block = nonces.block_number;
+                            if let Some(observed) = nonces.observed_event_nonce {
+                                legacy.last_observed_nonce =
+                                    legacy.last_observed_nonce.max(observed);
+                            }
+                            match get_event_nonce(legacy.address, ZERO_ADDRESS, &web3).await {
+                                Ok(legacy_event_nonce)
+                                    if legacy_event_nonce <= legacy.last_observed_nonce =>
+                                {
+                                    info!(
+                                        "All events from legacy Gravity contract {} have been observed, no longer watching it",
+                                        legacy.address
+                                    );
+                                    legacy_watcher = None;
+                                }
+                                _ => legacy_watcher = Some(legacy),
+                            }
+                        }
+                        Err(GravityError::HaltError(e)) => {
+                            return Some(GravityError::HaltError(e));
+                        }
+                        Err(e) => {
+                            error!(
+                                "Failed to get events from legacy Gravity contract {} {:?}",
+                                legacy.address, e
+                            );
+                            metrics_errors_counter(0, "Failed to get events from legacy contract");
+                        }
+                    }
+                }
+
+                None
             },
             tokio::time::sleep(ETH_SIGNER_LOOP_SPEED)
         );
+        if let Some(e) = halt {
+            return Err(e);
+        }
     }
 }
 
@@ -235,11 +522,42 @@ pub async fn eth_signer_main_loop(
     contact: Contact,
     grpc_client: GravityQueryClient<Channel>,
     fee: Coin,
+    retry_on_insufficient_fees: bool,
+    pause_on_cosmos_rollback: bool,
+    confirm_landing_blocks: u64,
+    confirm_contacts: Vec<Contact>,
+    stalled_height_iterations: u64,
+    stalled_height_failover: bool,
+    cosmos_not_ready_timeout: u64,
+    startup_params_retries: u64,
+    confirm_order: Vec<ConfirmKind>,
+    logic_call_contract_mode: LogicCallContractMode,
+    logic_call_contract_allowlist: Vec<EthAddress>,
 ) -> Result<(), GravityError> {
     let our_cosmos_address = cosmos_key.to_address(&contact.get_prefix()).unwrap();
+    // confirms are submitted through the primary node and any additional endpoints
+    let mut submission_contacts = vec![contact.clone()];
+    submission_contacts.extend(confirm_contacts);
+    let mut contact = contact;
     let mut grpc_client = grpc_client;
+    let mut fee = fee;
+    let mut cosmos_height = CosmosHeightTracker::default();
+    let mut last_cosmos_height = 0;
+    let mut stalled_iterations = 0;
+    let mut not_ready_since = None;
+    // set once confirms have been sent, cleared when the signer has nothing left to sign
+    let mut catching_up = false;
+
+    wait_for_gravity_params(&mut grpc_client, startup_params_retries).await?;
+    // sampling the block time takes a while, the signer must not wait for it
+    tokio::spawn(log_slashing_windows(contact.clone(), grpc_client.clone()));
 
     loop {
+        heartbeat("Ethereum signer");
+        // only set once the iteration has checked everything and found nothing to sign
+        let mut caught_up = false;
+        let mut sent_confirms = false;
+        let mut all_checked = true;
         let (async_result, _) = tokio::join!(
             async {
                 // repeatedly refreshing the parameters here maintains loop correctness
@@ -248,9 +566,14 @@ pub async fn eth_signer_main_loop(
                 let params = match get_gravity_params(&mut grpc_client).await {
                     Ok(p) => p,
                     Err(e) => {
-                        error!("Failed to get Gravity parameters with {} correct your Cosmos gRPC connection immediately, you are risking slashing",e);
+                        error!(target: SLASHING_RISK_LOG_TARGET, "Failed to get Gravity parameters with {} correct your Cosmos gRPC connection immediately, you are risking slashing",e);
                         metrics_errors_counter(2, "Failed to get Gravity parameters correct your Cosmos gRPC connection immediately, you are risking slashing");
-                        return Ok(());
+                        alert_slashing_risk(
+                            "gravity_params_unavailable",
+                            format!("Failed to get Gravity parameters with {}", e),
+                            None,
+                        );
+                        return Ok(None);
                     }
                 };
                 let blocks_until_slashing = min(
@@ -263,14 +586,74 @@ pub async fn eth_signer_main_loop(
                 match latest_cosmos_block {
                     Ok(ChainStatus::Moving { block_height }) => {
                         trace!("Latest Cosmos block {}", block_height,);
+                        not_ready_since = None;
+                        if !cosmos_height.check(block_height, "Eth signer")
+                            && pause_on_cosmos_rollback
+                        {
+                            sleep(DELAY).await;
+                            return Ok(None);
+                        }
+                        if !check_cosmos_height_advancing(
+                            &mut last_cosmos_height,
+                            &mut stalled_iterations,
+                            block_height,
+                            stalled_height_iterations,
+                        ) {
+                            error!(target: SLASHING_RISK_LOG_TARGET, "Cosmos node has reported block height {} for {} iterations, it is stalled even though it reports that it is moving! Eth signer paused", block_height, stalled_iterations);
+                            error!(target: SLASHING_RISK_LOG_TARGET, "If this lasts more than {} blocks of time you must find another node to submit signatures or risk slashing", blocks_until_slashing);
+                            metrics_errors_counter(
+                                2,
+                                "Cosmos node block height stalled, Eth signer paused",
+                            );
+                            metrics_latest(blocks_until_slashing, "blocks_until_slashing");
+                            alert_slashing_risk(
+                                "cosmos_node_stalled",
+                                format!(
+                                    "Cosmos node stalled at block height {}, Eth signer paused",
+                                    block_height
+                                ),
+                                Some(blocks_until_slashing),
+                            );
+                            if stalled_height_failover && submission_contacts.len() > 1 {
+                                submission_contacts.rotate_left(1);
+                                let next = submission_contacts[0].clone();
+                                match connect_grpc(next.get_url()).await {
+                                    Ok(client) => {
+                                        warn!(
+                                            "Eth signer failing over to Cosmos node {}",
+                                            next.get_url()
+                                        );
+                                        contact = next;
+                                        grpc_client = client;
+                                        // the new node may be slightly behind the old one
+                                        cosmos_height = CosmosHeightTracker::default();
+                                        last_cosmos_height = 0;
+                                        stalled_iterations = 0;
+                                    }
+                                    Err(e) => error!(
+                                        "Failed to fail over to Cosmos node {} {:?}",
+                                        next.get_url(),
+                                        e
+                                    ),
+                                }
+                            }
+                            sleep(DELAY).await;
+                            return Ok(None);
+                        }
                     }
                     Ok(ChainStatus::Syncing) => {
-                        warn!("Cosmos node syncing, Eth signer paused");
-                        warn!("If this operation will take more than {} blocks of time you must find another node to submit signatures or risk slashing", blocks_until_slashing);
+                        warn!(target: SLASHING_RISK_LOG_TARGET, "Cosmos node syncing, Eth signer paused");
+                        warn!(target: SLASHING_RISK_LOG_TARGET, "If this operation will take more than {} blocks of time you must find another node to submit signatures or risk slashing", blocks_until_slashing);
                         metrics_warnings_counter(2, "Cosmos node syncing, Eth signer paused");
                         metrics_latest(blocks_until_slashing, "blocks_until_slashing");
+                        alert_slashing_risk(
+                            "cosmos_node_syncing",
+                            "Cosmos node syncing, Eth signer paused".to_string(),
+                            Some(blocks_until_slashing),
+                        );
+                        check_not_ready_timeout(&mut not_ready_since, cosmos_not_ready_timeout)?;
                         sleep(DELAY).await;
-                        return Ok(());
+                        return Ok(None);
                     }
                     Ok(ChainStatus::WaitingToStart) => {
                         warn!("Cosmos node syncing waiting for chain start, Eth signer paused");
@@ -278,148 +661,493 @@ pub async fn eth_signer_main_loop(
                             2,
                             "Cosmos node syncing waiting for chain start, Eth signer paused",
                         );
+                        check_not_ready_timeout(&mut not_ready_since, cosmos_not_ready_timeout)?;
                         sleep(DELAY).await;
-                        return Ok(());
+                        return Ok(None);
                     }
-                    Err(_) => {
+                    Err(e) => {
+                        error!(
+                            target: SLASHING_RISK_LOG_TARGET,
+                            "Could not reach Cosmos rpc {:?} You must correct this or you risk being slashed",
+                            e
+                        );
                         metrics_latest(blocks_until_slashing, "blocks_until_slashing");
+                        alert_slashing_risk(
+                            "cosmos_rpc_unreachable",
+                            format!("Could not reach Cosmos rpc {:?}", e),
+                            Some(blocks_until_slashing),
+                        );
                         metrics_errors_counter(
                             2,
                             "Could not reach Cosmos rpc! You must correct this or you risk being slashed",
                         );
-                        return Ok(());
+                        return Ok(None);
                     }
                 }
 
-                // sign the last unsigned valsets
-                match get_oldest_unsigned_valsets(
-                    &mut grpc_client,
-                    our_cosmos_address,
-                    contact.get_prefix(),
-                )
-                .await
-                {
-                    Ok(valsets) => {
-                        if valsets.is_empty() {
-                            trace!("No validator sets to sign, node is caught up!")
-                        } else {
-                            info!(
-                                "Sending {} valset confirms starting with {}",
-                                valsets.len(),
-                                valsets[0].nonce
-                            );
-                            let res = send_valset_confirms(
-                                &contact,
-                                ethereum_key,
-                                fee.clone(),
-                                valsets,
-                                cosmos_key,
-                                gravity_id.clone(),
-                            )
-                            .await;
-                            trace!("Valset confirm result is {:?}", res);
-                            return check_for_fee_error(res, &fee);
-                        }
-                    }
-                    Err(e) => trace!(
-                        "Failed to get unsigned valsets, check your Cosmos gRPC {:?}",
-                        e
-                    ),
-                }
-
-                // sign the last unsigned batch, TODO check if we already have signed this
-                match get_oldest_unsigned_transaction_batches(
-                    &mut grpc_client,
-                    our_cosmos_address,
-                    contact.get_prefix(),
-                )
-                .await
-                {
-                    Ok(last_unsigned_batches) => {
-                        if last_unsigned_batches.is_empty() {
-                            trace!("No unsigned batch sets to sign, node is caught up!")
-                        } else {
-                            info!(
-                                "Sending {} valset confirms starting with {}",
-                                last_unsigned_batches.len(),
-                                last_unsigned_batches[0].nonce
-                            );
-
-                            let res = send_batch_confirm(
-                                &contact,
-                                ethereum_key,
-                                fee.clone(),
-                                last_unsigned_batches,
-                                cosmos_key,
-                                gravity_id.clone(),
-                            )
-                            .await;
-                            trace!("Batch confirm result is {:?}", res);
-                            return check_for_fee_error(res, &fee);
-                        }
-                    }
-                    Err(e) => trace!(
-                        "Failed to get unsigned Batches, check your Cosmos gRPC {:?}",
-                        e
-                    ),
+                if submissions_paused() {
+                    warn!(
+                        target: SLASHING_RISK_LOG_TARGET,
+                        "Submissions are paused, the Eth signer is not submitting confirms"
+                    );
+                    warn!(
+                        target: SLASHING_RISK_LOG_TARGET,
+                        "If this lasts more than {} blocks of time you risk slashing",
+                        blocks_until_slashing
+                    );
+                    metrics_latest(blocks_until_slashing, "blocks_until_slashing");
+                    return Ok(None);
                 }
 
-                match get_oldest_unsigned_logic_calls(
-                    &mut grpc_client,
+                // valsets always come first, the contract needs them to verify everything else,
+                // the rest follow confirm_order and only the first kind with work is signed
+                let signer = ConfirmSigner {
+                    cosmos_key,
+                    ethereum_key,
                     our_cosmos_address,
-                    contact.get_prefix(),
-                )
-                .await
-                {
-                    Ok(last_unsigned_calls) => {
-                        if last_unsigned_calls.is_empty() {
-                            trace!("No unsigned call sets to sign, node is caught up!")
-                        } else {
-                            info!(
-                                "Sending {} valset confirms starting with {}",
-                                last_unsigned_calls.len(),
-                                last_unsigned_calls[0].invalidation_nonce
-                            );
-                            let res = send_logic_call_confirm(
-                                &contact,
-                                ethereum_key,
-                                fee.clone(),
-                                last_unsigned_calls,
-                                cosmos_key,
-                                gravity_id.clone(),
-                            )
-                            .await;
-                            trace!("call confirm result is {:?}", res);
-                            return check_for_fee_error(res, &fee);
+                    contact: &contact,
+                    submission_contacts: &submission_contacts,
+                    fee: &fee,
+                    gravity_id,
+                    confirm_landing_blocks,
+                    retry_on_insufficient_fees,
+                    logic_call_contract_mode,
+                    logic_call_contract_allowlist: &logic_call_contract_allowlist,
+                };
+                for kind in confirm_order.iter() {
+                    let outcome = match kind {
+                        ConfirmKind::Valset => signer.sign_valsets(&mut grpc_client).await,
+                        ConfirmKind::Batch => signer.sign_batches(&mut grpc_client).await,
+                        ConfirmKind::LogicCall => signer.sign_logic_calls(&mut grpc_client).await,
+                    };
+                    match outcome {
+                        ConfirmOutcome::CaughtUp => {}
+                        ConfirmOutcome::QueryFailed => all_checked = false,
+                        ConfirmOutcome::Refused(e) => return Err(e),
+                        ConfirmOutcome::Sent(res) => {
+                            sent_confirms = true;
+                            return res;
                         }
                     }
-                    Err(e) => info!(
-                        "Failed to get unsigned Logic Calls, check your Cosmos gRPC {:?}",
-                        e
-                    ),
                 }
 
-                Ok(())
+                caught_up = all_checked;
+                Ok(None)
             },
             sleep(ETH_SIGNER_LOOP_SPEED)
         );
+        set_signer_behind(!caught_up);
+        if sent_confirms {
+            catching_up = true;
+            metrics_latest(0, "signer_caught_up");
+        } else if caught_up {
+            if catching_up {
+                info!("Ethereum signer has caught up, nothing is left to sign");
+                catching_up = false;
+            }
+            metrics_latest(1, "signer_caught_up");
+        }
 
-        if let Err(e) = async_result {
-            return Err(e);
+        match async_result {
+            Ok(Some(new_fee)) => {
+                warn!("Retrying confirm submission with fee {}", new_fee);
+                fee = new_fee;
+            }
+            Ok(None) => {}
+            Err(e) => return Err(e),
         }
     }
 }
 
+/// The outcome of checking one kind of confirm in an Eth signer iteration
+enum ConfirmOutcome {
+    /// nothing of this kind is left to sign
+    CaughtUp,
+    /// the unsigned items could not be queried
+    QueryFailed,
+    /// confirms were submitted, with a new fee to retry with if the fee was insufficient
+    Sent(Result<Option<Coin>, GravityError>),
+    /// signing was refused and the signer must stop
+    Refused(GravityError),
+}
+
+/// What the Eth signer needs to sign and submit confirms during one iteration
+struct ConfirmSigner<'a> {
+    cosmos_key: CosmosPrivateKey,
+    ethereum_key: EthPrivateKey,
+    our_cosmos_address: CosmosAddress,
+    contact: &'a Contact,
+    /// the primary node and any additional confirm submission endpoints
+    submission_contacts: &'a [Contact],
+    fee: &'a Coin,
+    gravity_id: String,
+    confirm_landing_blocks: u64,
+    retry_on_insufficient_fees: bool,
+    logic_call_contract_mode: LogicCallContractMode,
+    logic_call_contract_allowlist: &'a [EthAddress],
+}
+
+impl ConfirmSigner<'_> {
+    /// Signs the oldest unsigned valsets
+    async fn sign_valsets(&self, grpc_client: &mut GravityQueryClient<Channel>) -> ConfirmOutcome {
+        let valsets = match get_oldest_unsigned_valsets(
+            grpc_client,
+            self.our_cosmos_address,
+            self.contact.get_prefix(),
+        )
+        .await
+        {
+            Ok(valsets) => valsets,
+            Err(e) => {
+                trace!(
+                    "Failed to get unsigned valsets, check your Cosmos gRPC {:?}",
+                    e
+                );
+                return ConfirmOutcome::QueryFailed;
+            }
+        };
+        if valsets.is_empty() {
+            trace!("No validator sets to sign, node is caught up!");
+            return ConfirmOutcome::CaughtUp;
+        }
+        info!(
+            "Sending {} valset confirms starting with {}",
+            valsets.len(),
+            valsets[0].nonce
+        );
+        let nonce = valsets[0].nonce;
+        let res = broadcast_confirm(self.submission_contacts, |c| {
+            send_valset_confirms(
+                c,
+                self.ethereum_key,
+                self.fee.clone(),
+                valsets.clone(),
+                self.cosmos_key,
+                self.gravity_id.clone(),
+            )
+        })
+        .await;
+        trace!("Valset confirm result is {:?}", res);
+        self.finish(grpc_client, ConfirmKind::Valset, nonce, res)
+            .await
+    }
+
+    /// Signs the oldest unsigned batches
+    async fn sign_batches(&self, grpc_client: &mut GravityQueryClient<Channel>) -> ConfirmOutcome {
+        // TODO check if we already have signed this
+        let batches = match get_oldest_unsigned_transaction_batches(
+            grpc_client,
+            self.our_cosmos_address,
+            self.contact.get_prefix(),
+        )
+        .await
+        {
+            Ok(batches) => batches,
+            Err(e) => {
+                trace!(
+                    "Failed to get unsigned Batches, check your Cosmos gRPC {:?}",
+                    e
+                );
+                return ConfirmOutcome::QueryFailed;
+            }
+        };
+        if batches.is_empty() {
+            trace!("No unsigned batch sets to sign, node is caught up!");
+            return ConfirmOutcome::CaughtUp;
+        }
+        info!(
+            "Sending {} batch confirms starting with {}",
+            batches.len(),
+            batches[0].nonce
+        );
+        let nonce = batches[0].nonce;
+        let res = broadcast_confirm(self.submission_contacts, |c| {
+            send_batch_confirm(
+                c,
+                self.ethereum_key,
+                self.fee.clone(),
+                batches.clone(),
+                self.cosmos_key,
+                self.gravity_id.clone(),
+            )
+        })
+        .await;
+        trace!("Batch confirm result is {:?}", res);
+        self.finish(grpc_client, ConfirmKind::Batch, nonce, res)
+            .await
+    }
+
+    /// Signs the oldest unsigned logic calls
+    async fn sign_logic_calls(
+        &self,
+        grpc_client: &mut GravityQueryClient<Channel>,
+    ) -> ConfirmOutcome {
+        let calls = match get_oldest_unsigned_logic_calls(
+            grpc_client,
+            self.our_cosmos_address,
+            self.contact.get_prefix(),
+        )
+        .await
+        {
+            Ok(calls) => calls,
+            Err(e) => {
+                info!(
+                    "Failed to get unsigned Logic Calls, check your Cosmos gRPC {:?}",
+                    e
+                );
+                return ConfirmOutcome::QueryFailed;
+            }
+        };
+        if calls.is_empty() {
+            trace!("No unsigned call sets to sign, node is caught up!");
+            return ConfirmOutcome::CaughtUp;
+        }
+        if let Err(e) = check_logic_calls_to_sign(
+            &calls,
+            self.logic_call_contract_mode,
+            self.logic_call_contract_allowlist,
+        ) {
+            return ConfirmOutcome::Refused(e);
+        }
+        info!(
+            "Sending {} logic call confirms starting with {}",
+            calls.len(),
+            calls[0].invalidation_nonce
+        );
+        let nonce = calls[0].invalidation_nonce;
+        let res = broadcast_confirm(self.submission_contacts, |c| {
+            send_logic_call_confirm(
+                c,
+                self.ethereum_key,
+                self.fee.clone(),
+                calls.clone(),
+                self.cosmos_key,
+                self.gravity_id.clone(),
+            )
+        })
+        .await;
+        trace!("call confirm result is {:?}", res);
+        self.finish(grpc_client, ConfirmKind::LogicCall, nonce, res)
+            .await
+    }
+
+    /// Waits for submitted confirms to land if configured and checks the result for fee errors
+    async fn finish(
+        &self,
+        grpc_client: &mut GravityQueryClient<Channel>,
+        kind: ConfirmKind,
+        nonce: u64,
+        res: Result<TxResponse, CosmosGrpcError>,
+    ) -> ConfirmOutcome {
+        if res.is_ok() && self.confirm_landing_blocks > 0 {
+            wait_for_confirms_to_land(
+                self.contact,
+                grpc_client,
+                self.our_cosmos_address,
+                kind,
+                nonce,
+                self.confirm_landing_blocks,
+            )
+            .await;
+        }
+        ConfirmOutcome::Sent(check_for_fee_error(
+            res,
+            self.fee,
+            self.retry_on_insufficient_fees,
+        ))
+    }
+}
+
+/// Checks the contract of each logic call about to be signed against `allowlist`, warning or
+/// refusing to sign depending on `mode`. Unlike the oracle the signer always sees the calls on
+/// Cosmos, so this is where logic_call_contract_mode is reliably enforced
+fn check_logic_calls_to_sign(
+    calls: &[LogicCall],
+    mode: LogicCallContractMode,
+    allowlist: &[EthAddress],
+) -> Result<(), GravityError> {
+    if mode == LogicCallContractMode::Process {
+        return Ok(());
+    }
+    for call in calls {
+        let contract = match check_logic_call_contract(Some(call.logic_contract_address), allowlist)
+        {
+            LogicCallContractCheck::NotAllowed(contract) => contract,
+            _ => continue,
+        };
+        if mode == LogicCallContractMode::Halt {
+            return Err(GravityError::HaltError(format!(
+                "Logic call {}:{} calls {} which is not on the logic_call_contract_allowlist, refusing to sign it as configured by logic_call_contract_mode",
+                bytes_to_hex_str(&call.invalidation_id), call.invalidation_nonce, contract
+            )));
+        }
+        warn!(
+            "Signing logic call {}:{} which calls {}, it is not on the logic_call_contract_allowlist",
+            bytes_to_hex_str(&call.invalidation_id), call.invalidation_nonce, contract
+        );
+        metrics_warnings_counter(1, "Logic call to a contract not on the allowlist");
+    }
+    Ok(())
+}
+
+/// Blocks until the Gravity parameters can be fetched, so that the signer always starts with a
+/// known gravity_id and slashing windows. Gives up after `retries` failed attempts, zero
+/// retries forever
+async fn wait_for_gravity_params(
+    grpc_client: &mut GravityQueryClient<Channel>,
+    retries: u64,
+) -> Result<(), GravityError> {
+    let mut backoff = Backoff::new(RETRY_TIME, MAX_PARAMS_RETRY_TIME).with_jitter(0.2);
+    let mut attempts = 0;
+    loop {
+        match get_gravity_params(grpc_client).await {
+            Ok(_) => return Ok(()),
+            Err(e) => {
+                attempts += 1;
+                if retries != 0 && attempts >= retries {
+                    return Err(GravityError::UnrecoverableError(format!(
+                        "Eth signer could not get the Gravity parameters after {} attempts {:?}",
+                        attempts, e
+                    )));
+                }
+                let delay = backoff.next_delay();
+                warn!(
+                    "Eth signer failed to get the Gravity parameters, retrying in {}s {:?}",
+                    delay.as_secs(),
+                    e
+                );
+                metrics_errors_counter(2, "Eth signer failed to get Gravity parameters at startup");
+                sleep(delay).await;
+            }
+        }
+    }
+}
+
+/// Records that the Cosmos node is syncing or waiting for the chain to start, returning an
+/// error once it has been that way for more than `timeout` seconds. Zero waits forever
+fn check_not_ready_timeout(
+    not_ready_since: &mut Option<Instant>,
+    timeout: u64,
+) -> Result<(), GravityError> {
+    let since = *not_ready_since.get_or_insert_with(Instant::now);
+    if timeout != 0 && since.elapsed() > Duration::from_secs(timeout) {
+        return Err(GravityError::UnrecoverableError(format!(
+            "Cosmos node has not been producing blocks for over {} seconds",
+            timeout
+        )));
+    }
+    Ok(())
+}
+
+/// Broadcasts a confirm through every contact at once and returns the first successful
+/// result, or the last error if every node failed. Each copy is signed with the account
+/// sequence its node reports, nodes that are in sync report the same sequence so only one
+/// copy can be included in a block and the rest are rejected
+async fn broadcast_confirm<'a, F, Fut>(
+    contacts: &'a [Contact],
+    send: F,
+) -> Result<TxResponse, CosmosGrpcError>
+where
+    F: Fn(&'a Contact) -> Fut,
+    Fut: Future<Output = Result<TxResponse, CosmosGrpcError>>,
+{
+    if contacts.len() == 1 {
+        return send(&contacts[0]).await;
+    }
+    let send = &send;
+    let submissions = contacts.iter().map(|c| {
+        Box::pin(async move {
+            let res = send(c).await;
+            if let Err(e) = &res {
+                warn!("Confirm submission via {} failed {:?}", c.get_url(), e);
+            }
+            res
+        })
+    });
+    select_ok(submissions).await.map(|(res, _)| res)
+}
+
+/// Waits up to `blocks` Cosmos blocks for the confirm we just submitted for `nonce` to land,
+/// meaning the unsigned query for `kind` no longer returns it. If it does not land we only
+/// warn, the confirm is still unsigned so it will be submitted again on the next iteration
+async fn wait_for_confirms_to_land(
+    contact: &Contact,
+    grpc_client: &mut GravityQueryClient<Channel>,
+    our_cosmos_address: CosmosAddress,
+    kind: ConfirmKind,
+    nonce: u64,
+    blocks: u64,
+) {
+    for _ in 0..blocks {
+        if let Err(e) = contact
+            .wait_for_next_block(CONFIRM_LANDING_BLOCK_TIMEOUT)
+            .await
+        {
+            warn!("Failed to wait for the next Cosmos block {:?}", e);
+        }
+        let prefix = contact.get_prefix();
+        let still_unsigned = match kind {
+            ConfirmKind::Valset => {
+                get_oldest_unsigned_valsets(grpc_client, our_cosmos_address, prefix)
+                    .await
+                    .map(|v| v.iter().any(|v| v.nonce == nonce))
+            }
+            ConfirmKind::Batch => {
+                get_oldest_unsigned_transaction_batches(grpc_client, our_cosmos_address, prefix)
+                    .await
+                    .map(|b| b.iter().any(|b| b.nonce == nonce))
+            }
+            ConfirmKind::LogicCall => {
+                get_oldest_unsigned_logic_calls(grpc_client, our_cosmos_address, prefix)
+                    .await
+                    .map(|c| c.iter().any(|c| c.invalidation_nonce == nonce))
+            }
+        };
+        if let Ok(false) = still_unsigned {
+            trace!("{:?} confirm for {} has landed", kind, nonce);
+            return;
+        }
+    }
+    warn!(
+        "{:?} confirm for {} has not landed after {} blocks, it will be submitted again",
+        kind, nonce, blocks
+    );
+    metrics_warnings_counter(2, "Confirm did not land on chain");
+}
+
 /// Checks for fee errors on our confirm submission transactions, a failure here
 /// can be fatal and cause slashing so we want to warn the user and exit. There is
-/// no point in running if we can't perform our most important function
+/// no point in running if we can't perform our most important function. If
+/// `retry_on_insufficient_fees` is set the insufficient fee case is instead logged
+/// and the minimum fee reported by the node in our fee denom is returned so that
+/// the caller can retry with it
 fn check_for_fee_error(
     res: Result<TxResponse, CosmosGrpcError>,
     fee: &Coin,
-) -> Result<(), GravityError> {
+    retry_on_insufficient_fees: bool,
+) -> Result<Option<Coin>, GravityError> {
     if let Err(CosmosGrpcError::InsufficientFees { fee_info }) = res {
         match fee_info {
             FeeInfo::InsufficientFees { min_fees } => {
+                if retry_on_insufficient_fees {
+                    if let Some(min_fee) = min_fees.iter().find(|c| c.denom == fee.denom) {
+                        error!(
+                            "Your specified fee value {} is too small, retrying with {}",
+                            fee, min_fee
+                        );
+                        metrics_errors_counter(2, "Insufficient fees for confirm submission");
+                        return Ok(Some(min_fee.clone()));
+                    }
+                }
+                alert_slashing_risk(
+                    "insufficient_fees",
+                    format!(
+                        "Fee {} is too small, at least {} is required",
+                        fee,
+                        Coin::display_list(&min_fees)
+                    ),
+                    None,
+                );
                 return Err(GravityError::UnrecoverableError(
                     format!( "Your specified fee value {} is too small please use at least {} \n\
                     Correct fee argument immediately! You will be slashed within a few hours if you fail to do so",  fee, Coin::display_list(&min_fees)),
@@ -436,5 +1164,265 @@ fn check_for_fee_error(
         error!("{:?}", error);
     }
 
-    Ok(())
+    Ok(None)
+}
+
+/// Corrects the oracle's `last_checked_event` to the event nonce Cosmos has recorded for us,
+/// which is authoritative. Returns true if Cosmos is behind, meaning events must be replayed
+/// from the block of the Cosmos nonce as after a governance unhalt
+fn reconcile_last_checked_event(last_checked_event: &mut Uint256, cosmos_nonce: Uint256) -> bool {
+    // nothing has been checked yet, so there is nothing to reconcile
+    if last_checked_event.is_zero() || *last_checked_event == cosmos_nonce {
+        *last_checked_event = cosmos_nonce;
+        return false;
+    }
+    warn!(
+        "Oracle last checked event {} differs from the event nonce {} recorded on Cosmos, correcting it",
+        last_checked_event, cosmos_nonce
+    );
+    metrics_warnings_counter(2, "Oracle last checked event differs from Cosmos");
+    let replay = cosmos_nonce < *last_checked_event;
+    *last_checked_event = cosmos_nonce;
+    metrics_latest(cosmos_nonce.resize_to_u64(), "last_checked_event");
+    replay
+}
+
+/// Moves the oracle's starting block back by `rewind_blocks` so that a block whose events were
+/// not fully submitted before a restart is scanned again. This is safe since events with nonces
+/// Cosmos already has are filtered out before any claims are sent
+fn rewind_start_block(last_checked_block: Uint256, rewind_blocks: u64) -> Uint256 {
+    if rewind_blocks == 0 {
+        return last_checked_block;
+    }
+    let start = last_checked_block
+        .checked_sub(Uint256::from_u64(rewind_blocks))
+        .unwrap_or_else(|| u256!(0));
+    info!(
+        "Rescanning from Ethereum block {}, {} blocks before our last checked block {}",
+        start, rewind_blocks, last_checked_block
+    );
+    start
+}
+
+/// Tracks the highest Cosmos block height seen by a loop. A height lower than it means the
+/// node has been reset or is serving rolled back state, and anything it reports may be stale
+#[derive(Debug, Default)]
+struct CosmosHeightTracker {
+    max_height: u64,
+    /// set once the height went backwards, until the node surpasses `max_height`
+    rolled_back: bool,
+}
+
+impl CosmosHeightTracker {
+    /// Returns false if `block_height` went backwards, or did since and has not yet
+    /// surpassed the highest height seen before
+    fn check(&mut self, block_height: u64, loop_name: &str) -> bool {
+        if block_height < self.max_height {
+            error!(
+                "Cosmos block height went backwards from {} to {}, your Cosmos node may have been reset or is serving rolled back state! {} may be acting on stale data",
+                self.max_height, block_height, loop_name
+            );
+            metrics_errors_counter(2, "Cosmos block height went backwards");
+            self.rolled_back = true;
+            return false;
+        }
+        if self.rolled_back {
+            if block_height <= self.max_height {
+                return false;
+            }
+            info!(
+                "Cosmos block height {} has surpassed {}, the height seen before it went backwards",
+                block_height, self.max_height
+            );
+            self.rolled_back = false;
+        }
+        self.max_height = block_height;
+        true
+    }
+}
+
+/// Counts the consecutive iterations the Cosmos block height has not advanced in
+/// `stalled_iterations`, returning false once it has been unchanged for `limit` iterations.
+/// A node that is stuck can still report `ChainStatus::Moving`, a `limit` of zero disables
+/// the check
+fn check_cosmos_height_advancing(
+    last_height: &mut u64,
+    stalled_iterations: &mut u64,
+    block_height: u64,
+    limit: u64,
+) -> bool {
+    if block_height > *last_height {
+        *last_height = block_height;
+        *stalled_iterations = 0;
+        return true;
+    }
+    *stalled_iterations += 1;
+    limit == 0 || *stalled_iterations < limit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insufficient_fees(min_fees: Vec<Coin>) -> Result<TxResponse, CosmosGrpcError> {
+        Err(CosmosGrpcError::InsufficientFees {
+            fee_info: FeeInfo::InsufficientFees { min_fees },
+        })
+    }
+
+    /// A logic call is gone from Cosmos by the time most validators' oracles see it executed,
+    /// so the allowlist has to be enforced while the call is still pending and being signed
+    #[test]
+    fn test_check_logic_calls_to_sign() {
+        let allowed: EthAddress = "0x6Bd41fCdF129297c3524395d669c0865b3CA85B2"
+            .parse()
+            .unwrap();
+        let other: EthAddress = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"
+            .parse()
+            .unwrap();
+        let call = |contract, invalidation_nonce| LogicCall {
+            transfers: Vec::new(),
+            fees: Vec::new(),
+            logic_contract_address: contract,
+            payload: Vec::new(),
+            timeout: 1000,
+            invalidation_id: vec![1],
+            invalidation_nonce,
+        };
+        let calls = vec![call(allowed, 1), call(other, 2)];
+
+        check_logic_calls_to_sign(&calls[..1], LogicCallContractMode::Halt, &[allowed]).unwrap();
+        assert!(matches!(
+            check_logic_calls_to_sign(&calls, LogicCallContractMode::Halt, &[allowed]),
+            Err(GravityError::HaltError(_))
+        ));
+        check_logic_calls_to_sign(&calls, LogicCallContractMode::Warn, &[allowed]).unwrap();
+        check_logic_calls_to_sign(&calls, LogicCallContractMode::Process, &[]).unwrap();
+    }
+
+    #[test]
+    fn test_rewind_start_block() {
+        assert_eq!(rewind_start_block(u256!(100), 10), u256!(90));
+        assert_eq!(rewind_start_block(u256!(5), 10), u256!(0));
+        assert_eq!(rewind_start_block(u256!(100), 0), u256!(100));
+    }
+
+    #[test]
+    fn test_fee_error_retry() {
+        let fee = Coin {
+            amount: u256!(1),
+            denom: "anom".to_string(),
+        };
+        let min_fee = Coin {
+            amount: u256!(1000),
+            denom: "anom".to_string(),
+        };
+        let other_min_fee = Coin {
+            amount: u256!(1000),
+            denom: "footoken".to_string(),
+        };
+
+        // fatal unless retrying is enabled
+        assert!(matches!(
+            check_for_fee_error(insufficient_fees(vec![min_fee.clone()]), &fee, false),
+            Err(GravityError::UnrecoverableError(_))
+        ));
+        assert_eq!(
+            check_for_fee_error(insufficient_fees(vec![min_fee.clone()]), &fee, true).unwrap(),
+            Some(min_fee)
+        );
+        // we can't retry if the node doesn't report a minimum in our denom
+        assert!(matches!(
+            check_for_fee_error(insufficient_fees(vec![other_min_fee]), &fee, true),
+            Err(GravityError::UnrecoverableError(_))
+        ));
+        assert_eq!(
+            check_for_fee_error(Ok(TxResponse::default()), &fee, true).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_cosmos_height_rollback() {
+        let mut tracker = CosmosHeightTracker::default();
+        assert!(tracker.check(10, "test"));
+        // a node that has not produced a block since the last check is not rolled back
+        assert!(tracker.check(10, "test"));
+        assert!(!tracker.check(5, "test"));
+        // the previous maximum is kept until the node surpasses it
+        assert_eq!(tracker.max_height, 10);
+        assert!(!tracker.check(9, "test"));
+        assert!(!tracker.check(10, "test"));
+        assert!(tracker.check(11, "test"));
+        assert_eq!(tracker.max_height, 11);
+        assert!(tracker.check(11, "test"));
+    }
+
+    #[test]
+    fn test_reconcile_last_checked_event() {
+        let mut last_checked_event = u256!(0);
+        assert!(!reconcile_last_checked_event(
+            &mut last_checked_event,
+            u256!(5)
+        ));
+        assert_eq!(last_checked_event, u256!(5));
+        // Cosmos ahead of us is corrected without a replay
+        assert!(!reconcile_last_checked_event(
+            &mut last_checked_event,
+            u256!(7)
+        ));
+        assert_eq!(last_checked_event, u256!(7));
+        // Cosmos behind us requires a replay
+        assert!(reconcile_last_checked_event(
+            &mut last_checked_event,
+            u256!(3)
+        ));
+        assert_eq!(last_checked_event, u256!(3));
+    }
+
+    #[test]
+    fn test_cosmos_height_stalled() {
+        let (mut last_height, mut stalled) = (0, 0);
+        assert!(check_cosmos_height_advancing(
+            &mut last_height,
+            &mut stalled,
+            10,
+            3
+        ));
+        assert!(check_cosmos_height_advancing(
+            &mut last_height,
+            &mut stalled,
+            10,
+            3
+        ));
+        assert!(check_cosmos_height_advancing(
+            &mut last_height,
+            &mut stalled,
+            10,
+            3
+        ));
+        assert!(!check_cosmos_height_advancing(
+            &mut last_height,
+            &mut stalled,
+            10,
+            3
+        ));
+        // any progress clears the stall
+        assert!(check_cosmos_height_advancing(
+            &mut last_height,
+            &mut stalled,
+            11,
+            3
+        ));
+        assert_eq!(stalled, 0);
+        // a limit of zero never reports a stall
+        for _ in 0..10 {
+            assert!(check_cosmos_height_advancing(
+                &mut last_height,
+                &mut stalled,
+                11,
+                0
+            ));
+        }
+    }
 }
@@ -1,8 +1,12 @@
+use std::time::Duration;
+
 use cosmos_gravity::utils::get_last_event_nonce_with_retry;
 use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
 use gravity_utils::{
+    backoff::Backoff,
     clarity::{u256, Address, Uint256},
     deep_space::address::Address as CosmosAddress,
+    error::GravityError,
     get_with_retry::{get_latest_block_number_with_retry, RETRY_TIME},
     types::{
         event_signatures::*, Erc20DeployedEvent, LogicCallExecutedEvent, SendToCosmosEvent,
@@ -15,22 +19,73 @@ use metrics_exporter::metrics_errors_counter;
 use tokio::time::sleep as delay_for;
 use tonic::transport::Channel;
 
+/// The longest the resync waits between attempts to get events from a failing Ethereum node
+const MAX_RESYNC_RETRY_TIME: Duration = Duration::from_secs(60);
+
 /// This function retrieves the last event nonce this oracle has relayed to Cosmos
-/// it then uses the Ethereum indexes to determine what block the last entry
+/// it then uses the Ethereum indexes to determine what block the last entry.
+/// `orchestrator_address` is normally our own, but no key is needed so the progress of
+/// any validator's oracle can be observed by passing its Orchestrator address
 pub async fn get_last_checked_block(
     grpc_client: GravityQueryClient<Channel>,
-    our_cosmos_address: CosmosAddress,
+    orchestrator_address: CosmosAddress,
     prefix: String,
     gravity_contract_address: Address,
     web3: &Web3,
 ) -> Uint256 {
     let mut grpc_client = grpc_client;
+    let last_event_nonce =
+        get_last_event_nonce_with_retry(&mut grpc_client, orchestrator_address, prefix).await;
+    match search_for_event_nonce(
+        last_event_nonce,
+        orchestrator_address,
+        gravity_contract_address,
+        web3,
+        true,
+    )
+    .await
+    {
+        Ok(block) => block,
+        Err(e) => panic!("{}", e),
+    }
+}
+
+/// Like get_last_checked_block, but for an `event_nonce` the caller already has. It neither
+/// retries failed queries nor panics, so it suits observers like the monitor that can skip a
+/// check and try again later
+pub async fn get_block_of_event_nonce(
+    event_nonce: u64,
+    orchestrator_address: CosmosAddress,
+    gravity_contract_address: Address,
+    web3: &Web3,
+) -> Result<Uint256, GravityError> {
+    search_for_event_nonce(
+        event_nonce,
+        orchestrator_address,
+        gravity_contract_address,
+        web3,
+        false,
+    )
+    .await
+}
+
+/// Searches back from the latest block for the block of the event with `event_nonce`, with
+/// `retry` failed queries are retried until they succeed instead of returning an error
+async fn search_for_event_nonce(
+    event_nonce: u64,
+    orchestrator_address: CosmosAddress,
+    gravity_contract_address: Address,
+    web3: &Web3,
+    retry: bool,
+) -> Result<Uint256, GravityError> {
     const BLOCKS_TO_SEARCH: Uint256 = u256!(5_000);
 
-    let latest_block = get_latest_block_number_with_retry(web3).await;
-    let mut last_event_nonce = Uint256::from_u64(
-        get_last_event_nonce_with_retry(&mut grpc_client, our_cosmos_address, prefix).await,
-    );
+    let latest_block = if retry {
+        get_latest_block_number_with_retry(web3).await
+    } else {
+        web3.eth_block_number().await?
+    };
+    let mut last_event_nonce = Uint256::from_u64(event_nonce);
 
     // zero indicates this oracle has never submitted an event before since there is no
     // zero event nonce (it's pre-incremented in the solidity contract) we have to go
@@ -40,6 +95,8 @@ pub async fn get_last_checked_block(
     }
 
     let mut current_block: Uint256 = latest_block;
+    // back off so that a struggling node is not hammered with large log queries
+    let mut backoff = Backoff::new(RETRY_TIME, MAX_RESYNC_RETRY_TIME).with_jitter(0.2);
 
     while !current_block.is_zero() {
         info!(
@@ -101,11 +158,17 @@ pub async fn get_last_checked_block(
             || erc20_deployed_events.is_err()
             || logic_call_executed_events.is_err()
         {
+            if !retry {
+                return Err(GravityError::RecoverableError(
+                    "Failed to get blockchain events while searching for an event nonce".into(),
+                ));
+            }
             error!("Failed to get blockchain events while resyncing, is your Eth node working? If you see only one of these it's fine",);
-            delay_for(RETRY_TIME).await;
+            delay_for(backoff.next_delay()).await;
             metrics_errors_counter(1, "Failed to get blockchain events while resyncing");
             continue;
         }
+        backoff.reset();
         let batch_events = batch_events.unwrap();
         let send_to_cosmos_events = send_to_cosmos_events.unwrap();
         let mut valset_events = valset_events.unwrap();
@@ -127,7 +190,7 @@ pub async fn get_last_checked_block(
                     if Uint256::from_u64(batch.event_nonce) == last_event_nonce
                         && event.block_number.is_some()
                     {
-                        return event.block_number.unwrap();
+                        return Ok(event.block_number.unwrap());
                     }
                 }
                 Err(e) => {
@@ -147,7 +210,7 @@ pub async fn get_last_checked_block(
                     if Uint256::from_u64(send.event_nonce) == last_event_nonce
                         && event.block_number.is_some()
                     {
-                        return event.block_number.unwrap();
+                        return Ok(event.block_number.unwrap());
                     }
                 }
                 Err(e) => {
@@ -167,7 +230,7 @@ pub async fn get_last_checked_block(
                     if Uint256::from_u64(deploy.event_nonce) == last_event_nonce
                         && event.block_number.is_some()
                     {
-                        return event.block_number.unwrap();
+                        return Ok(event.block_number.unwrap());
                     }
                 }
                 Err(e) => {
@@ -187,7 +250,7 @@ pub async fn get_last_checked_block(
                     if Uint256::from_u64(call.event_nonce) == last_event_nonce
                         && event.block_number.is_some()
                     {
-                        return event.block_number.unwrap();
+                        return Ok(event.block_number.unwrap());
                     }
                 }
                 Err(e) => {
@@ -219,12 +282,12 @@ pub async fn get_last_checked_block(
                         last_event_nonce
                     );
                     if common_case || bootstrapping {
-                        return event.block_number.unwrap();
+                        return Ok(event.block_number.unwrap());
                     }
                     // if we're looking for a later event nonce and we find the deployment of the contract
                     // we must have failed to parse the event we're looking for. The oracle can not start
                     else if valset.valset_nonce == 0 && last_event_nonce > u256!(1) {
-                        panic!("Could not find the last event relayed by {}, Last Event nonce is {} but no event matching that could be found!", our_cosmos_address, last_event_nonce)
+                        return Err(GravityError::UnrecoverableError(format!("Could not find the last event relayed by {}, Last Event nonce is {} but no event matching that could be found!", orchestrator_address, last_event_nonce)));
                     }
                 }
                 Err(e) => {
@@ -238,5 +301,5 @@ pub async fn get_last_checked_block(
 
     // we should exit above when we find the zero valset, if we have the wrong contract address through we could be at it a while as we go over
     // the entire history to 'prove' it.
-    panic!("You have reached the end of block history without finding the Gravity contract deploy event! You must have the wrong contract address!");
+    Err(GravityError::UnrecoverableError("You have reached the end of block history without finding the Gravity contract deploy event! You must have the wrong contract address!".into()))
 }
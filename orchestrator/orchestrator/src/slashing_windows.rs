@@ -0,0 +1,107 @@
+//! The Gravity slashing windows are defined in Cosmos blocks, this converts them into the
+//! approximate wall-clock time an operator has to get a stopped Eth signer running again
+//! before their validator is slashed, using the block time sampled from the Cosmos node
+
+use std::time::{Duration, Instant};
+
+use cosmos_gravity::query::get_gravity_params;
+use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
+use gravity_utils::deep_space::{client::ChainStatus, Contact};
+use tokio::time::sleep;
+use tonic::transport::Channel;
+
+/// How long the Cosmos block height is watched to estimate the block time
+pub const BLOCK_TIME_SAMPLE: Duration = Duration::from_secs(60);
+
+/// Estimates the average Cosmos block time by watching the block height advance for `sample`,
+/// None if the node could not be queried or did not produce any blocks in that time
+pub async fn estimate_block_time(contact: &Contact, sample: Duration) -> Option<Duration> {
+    let start_height = moving_height(contact).await?;
+    let start = Instant::now();
+    sleep(sample).await;
+    let end_height = moving_height(contact).await?;
+    let blocks = end_height.checked_sub(start_height).filter(|b| *b > 0)?;
+    Some(start.elapsed() / blocks as u32)
+}
+
+async fn moving_height(contact: &Contact) -> Option<u64> {
+    match contact.get_chain_status().await {
+        Ok(ChainStatus::Moving { block_height }) => Some(block_height),
+        _ => None,
+    }
+}
+
+/// The approximate time it takes to produce `blocks` blocks at `block_time`
+pub fn blocks_to_duration(blocks: u64, block_time: Duration) -> Duration {
+    Duration::from_secs_f64(block_time.as_secs_f64() * blocks as f64)
+}
+
+/// Formats a duration in the largest units that are useful to an operator, such as "2h 5m"
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (days, hours, minutes) = (secs / 86400, secs % 86400 / 3600, secs % 3600 / 60);
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else if minutes > 0 {
+        format!("{minutes}m")
+    } else {
+        format!("{secs}s")
+    }
+}
+
+/// Logs the slashing windows of the chain in blocks and in approximate wall-clock time. This
+/// samples the block time for BLOCK_TIME_SAMPLE, so it is meant to be spawned rather than awaited
+pub async fn log_slashing_windows(contact: Contact, grpc_client: GravityQueryClient<Channel>) {
+    let mut grpc_client = grpc_client;
+    let params = match get_gravity_params(&mut grpc_client).await {
+        Ok(params) => params,
+        Err(e) => {
+            warn!("Could not get the slashing windows {:?}", e);
+            return;
+        }
+    };
+    let block_time = match estimate_block_time(&contact, BLOCK_TIME_SAMPLE).await {
+        Some(block_time) => block_time,
+        None => {
+            warn!("Could not estimate the Cosmos block time, slashing windows are only known in blocks");
+            return;
+        }
+    };
+    info!(
+        "Cosmos blocks take ~{:.1}s, slashing windows are valsets {} blocks (~{}) batches {} blocks (~{}) logic calls {} blocks (~{})",
+        block_time.as_secs_f64(),
+        params.signed_valsets_window,
+        format_duration(blocks_to_duration(params.signed_valsets_window, block_time)),
+        params.signed_batches_window,
+        format_duration(blocks_to_duration(params.signed_batches_window, block_time)),
+        params.signed_logic_calls_window,
+        format_duration(blocks_to_duration(params.signed_logic_calls_window, block_time)),
+    );
+    let shortest = params
+        .signed_valsets_window
+        .min(params.signed_batches_window)
+        .min(params.signed_logic_calls_window);
+    info!(
+        "If the Eth signer stops you have ~{} to get it signing again before slashing",
+        format_duration(blocks_to_duration(shortest, block_time))
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(42)), "42s");
+        assert_eq!(format_duration(Duration::from_secs(300)), "5m");
+        assert_eq!(format_duration(Duration::from_secs(7500)), "2h 5m");
+        assert_eq!(format_duration(Duration::from_secs(90000)), "1d 1h");
+        assert_eq!(
+            blocks_to_duration(10_000, Duration::from_secs(6)),
+            Duration::from_secs(60_000)
+        );
+    }
+}
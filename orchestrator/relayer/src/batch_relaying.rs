@@ -1,25 +1,46 @@
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
 
-use cosmos_gravity::query::{get_latest_transaction_batches, get_transaction_batch_signatures};
+use cosmos_gravity::query::{
+    get_latest_transaction_batches, get_latest_valsets, get_transaction_batch_signatures,
+};
 use ethereum_gravity::{
-    message_signatures::encode_tx_batch_confirm_hashed, submit_batch::send_eth_transaction_batch,
-    utils::get_tx_batch_nonce,
+    checkpoint_layout::describe_tx_batch_confirm,
+    message_signatures::encode_tx_batch_confirm_hashed,
+    nonce_manager::NonceManager,
+    submit_batch::{send_eth_transaction_batch, simulate_eth_transaction_batch},
+    utils::{get_tx_batch_nonce, GasCost},
 };
 use futures::stream::{self, StreamExt};
 use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
 use gravity_utils::{
-    clarity::{address::Address as EthAddress, PrivateKey as EthPrivateKey, Uint256},
+    clarity::{address::Address as EthAddress, u256, PrivateKey as EthPrivateKey, Uint256},
+    error::GravityError,
     num_conversion::{print_eth, print_gwei},
-    prices::get_weth_price,
+    prices::get_base_token_price,
     types::{
-        BatchConfirmResponse, BatchRelayingMode, RelayerConfig, TransactionBatch, Valset,
-        WhitelistToken,
+        BatchConfirmResponse, BatchRelayOrder, BatchRelayingMode, RelayerConfig, TransactionBatch,
+        Valset, WhitelistToken,
     },
+    u64_array_bigints,
     web30::client::Web3,
 };
 use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
 use tonic::transport::Channel;
 
+use crate::pnl::record_relay;
+
+/// The number of steps the relayer's ETH balance is divided into when selecting which batches
+/// it can afford, costs are rounded up to a step so a selection never exceeds the balance
+const BUDGET_RESOLUTION: usize = 1000;
+/// A rough upper bound on the gas of relaying a full batch, only used to skip estimating every
+/// pending batch when our balance clearly pays for them all
+const BATCH_GAS_UPPER_BOUND: u64 = 6_000_000;
+
 #[derive(Debug, Clone)]
 struct SubmittableBatch {
     batch: TransactionBatch,
@@ -44,9 +65,15 @@ pub async fn relay_batches(
     gravity_id: String,
     timeout: Duration,
     config: &RelayerConfig,
+    nonce_manager: &NonceManager,
 ) {
-    let possible_batches =
-        get_batches_and_signatures(current_valset, grpc_client, gravity_id.clone()).await;
+    let possible_batches = get_batches_and_signatures(
+        current_valset,
+        grpc_client,
+        gravity_id.clone(),
+        config.min_relay_power(),
+    )
+    .await;
 
     trace!("possible batches {:?}", possible_batches);
 
@@ -59,10 +86,120 @@ pub async fn relay_batches(
         timeout,
         possible_batches,
         config,
+        nonce_manager,
     )
     .await;
 }
 
+/// The estimated cost of relaying a single batch to Ethereum
+#[derive(Debug, Clone)]
+pub struct BatchCostEstimate {
+    pub token_contract: EthAddress,
+    pub nonce: u64,
+    pub cost: GasCost,
+}
+
+/// Returns true if a pending batch can not be verified by `current_valset` but can by the
+/// latest valset on Cosmos, meaning a valset update must be relayed before the batch can be
+pub async fn batches_need_valset_update(
+    current_valset: &Valset,
+    grpc_client: &mut GravityQueryClient<Channel>,
+    gravity_id: String,
+) -> bool {
+    let latest_valset = match get_latest_valsets(grpc_client).await {
+        Ok(valsets) => valsets.into_iter().max_by_key(|v| v.nonce),
+        Err(e) => {
+            warn!("Could not get the latest valsets {:?}", e);
+            return false;
+        }
+    };
+    let latest_valset = match latest_valset {
+        Some(v) if v.nonce > current_valset.nonce => v,
+        _ => return false,
+    };
+    let batches = match get_latest_transaction_batches(grpc_client).await {
+        Ok(batches) => batches,
+        Err(_) => return false,
+    };
+    for batch in batches {
+        let sigs =
+            get_transaction_batch_signatures(grpc_client, batch.nonce, batch.token_contract).await;
+        if let Ok(sigs) = sigs {
+            let hash = encode_tx_batch_confirm_hashed(gravity_id.clone(), &batch);
+            if current_valset.order_sigs(&hash, &sigs).is_err()
+                && latest_valset.order_sigs(&hash, &sigs).is_ok()
+            {
+                info!(
+                    "Batch {}/{} is waiting on valset {} to be relayed, Ethereum has valset {}",
+                    batch.token_contract, batch.nonce, latest_valset.nonce, current_valset.nonce
+                );
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Estimates the cost of relaying every batch that is currently valid to submit to
+/// Ethereum, that is every batch with enough signatures for the current valset that
+/// has not yet timed out or been submitted. Profitability is not considered, batches
+/// that fail to estimate are logged and left out of the result
+pub async fn estimate_pending_batch_costs(
+    current_valset: &Valset,
+    ethereum_key: EthPrivateKey,
+    web3: &Web3,
+    grpc_client: &mut GravityQueryClient<Channel>,
+    gravity_contract_address: EthAddress,
+    gravity_id: String,
+) -> Result<Vec<BatchCostEstimate>, GravityError> {
+    let our_ethereum_address = ethereum_key.to_address();
+    let possible_batches =
+        get_batches_and_signatures(current_valset, grpc_client, gravity_id.clone(), 0).await;
+    let ethereum_block_height = web3.eth_block_number().await?;
+
+    let mut estimates = Vec::new();
+    for (token_contract, batches) in possible_batches {
+        let latest_ethereum_batch = get_tx_batch_nonce(
+            gravity_contract_address,
+            token_contract,
+            our_ethereum_address,
+            web3,
+        )
+        .await?;
+
+        for batch in batches {
+            if Uint256::from_u64(batch.batch.batch_timeout) < ethereum_block_height
+                || batch.batch.nonce <= latest_ethereum_batch
+            {
+                continue;
+            }
+            let nonce = batch.batch.nonce;
+            let cost = ethereum_gravity::submit_batch::estimate_tx_batch_cost(
+                current_valset,
+                batch.batch,
+                &batch.sigs,
+                web3,
+                gravity_contract_address,
+                gravity_id.clone(),
+                ethereum_key,
+            )
+            .await;
+            match cost {
+                Ok(cost) => estimates.push(BatchCostEstimate {
+                    token_contract,
+                    nonce,
+                    cost,
+                }),
+                Err(e) => warn!(
+                    "Batch {}/{} cost estimate failed with {:?}",
+                    token_contract, nonce, e
+                ),
+            }
+        }
+    }
+    Ok(estimates)
+}
+
 /// This function retrieves the latest batches from the Cosmos module and then
 /// iterates through the signatures for each batch, determining if they are ready
 /// to submit. It is possible for a batch to not have valid signatures for two reasons
@@ -75,6 +212,7 @@ async fn get_batches_and_signatures(
     current_valset: &Valset,
     grpc_client: &mut GravityQueryClient<Channel>,
     gravity_id: String,
+    min_power: u64,
 ) -> HashMap<EthAddress, Vec<SubmittableBatch>> {
     let latest_batches = if let Ok(lb) = get_latest_transaction_batches(grpc_client).await {
         lb
@@ -93,7 +231,10 @@ async fn get_batches_and_signatures(
         if let Ok(sigs) = signatures {
             // this checks that the signatures for the batch are actually possible to submit to the chain
             let hash = encode_tx_batch_confirm_hashed(gravity_id.clone(), &batch);
-            if current_valset.order_sigs(&hash, &sigs).is_ok() {
+            if current_valset
+                .order_sigs_with_min_power(&hash, &sigs, min_power)
+                .is_ok()
+            {
                 // we've found a valid batch, add it to the list for it's token type
                 possible_batches
                     .entry(batch.token_contract)
@@ -106,6 +247,7 @@ async fn get_batches_and_signatures(
                     "Batch {}/{} can not be submitted yet, waiting for more signatures",
                     batch.token_contract, batch.nonce
                 );
+                trace!("{}", describe_tx_batch_confirm(gravity_id.clone(), &batch));
             }
         } else {
             error!(
@@ -124,13 +266,16 @@ async fn get_batches_and_signatures(
 }
 
 // Determines whether or not submitting `batch` will be profitable given the estimated `cost`
-// and the current exchange rate available on uniswap
+// and the current exchange rate available on uniswap, `always_relay` batches are relayed
+// regardless with a warning when they are not
 async fn should_relay_batch(
     web3: &Web3,
     batch: &TransactionBatch,
     cost: Uint256,
     pubkey: EthAddress,
     config: &BatchRelayingMode,
+    price_base_token: EthAddress,
+    always_relay: bool,
 ) -> bool {
     // skip price request below in the trivial case, couldn't really
     // figure the code duplication / extra network IO balance otherwise
@@ -140,9 +285,16 @@ async fn should_relay_batch(
 
     let batch_reward_amount = batch.total_fee.amount;
     let batch_reward_token = batch.total_fee.token_contract_address;
-    let price = get_weth_price(batch_reward_token, batch_reward_amount, pubkey, web3).await;
+    let price = get_base_token_price(
+        price_base_token,
+        batch_reward_token,
+        batch_reward_amount,
+        pubkey,
+        web3,
+    )
+    .await;
 
-    match config {
+    let profitable = match config {
         BatchRelayingMode::EveryBatch => true,
         BatchRelayingMode::ProfitableOnly { margin } => {
             let cost_with_margin = get_cost_with_margin(cost, *margin);
@@ -178,7 +330,18 @@ async fn should_relay_batch(
                 }
             }
         }
+    };
+
+    if !profitable && always_relay {
+        warn!(
+            "Relaying batch {}/{} of an always relay token at a loss, it is estimated to cost {}",
+            batch.token_contract,
+            batch.nonce,
+            print_eth(cost)
+        );
+        return true;
     }
+    profitable
 }
 
 /// Takes a token price whitelist, gets the amount of tokens for the specified
@@ -192,6 +355,269 @@ fn get_whitelist_amount(erc20: EthAddress, whitelist: &[WhitelistToken]) -> Opti
     None
 }
 
+/// Estimates the value of the fees of `batch` in the price base token minus the estimated gas
+/// cost of relaying it, zero if it is unprofitable and None if it can not be priced
+async fn estimate_batch_profit(
+    current_valset: &Valset,
+    batch: &SubmittableBatch,
+    ethereum_key: EthPrivateKey,
+    web3: &Web3,
+    gravity_contract_address: EthAddress,
+    gravity_id: String,
+    price_base_token: EthAddress,
+) -> Option<Uint256> {
+    let cost = ethereum_gravity::submit_batch::estimate_tx_batch_cost(
+        current_valset,
+        batch.batch.clone(),
+        &batch.sigs,
+        web3,
+        gravity_contract_address,
+        gravity_id,
+        ethereum_key,
+    )
+    .await;
+    let value = get_base_token_price(
+        price_base_token,
+        batch.batch.total_fee.token_contract_address,
+        batch.batch.total_fee.amount,
+        ethereum_key.to_address(),
+        web3,
+    )
+    .await;
+    match (value, cost) {
+        (Ok(value), Ok(cost)) => Some(value.checked_sub(cost.get_total()).unwrap_or(u256!(0))),
+        _ => None,
+    }
+}
+
+/// Orders items by their estimated profit, most profitable first. Items that could not be
+/// priced go last, after even unprofitable ones, rather than ranking alongside free batches
+fn sort_by_fee_priority<T>(items: &mut [(Option<Uint256>, T)]) {
+    items.sort_by(|a, b| match (a.0, b.0) {
+        (Some(a), Some(b)) => b.cmp(&a),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    });
+}
+
+/// How a batch competes for our ETH balance when it can not pay for every pending batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BudgetPriority {
+    /// always relay tokens and batches paying an acceptable whitelisted reward, these are
+    /// relayed regardless of profit so they are paid for first
+    Required,
+    /// batches that pass the profit margin, the estimated profit decides between them
+    Profit(Uint256),
+}
+
+/// Decides how `batch` competes for our balance, following the same rules as
+/// should_relay_batch. None for batches that would not be relayed at any balance
+async fn budget_priority(
+    web3: &Web3,
+    batch: &TransactionBatch,
+    cost: Uint256,
+    pubkey: EthAddress,
+    config: &RelayerConfig,
+) -> Option<BudgetPriority> {
+    if config.always_relays_token(batch.token_contract) {
+        return Some(BudgetPriority::Required);
+    }
+    let margin = match &config.batch_relaying_mode {
+        BatchRelayingMode::EveryBatch => return Some(BudgetPriority::Required),
+        BatchRelayingMode::ProfitableOnly { margin } => *margin,
+        BatchRelayingMode::ProfitableWithWhitelist { margin, whitelist } => {
+            if let Some(amount) = get_whitelist_amount(batch.token_contract, whitelist) {
+                return (amount <= batch.total_fee.amount).then_some(BudgetPriority::Required);
+            }
+            *margin
+        }
+    };
+    let value = get_base_token_price(
+        config.price_base_token,
+        batch.total_fee.token_contract_address,
+        batch.total_fee.amount,
+        pubkey,
+        web3,
+    )
+    .await
+    .ok()?;
+    profit_priority(value, cost, margin)
+}
+
+/// The priority of a batch whose fees are worth `value` in the price base token, None if it
+/// does not pass the margin
+fn profit_priority(value: Uint256, cost: Uint256, margin: f64) -> Option<BudgetPriority> {
+    if value > get_cost_with_margin(cost, margin) {
+        // a margin below one accepts a loss, such batches are only relayed with spare balance
+        Some(BudgetPriority::Profit(
+            value.checked_sub(cost).unwrap_or(u256!(0)),
+        ))
+    } else {
+        None
+    }
+}
+
+/// When the relayer's ETH balance can not pay for relaying every pending batch, selects the
+/// batches it can pay for, required batches first and then those with the most total
+/// estimated profit. Returns None when the balance is enough to relay everything or in
+/// `EveryBatch` mode. Batch costs are only estimated when a rough upper bound on them is
+/// more than our balance
+#[allow(clippy::too_many_arguments)]
+async fn select_affordable_batches(
+    current_valset: &Valset,
+    ethereum_key: EthPrivateKey,
+    web3: &Web3,
+    gravity_contract_address: EthAddress,
+    gravity_id: &str,
+    possible_batches: &[(EthAddress, Vec<SubmittableBatch>)],
+    config: &RelayerConfig,
+    ethereum_block_height: Uint256,
+) -> Option<HashSet<(EthAddress, u64)>> {
+    if let BatchRelayingMode::EveryBatch = config.batch_relaying_mode {
+        return None;
+    }
+    let our_ethereum_address = ethereum_key.to_address();
+    let candidates: Vec<&SubmittableBatch> = possible_batches
+        .iter()
+        .filter(|(token, _)| config.should_relay_token(*token))
+        .flat_map(|(_, batches)| batches.iter())
+        .filter(|b| Uint256::from_u64(b.batch.batch_timeout) >= ethereum_block_height)
+        .collect();
+    if candidates.is_empty() {
+        return None;
+    }
+    let (balance, gas_price) = match (
+        web3.eth_get_balance(our_ethereum_address).await,
+        web3.eth_gas_price().await,
+    ) {
+        (Ok(balance), Ok(gas_price)) => (balance, gas_price),
+        (Err(e), _) | (_, Err(e)) => {
+            warn!(
+                "Failed to get our ETH balance or the gas price to budget batch relays {:?}",
+                e
+            );
+            return None;
+        }
+    };
+    let cost_bound = gas_price.checked_mul(Uint256::from_u64(
+        BATCH_GAS_UPPER_BOUND * candidates.len() as u64,
+    ));
+    if matches!(cost_bound, Some(bound) if bound <= balance) {
+        return None;
+    }
+
+    let mut estimated = Vec::new();
+    let mut total_cost = u256!(0);
+    for batch in candidates {
+        // batches that can not be estimated will not be relayed either
+        if let Ok(cost) = ethereum_gravity::submit_batch::estimate_tx_batch_cost(
+            current_valset,
+            batch.batch.clone(),
+            &batch.sigs,
+            web3,
+            gravity_contract_address,
+            gravity_id.to_string(),
+            ethereum_key,
+        )
+        .await
+        {
+            total_cost = total_cost.checked_add(cost.get_total()).unwrap();
+            estimated.push((batch, cost.get_total()));
+        }
+    }
+    if total_cost <= balance {
+        return None;
+    }
+
+    let mut items = Vec::new();
+    for (batch, cost) in estimated {
+        if let Some(priority) =
+            budget_priority(web3, &batch.batch, cost, our_ethereum_address, config).await
+        {
+            items.push((batch, cost, priority));
+        }
+    }
+    let chosen = select_within_budget(
+        &items
+            .iter()
+            .map(|(_, cost, priority)| (*cost, *priority))
+            .collect::<Vec<_>>(),
+        balance,
+    );
+    info!(
+        "Our balance of {} ETH can not relay every pending batch estimated at {} ETH, relaying the {} we can afford",
+        print_eth(balance),
+        print_eth(total_cost),
+        chosen.len()
+    );
+    Some(
+        chosen
+            .into_iter()
+            .map(|i| (items[i].0.batch.token_contract, items[i].0.batch.nonce))
+            .collect(),
+    )
+}
+
+/// Chooses the items, given as (cost, priority), that fit in `budget` and returns their
+/// indexes. Required items are paid for first in the given order, the rest of the budget goes
+/// to the profitable items with the most total profit
+fn select_within_budget(items: &[(Uint256, BudgetPriority)], budget: Uint256) -> Vec<usize> {
+    let mut budget = budget;
+    let mut chosen = Vec::new();
+    let mut profitable = Vec::new();
+    for (i, (cost, priority)) in items.iter().enumerate() {
+        match priority {
+            BudgetPriority::Required => {
+                if let Some(rest) = budget.checked_sub(*cost) {
+                    budget = rest;
+                    chosen.push(i);
+                }
+            }
+            BudgetPriority::Profit(profit) => profitable.push((i, (*cost, *profit))),
+        }
+    }
+    let profitable_items: Vec<_> = profitable.iter().map(|(_, item)| *item).collect();
+    chosen.extend(
+        select_most_profitable(&profitable_items, budget)
+            .into_iter()
+            .map(|j| profitable[j].0),
+    );
+    chosen.sort_unstable();
+    chosen
+}
+
+/// Chooses the items, given as (cost, profit), with the most total profit whose total cost
+/// fits in `budget` and returns their indexes. This is a 0/1 knapsack over the budget
+/// divided into BUDGET_RESOLUTION steps
+fn select_most_profitable(items: &[(Uint256, Uint256)], budget: Uint256) -> Vec<usize> {
+    if budget == u256!(0) {
+        return Vec::new();
+    }
+    let budget_float: f64 = budget.to_string().parse().unwrap();
+    // the best (profit, items) for every number of budget steps
+    let mut best: Vec<(Uint256, Vec<usize>)> = vec![(u256!(0), Vec::new()); BUDGET_RESOLUTION + 1];
+    for (i, (cost, profit)) in items.iter().enumerate() {
+        let cost_float: f64 = cost.to_string().parse().unwrap();
+        let steps = (cost_float / budget_float * BUDGET_RESOLUTION as f64).ceil();
+        if steps > BUDGET_RESOLUTION as f64 {
+            continue;
+        }
+        let steps = steps as usize;
+        for capacity in (steps..=BUDGET_RESOLUTION).rev() {
+            let with_item = best[capacity - steps].0.checked_add(*profit).unwrap();
+            if with_item > best[capacity].0 {
+                let mut chosen = best[capacity - steps].1.clone();
+                chosen.push(i);
+                best[capacity] = (with_item, chosen);
+            }
+        }
+    }
+    let mut chosen = best.swap_remove(BUDGET_RESOLUTION).1;
+    chosen.sort_unstable();
+    chosen
+}
+
 /// bakes the margin into the cost to provide an easy value to compare against
 pub fn get_cost_with_margin(cost: Uint256, margin: f64) -> Uint256 {
     let cost_as_float: f64 = cost.to_string().parse().unwrap();
@@ -211,7 +637,9 @@ pub fn get_cost_with_margin(cost: Uint256, margin: f64) -> Uint256 {
 /// be skipped and a later, more profitable, batch may be submitted.
 /// Keep in mind that many other relayers are making this same computation and some may have
 /// different standards for their profit margin, therefore there may be a race not only to
-/// submit individual batches but also batches in different orders
+/// submit individual batches but also batches in different orders.
+/// If our ETH balance can not pay for every batch only the most profitable subset we can
+/// afford is submitted, see select_affordable_batches
 async fn submit_batches(
     current_valset: &Valset,
     ethereum_key: EthPrivateKey,
@@ -221,6 +649,7 @@ async fn submit_batches(
     timeout: Duration,
     possible_batches: HashMap<EthAddress, Vec<SubmittableBatch>>,
     config: &RelayerConfig,
+    nonce_manager: &NonceManager,
 ) {
     let our_ethereum_address = ethereum_key.to_address();
     let ethereum_block_height = if let Ok(bn) = web3.eth_block_number().await {
@@ -230,41 +659,91 @@ async fn submit_batches(
         return;
     };
 
-    let data_holder = Arc::new((ethereum_block_height, current_valset, gravity_id, config));
+    let mut possible_batches: Vec<_> = possible_batches
+        .into_iter()
+        .filter(|(token, _)| {
+            let relay = config.should_relay_token(*token);
+            if !relay {
+                trace!(
+                    "Not relaying batches for {}, it is not in batch_relay_tokens",
+                    token
+                );
+            }
+            relay
+        })
+        .collect();
+    let fee_priority = config.batch_relay_order == BatchRelayOrder::FeePriority;
+    if fee_priority {
+        let mut prioritized = Vec::new();
+        for (token, batches) in possible_batches {
+            let profit = match batches.first() {
+                Some(oldest) => {
+                    estimate_batch_profit(
+                        current_valset,
+                        oldest,
+                        ethereum_key,
+                        web3,
+                        gravity_contract_address,
+                        gravity_id.clone(),
+                        config.price_base_token,
+                    )
+                    .await
+                }
+                None => None,
+            };
+            prioritized.push((profit, (token, batches)));
+        }
+        sort_by_fee_priority(&mut prioritized);
+        possible_batches = prioritized.into_iter().map(|(_, item)| item).collect();
+    }
+
+    let affordable_batches = select_affordable_batches(
+        current_valset,
+        ethereum_key,
+        web3,
+        gravity_contract_address,
+        &gravity_id,
+        &possible_batches,
+        config,
+        ethereum_block_height,
+    )
+    .await;
 
-    // requests data from Ethereum only once per token type, this is valid because we are
-    // iterating from oldest to newest, so submitting a batch earlier in the loop won't
-    // ever invalidate submitting a batch later in the loop. Another relayer could always
-    // do that though.
+    let data_holder = Arc::new((
+        ethereum_block_height,
+        current_valset,
+        gravity_id,
+        config,
+        affordable_batches,
+    ));
 
+    // the batches of each token are submitted oldest to newest and the Ethereum batch nonce
+    // is checked before each one, since another relayer may submit a batch at any time. Tokens
+    // are relayed two at a time, unless they are in fee priority order which only holds if
+    // they are relayed one after the other
+    let concurrency = if fee_priority { 1 } else { 2 };
     stream::iter(possible_batches)
         .zip(stream::repeat(data_holder.clone()))
-        .for_each_concurrent(2, |((token_type, batches), data_holder)| async move {
-            let (ethereum_block_height, current_valset, gravity_id, config) = &*data_holder;
+        .for_each_concurrent(concurrency, |((token_type, batches), data_holder)| async move {
+            let (ethereum_block_height, current_valset, gravity_id, config, affordable_batches) =
+                &*data_holder;
             let erc20_contract = token_type;
 
-            let latest_ethereum_batch = get_tx_batch_nonce(
-                gravity_contract_address,
-                erc20_contract,
-                our_ethereum_address,
-                web3,
-            )
-            .await;
-
-            if latest_ethereum_batch.is_err() {
-                error!(
-                    "Failed to get latest Ethereum batch with {:?}",
-                    latest_ethereum_batch
-                );
-                return;
-            }
-
-            let latest_ethereum_batch = latest_ethereum_batch.unwrap();
-
             for batch in batches {
                 let oldest_signed_batch = batch.batch;
                 let oldest_signatures = batch.sigs;
 
+                if let Some(affordable_batches) = affordable_batches {
+                    let key = (oldest_signed_batch.token_contract, oldest_signed_batch.nonce);
+                    if !affordable_batches.contains(&key) {
+                        info!(
+                            "Not relaying batch {}/{}, our ETH balance is kept for more profitable batches",
+                            oldest_signed_batch.token_contract, oldest_signed_batch.nonce
+                        );
+                        continue;
+                    }
+                }
+
                 let timeout_height = Uint256::from_u64(oldest_signed_batch.batch_timeout);
                 if timeout_height < *ethereum_block_height {
                     warn!(
@@ -274,8 +753,34 @@ async fn submit_batches(
                     continue;
                 }
 
-            let latest_cosmos_batch_nonce = oldest_signed_batch.clone().nonce;
-            if latest_cosmos_batch_nonce > latest_ethereum_batch {
+                // the contract is checked before every batch since another relayer may have
+                // relayed it while we were pricing or submitting earlier ones, estimating it
+                // would waste time and submitting it would waste gas on a reverted tx
+                let latest_ethereum_batch = get_tx_batch_nonce(
+                    gravity_contract_address,
+                    erc20_contract,
+                    our_ethereum_address,
+                    web3,
+                )
+                .await;
+                if latest_ethereum_batch.is_err() {
+                    error!(
+                        "Failed to get latest Ethereum batch with {:?}",
+                        latest_ethereum_batch
+                    );
+                    return;
+                }
+                let latest_ethereum_batch = latest_ethereum_batch.unwrap();
+                if oldest_signed_batch.nonce <= latest_ethereum_batch {
+                    info!(
+                        "Batch {}/{} has already been relayed, Ethereum is on batch {}",
+                        oldest_signed_batch.token_contract,
+                        oldest_signed_batch.nonce,
+                        latest_ethereum_batch
+                    );
+                    continue;
+                }
+
                 let cost = ethereum_gravity::submit_batch::estimate_tx_batch_cost(
                     current_valset,
                     oldest_signed_batch.clone(),
@@ -308,10 +813,37 @@ async fn submit_batches(
                     cost.get_total(),
                     our_ethereum_address,
                     &config.batch_relaying_mode,
+                    config.price_base_token,
+                    config.always_relays_token(oldest_signed_batch.token_contract),
                 )
                 .await;
 
+                if should_relay && config.simulate_batch_relays {
+                    if let Err(e) = simulate_eth_transaction_batch(
+                        current_valset,
+                        &oldest_signed_batch,
+                        &oldest_signatures,
+                        web3,
+                        gravity_contract_address,
+                        gravity_id.clone(),
+                        our_ethereum_address,
+                    )
+                    .await
+                    {
+                        warn!(
+                            "Not relaying batch {}/{}, simulating the submission failed with {:?}",
+                            oldest_signed_batch.token_contract, oldest_signed_batch.nonce, e
+                        );
+                        continue;
+                    }
+                }
+
                 if should_relay {
+                    let item = format!(
+                        "batch {}/{}",
+                        oldest_signed_batch.token_contract, oldest_signed_batch.nonce
+                    );
+                    let reward = oldest_signed_batch.total_fee;
                     let res = send_eth_transaction_batch(
                         current_valset,
                         oldest_signed_batch,
@@ -321,10 +853,22 @@ async fn submit_batches(
                         gravity_contract_address,
                         gravity_id.clone(),
                         ethereum_key,
+                        nonce_manager,
                     )
                     .await;
-                    if res.is_err() {
-                        info!("Batch submission failed with {:?}", res);
+                    match res {
+                        Ok(_) => {
+                            record_relay(
+                                &item,
+                                &[reward],
+                                cost.get_total(),
+                                config.price_base_token,
+                                our_ethereum_address,
+                                web3,
+                            )
+                            .await
+                        }
+                        Err(e) => info!("Batch submission failed with {:?}", e),
                     }
                 } else {
                     info!(
@@ -333,7 +877,78 @@ async fn submit_batches(
                                             );
                 }
             }
-            }
         })
         .await;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_by_fee_priority() {
+        let mut items = vec![
+            (None, "unpriced"),
+            (Some(u256!(0)), "unprofitable"),
+            (Some(u256!(50)), "profitable"),
+            (Some(u256!(100)), "most profitable"),
+        ];
+        sort_by_fee_priority(&mut items);
+        let order: Vec<_> = items.iter().map(|(_, name)| *name).collect();
+        assert_eq!(
+            order,
+            vec!["most profitable", "profitable", "unprofitable", "unpriced"]
+        );
+    }
+
+    #[test]
+    fn test_select_most_profitable() {
+        let items = [
+            (u256!(6), u256!(10)),
+            (u256!(5), u256!(7)),
+            (u256!(5), u256!(7)),
+            (u256!(11), u256!(100)),
+        ];
+        // the single most profitable affordable batch loses to the two that fill the budget
+        assert_eq!(select_most_profitable(&items, u256!(10)), vec![1, 2]);
+        assert_eq!(
+            select_most_profitable(&items, u256!(4)),
+            Vec::<usize>::new()
+        );
+        assert_eq!(
+            select_most_profitable(&items, u256!(0)),
+            Vec::<usize>::new()
+        );
+        assert_eq!(select_most_profitable(&items, u256!(20)), vec![0, 3]);
+    }
+
+    #[test]
+    fn test_select_within_budget() {
+        let items = [
+            (u256!(5), BudgetPriority::Profit(u256!(100))),
+            (u256!(6), BudgetPriority::Required),
+            (u256!(5), BudgetPriority::Profit(u256!(7))),
+            (u256!(20), BudgetPriority::Required),
+        ];
+        // required batches are paid for first, even when they are less profitable
+        assert_eq!(select_within_budget(&items, u256!(10)), vec![1]);
+        assert_eq!(select_within_budget(&items, u256!(11)), vec![0, 1]);
+        // a required batch we can not afford does not stop the others
+        assert_eq!(select_within_budget(&items, u256!(16)), vec![0, 1, 2]);
+        assert_eq!(select_within_budget(&items, u256!(36)), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_profit_priority() {
+        // the margin decides if a batch is relayed, the profit without it how it ranks
+        assert_eq!(
+            profit_priority(u256!(150), u256!(100), 1.2),
+            Some(BudgetPriority::Profit(u256!(50)))
+        );
+        assert_eq!(profit_priority(u256!(110), u256!(100), 1.2), None);
+        assert_eq!(
+            profit_priority(u256!(90), u256!(100), 0.8),
+            Some(BudgetPriority::Profit(u256!(0)))
+        );
+    }
+}
@@ -1,16 +1,17 @@
+use ethereum_gravity::utils::get_ethereum_valset;
 use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
 use gravity_utils::{
-    clarity::{u256, Address, Uint256},
+    clarity::{constants::ZERO_ADDRESS, Address},
     error::GravityError,
-    types::{event_signatures::*, Valset, ValsetUpdatedEvent},
-    u64_array_bigints,
+    types::Valset,
     web30::client::Web3,
 };
 use tonic::transport::Channel;
 
-/// This function finds the latest valset on the Gravity contract by looking back through the event
-/// history and finding the most recent ValsetUpdatedEvent. Most of the time this will be very fast
-/// as the latest update will be in recent blockchain history and the search moves from the present
+/// This function finds the latest valset on the Gravity contract with get_ethereum_valset, which
+/// looks back through the event history for the ValsetUpdatedEvent of the contract's valset nonce
+/// and checks it against the contract's checkpoint. Most of the time this will be very fast as the
+/// latest update will be in recent blockchain history and the search moves from the present
 /// backwards in time. In the case that the validator set has not been updated for a very long time
 /// this will take longer.
 pub async fn find_latest_valset(
@@ -18,55 +19,12 @@ pub async fn find_latest_valset(
     gravity_contract_address: Address,
     web3: &Web3,
 ) -> Result<Valset, GravityError> {
-    const BLOCKS_TO_SEARCH: Uint256 = u256!(5_000);
-    let latest_block = web3.eth_block_number().await?;
-    let mut current_block: Uint256 = latest_block;
-
-    while !current_block.is_zero() {
-        trace!(
-            "About to submit a Valset or Batch looking back into the history to find the last Valset Update, on block {}",
-            current_block
-        );
-        let end_search = current_block
-            .checked_sub(BLOCKS_TO_SEARCH)
-            .unwrap_or_else(|| u256!(0));
-        let mut all_valset_events = web3
-            .check_for_events(
-                end_search,
-                Some(current_block),
-                vec![gravity_contract_address],
-                vec![VALSET_UPDATED_EVENT_SIG],
-            )
-            .await?;
-        // by default the lowest found valset goes first, we want the highest.
-        all_valset_events.reverse();
-
-        trace!("Found events {:?}", all_valset_events);
-
-        // we take only the first event if we find any at all.
-        if !all_valset_events.is_empty() {
-            let event = &all_valset_events[0];
-            match ValsetUpdatedEvent::from_log(event) {
-                Ok(event) => {
-                    let latest_eth_valset = Valset {
-                        nonce: event.valset_nonce,
-                        members: event.members,
-                        reward_amount: event.reward_amount,
-                        reward_token: event.reward_token,
-                    };
-                    let cosmos_chain_valset =
-                        cosmos_gravity::query::get_valset(grpc_client, latest_eth_valset.nonce)
-                            .await?;
-                    check_if_valsets_differ(cosmos_chain_valset, &latest_eth_valset);
-                    return Ok(latest_eth_valset);
-                }
-                Err(e) => error!("Got valset event that we can't parse {}", e),
-            }
-        }
-        current_block = end_search;
-    }
-
-    panic!("Could not find the last validator set for contract {}, probably not a valid Gravity contract!", gravity_contract_address)
+    let latest_eth_valset =
+        get_ethereum_valset(gravity_contract_address, ZERO_ADDRESS, web3).await?;
+    let cosmos_chain_valset =
+        cosmos_gravity::query::get_valset(grpc_client, latest_eth_valset.nonce).await?;
+    check_if_valsets_differ(cosmos_chain_valset, &latest_eth_valset);
+    Ok(latest_eth_valset)
 }
 
 /// This function exists to provide a warning if Cosmos and Ethereum have different validator sets
@@ -5,7 +5,8 @@ use std::{
 
 use cosmos_gravity::query::{get_latest_logic_calls, get_logic_call_signatures};
 use ethereum_gravity::{
-    logic_call::send_eth_logic_call, message_signatures::encode_logic_call_confirm_hashed,
+    checkpoint_layout::describe_logic_call_confirm, logic_call::send_eth_logic_call,
+    message_signatures::encode_logic_call_confirm_hashed, nonce_manager::NonceManager,
     utils::get_logic_call_nonce,
 };
 use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
@@ -16,10 +17,12 @@ use gravity_utils::{
     },
     num_conversion::{print_eth, print_gwei},
     types::{LogicCall, LogicCallConfirmResponse, RelayerConfig, Valset},
-    web30::{amm::WETH_CONTRACT_ADDRESS, client::Web3},
+    web30::client::Web3,
 };
 use tonic::transport::Channel;
 
+use crate::pnl::record_relay;
+
 // Determines whether or not submitting `logic_call` will be profitable given the estimated `cost`
 // and the current exchange rate available on uniswap
 async fn should_relay_logic_call(
@@ -27,6 +30,7 @@ async fn should_relay_logic_call(
     web3: &Web3,
     logic_call: &LogicCall,
     cost: Uint256,
+    price_base_token: EthAddress,
 ) -> bool {
     // Fill a hashmap with reward totals by token type
     let mut rewards: HashMap<EthAddress, Uint256> = HashMap::new();
@@ -45,8 +49,8 @@ async fn should_relay_logic_call(
     // Check the values in the map to see if we have enough to relay
     let mut total_weth_reward: Uint256 = Uint256::default();
     for (token, total) in rewards.iter() {
-        if *token == *WETH_CONTRACT_ADDRESS {
-            // WETH directly counts as ETH
+        if *token == price_base_token {
+            // the base token (WETH on Ethereum) directly counts as ETH
             total_weth_reward = total_weth_reward.checked_add(*total).unwrap();
         } else {
             // Get the token's value in ETH as of the current moment
@@ -54,7 +58,7 @@ async fn should_relay_logic_call(
                 .get_uniswap_price(
                     our_address,
                     *token,
-                    *WETH_CONTRACT_ADDRESS,
+                    price_base_token,
                     None,
                     *total,
                     None,
@@ -93,6 +97,7 @@ pub async fn relay_logic_calls(
     gravity_id: String,
     timeout: Duration,
     config: &RelayerConfig,
+    nonce_manager: &NonceManager,
 ) {
     let our_ethereum_address = ethereum_key.to_address();
 
@@ -115,7 +120,10 @@ pub async fn relay_logic_calls(
         if let Ok(sigs) = sigs {
             let hash = encode_logic_call_confirm_hashed(gravity_id.clone(), call.clone());
             // this checks that the signatures for the batch are actually possible to submit to the chain
-            if current_valset.order_sigs(&hash, &sigs).is_ok() {
+            if current_valset
+                .order_sigs_with_min_power(&hash, &sigs, config.min_relay_power())
+                .is_ok()
+            {
                 oldest_signed_call = Some(call);
                 oldest_signatures = Some(sigs);
             } else {
@@ -124,6 +132,7 @@ pub async fn relay_logic_calls(
                     bytes_to_hex_str(&call.invalidation_id),
                     call.invalidation_nonce
                 );
+                trace!("{}", describe_logic_call_confirm(gravity_id.clone(), call));
             }
         } else {
             error!(
@@ -188,6 +197,7 @@ pub async fn relay_logic_calls(
                 web3,
                 &oldest_signed_call,
                 cost.get_total(),
+                config.price_base_token,
             )
             .await
         } else {
@@ -195,6 +205,12 @@ pub async fn relay_logic_calls(
         };
 
         if should_relay {
+            let item = format!(
+                "logic call {}/{}",
+                bytes_to_hex_str(&oldest_signed_call.invalidation_id),
+                oldest_signed_call.invalidation_nonce
+            );
+            let rewards = oldest_signed_call.fees.clone();
             let res = send_eth_logic_call(
                 current_valset,
                 oldest_signed_call,
@@ -204,10 +220,22 @@ pub async fn relay_logic_calls(
                 gravity_contract_address,
                 gravity_id.clone(),
                 ethereum_key,
+                nonce_manager,
             )
             .await;
-            if res.is_err() {
-                info!("LogicCall submission failed with {:?}", res);
+            match res {
+                Ok(_) => {
+                    record_relay(
+                        &item,
+                        &rewards,
+                        cost.get_total(),
+                        config.price_base_token,
+                        our_ethereum_address,
+                        web3,
+                    )
+                    .await
+                }
+                Err(e) => info!("LogicCall submission failed with {:?}", e),
             }
         } else {
             info!(
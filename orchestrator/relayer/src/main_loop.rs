@@ -1,19 +1,31 @@
-use std::time::Duration;
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    time::Duration,
+};
 
+use cosmos_gravity::query::get_pending_transfer_counts;
+use ethereum_gravity::{nonce_manager::NonceManager, utils::get_gravity_paused};
 use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
 use gravity_utils::{
     clarity::{address::Address as EthAddress, PrivateKey as EthPrivateKey},
     deep_space::{Coin, Contact, PrivateKey as CosmosPrivateKey},
     error::GravityError,
+    pause::submissions_paused,
+    signer_status::signer_behind,
     types::RelayerConfig,
+    watchdog::heartbeat,
     web30::client::Web3,
 };
+use metrics_exporter::{metrics_latest, metrics_pending_transfers};
 use tokio::time::sleep;
 use tonic::transport::Channel;
 
 use crate::{
-    batch_relaying::relay_batches, find_latest_valset::find_latest_valset,
-    logic_call_relaying::relay_logic_calls, request_batches::request_batches,
+    batch_relaying::{batches_need_valset_update, relay_batches},
+    find_latest_valset::find_latest_valset,
+    logic_call_relaying::relay_logic_calls,
+    request_batches::request_batches,
     valset_relaying::relay_valsets,
 };
 
@@ -35,9 +47,36 @@ pub async fn relayer_main_loop(
 ) -> Result<(), GravityError> {
     let mut grpc_client = grpc_client;
     let loop_speed = Duration::from_secs(relayer_config.relayer_loop_speed);
+    let mut nonce_manager = NonceManager::new(relayer_config.nonce_management)
+        .with_max_in_flight(relayer_config.max_in_flight_relays);
+    if let Some(path) = &relayer_config.nonce_state_file {
+        nonce_manager = nonce_manager.with_state_file(PathBuf::from(path));
+    }
+    nonce_manager
+        .restore_state(&web3, ethereum_key.to_address())
+        .await;
+    let mut pending_transfer_tokens = HashSet::new();
+    let mut contract_paused = false;
+    let mut last_batch_requests = HashMap::new();
     loop {
+        heartbeat("relayer");
         let (async_result, _) = tokio::join!(
             async {
+                if submissions_paused() {
+                    warn!("Submissions are paused, skipping this relayer iteration");
+                    return Ok(());
+                }
+                if gravity_contract_paused(
+                    gravity_contract_address,
+                    ethereum_key.to_address(),
+                    &web3,
+                    &mut contract_paused,
+                )
+                .await
+                {
+                    return Ok(());
+                }
+
                 let current_valset =
                     find_latest_valset(&mut grpc_client, gravity_contract_address, &web3).await;
 
@@ -48,7 +87,14 @@ pub async fn relayer_main_loop(
 
                 let current_valset = current_valset.unwrap();
 
-                relay_valsets(
+                let force_valset = relayer_config.relay_prerequisite_valsets
+                    && batches_need_valset_update(
+                        &current_valset,
+                        &mut grpc_client,
+                        gravity_id.clone(),
+                    )
+                    .await;
+                let relayed_valset = relay_valsets(
                     &current_valset,
                     ethereum_key,
                     &web3,
@@ -57,44 +103,81 @@ pub async fn relayer_main_loop(
                     gravity_id.clone(),
                     TIMEOUT,
                     relayer_config,
+                    &nonce_manager,
+                    force_valset,
                 )
                 .await;
 
-                relay_batches(
-                    &current_valset,
-                    ethereum_key,
-                    &web3,
-                    &mut grpc_client,
-                    gravity_contract_address,
-                    gravity_id.clone(),
-                    TIMEOUT,
-                    relayer_config,
-                )
-                .await;
+                // signatures checked against the replaced valset would revert on Ethereum
+                let current_valset = if relayed_valset {
+                    match find_latest_valset(&mut grpc_client, gravity_contract_address, &web3)
+                        .await
+                    {
+                        Ok(v) => v,
+                        Err(e) => {
+                            error!("Could not get current valset! {:?}", e);
+                            return Ok(());
+                        }
+                    }
+                } else {
+                    current_valset
+                };
 
-                relay_logic_calls(
-                    &current_valset,
-                    ethereum_key,
-                    &web3,
-                    &mut grpc_client,
-                    gravity_contract_address,
-                    gravity_id.clone(),
-                    TIMEOUT,
-                    relayer_config,
-                )
-                .await;
+                // valsets are still relayed, everything else waits for the signer so that
+                // slashing avoidance comes first
+                let wait_for_signer = relayer_config.confirm_first && signer_behind();
+                if wait_for_signer {
+                    info!("Eth signer is behind on confirms, skipping batch and logic call relaying and batch requests");
+                } else {
+                    relay_batches(
+                        &current_valset,
+                        ethereum_key,
+                        &web3,
+                        &mut grpc_client,
+                        gravity_contract_address,
+                        gravity_id.clone(),
+                        TIMEOUT,
+                        relayer_config,
+                        &nonce_manager,
+                    )
+                    .await;
+
+                    relay_logic_calls(
+                        &current_valset,
+                        ethereum_key,
+                        &web3,
+                        &mut grpc_client,
+                        gravity_contract_address,
+                        gravity_id.clone(),
+                        TIMEOUT,
+                        relayer_config,
+                        &nonce_manager,
+                    )
+                    .await;
+                }
 
-                if let (Some(cosmos_key), Some(cosmos_fee)) = (cosmos_key, cosmos_fee.clone()) {
-                    request_batches(
+                record_pending_transfers(&mut grpc_client, &mut pending_transfer_tokens).await;
+
+                if let (false, Some(cosmos_key), Some(cosmos_fee)) =
+                    (wait_for_signer, cosmos_key, cosmos_fee.clone())
+                {
+                    let summary = request_batches(
                         &contact,
                         &web3,
                         &mut grpc_client,
-                        relayer_config.batch_request_mode,
+                        relayer_config,
                         ethereum_key.to_address(),
                         cosmos_key,
                         cosmos_fee,
+                        &mut last_batch_requests,
                     )
-                    .await
+                    .await;
+                    // unprofitable tokens are reconsidered every iteration, only log those at debug
+                    if summary.error.is_some() || !summary.requested().is_empty() {
+                        info!("{}", summary);
+                    } else if !summary.tokens.is_empty() {
+                        debug!("{}", summary);
+                    }
                 }
 
                 Ok(())
@@ -109,3 +192,66 @@ pub async fn relayer_main_loop(
         }
     }
 }
+
+/// Returns true if the Gravity contract is paused, relays would only revert and waste gas.
+/// Changes of the paused state are logged and the state is exported as the
+/// `gravity_contract_paused` metric. A failed check keeps the previous state, so contracts
+/// that can not be paused count as not paused while a paused contract stays paused until a
+/// check shows it unpaused
+async fn gravity_contract_paused(
+    gravity_contract_address: EthAddress,
+    our_ethereum_address: EthAddress,
+    web3: &Web3,
+    last_paused: &mut bool,
+) -> bool {
+    let paused = match get_gravity_paused(gravity_contract_address, our_ethereum_address, web3)
+        .await
+    {
+        Ok(paused) => paused,
+        // contracts without paused() fail every check, only worth a warning while paused
+        Err(e) if *last_paused => {
+            warn!(
+                "Could not check if the Gravity contract is still paused {:?}, relaying stays suspended",
+                e
+            );
+            true
+        }
+        Err(e) => {
+            trace!("Could not check if the Gravity contract is paused {:?}", e);
+            false
+        }
+    };
+    if paused && !*last_paused {
+        warn!("The Gravity contract is paused, relaying is suspended until it is unpaused");
+    } else if !paused && *last_paused {
+        info!("The Gravity contract is no longer paused, resuming relaying");
+    }
+    metrics_latest(paused as u64, "gravity_contract_paused");
+    *last_paused = paused;
+    paused
+}
+
+/// Exports the number of transfers waiting to be batched for each token, tokens that had
+/// transfers waiting in an earlier iteration and now have none are reported as zero
+async fn record_pending_transfers(
+    grpc_client: &mut GravityQueryClient<Channel>,
+    reported_tokens: &mut HashSet<EthAddress>,
+) {
+    let counts = match get_pending_transfer_counts(grpc_client).await {
+        Ok(counts) => counts,
+        Err(e) => {
+            warn!("Failed to get pending transfer counts {:?}", e);
+            return;
+        }
+    };
+    for token in reported_tokens.iter() {
+        if !counts.contains_key(token) {
+            metrics_pending_transfers(&token.to_string(), 0);
+        }
+    }
+    reported_tokens.clear();
+    for (token, count) in counts {
+        metrics_pending_transfers(&token.to_string(), count);
+        reported_tokens.insert(token);
+    }
+}
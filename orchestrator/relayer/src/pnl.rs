@@ -0,0 +1,67 @@
+//! Tracks the relayer's running profit and loss over the life of the process, the rewards of
+//! every successful relay valued in the price base token minus the gas spent relaying it.
+//! Gas is counted at the cost estimated right before submission, so the figure is an
+//! approximation of what a full accounting from transaction receipts would report
+
+use std::sync::Mutex;
+
+use gravity_utils::{
+    clarity::{Address as EthAddress, Uint256},
+    prices::get_base_token_price,
+    types::Erc20Token,
+    web30::client::Web3,
+};
+use metrics_exporter::metrics_relayer_net_pnl;
+
+const ONE_ETH_FLOAT: f64 = 1_000_000_000_000_000_000f64;
+
+/// The net profit of every relay since startup, in whole units of the price base token
+static NET_PNL: Mutex<f64> = Mutex::new(0f64);
+
+/// Adds a successful relay paying `rewards` at an estimated `gas_cost` to the running profit
+/// and loss and updates the `relayer_net_pnl_eth` gauge. Rewards that can not be priced are
+/// counted as worth nothing
+pub async fn record_relay(
+    item: &str,
+    rewards: &[Erc20Token],
+    gas_cost: Uint256,
+    price_base_token: EthAddress,
+    pubkey: EthAddress,
+    web3: &Web3,
+) {
+    let mut reward_value = 0f64;
+    for reward in rewards {
+        match get_base_token_price(
+            price_base_token,
+            reward.token_contract_address,
+            reward.amount,
+            pubkey,
+            web3,
+        )
+        .await
+        {
+            Ok(value) => reward_value += to_eth(value),
+            Err(e) => info!(
+                "Unable to price reward token {} of {} for the relayer P&L {:?}",
+                reward.token_contract_address, item, e
+            ),
+        }
+    }
+    let net = reward_value - to_eth(gas_cost);
+
+    let total = {
+        let mut pnl = NET_PNL.lock().unwrap();
+        *pnl += net;
+        *pnl
+    };
+    info!(
+        "Relayed {} for a net {:.4} ETH, relayer P&L since startup is {:.4} ETH",
+        item, net, total
+    );
+    metrics_relayer_net_pnl(total);
+}
+
+fn to_eth(input: Uint256) -> f64 {
+    let float: f64 = input.to_string().parse().unwrap();
+    float / ONE_ETH_FLOAT
+}
@@ -3,30 +3,145 @@
 //! By having batches requested by relayers instead of created automatically the chain can outsource
 //! the significant work of checking if a batch is profitable before creating it
 
+use std::{
+    collections::HashMap,
+    fmt,
+    time::{Duration, Instant},
+};
+
 use cosmos_gravity::{
     query::{get_erc20_to_denom, get_pending_batch_fees},
     send::send_request_batch,
 };
+use futures::stream::{self, StreamExt};
 use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
 use gravity_utils::{
     clarity::{Address as EthAddress, Uint256},
     deep_space::{Coin, Contact, PrivateKey},
-    prices::get_weth_price,
-    types::BatchRequestMode,
+    num_conversion::{print_eth, print_gwei},
+    prices::{get_base_token_price, get_erc20_decimals_cached, rescale_decimals, NATIVE_DECIMALS},
+    types::{BatchRequestMode, RelayerConfig},
     web30::client::Web3,
     TEST_GAS_LIMIT,
 };
+use metrics_exporter::metrics_errors_counter;
 use tonic::transport::Channel;
 
+/// The maximum number of token price lookups in flight at once
+const PRICE_LOOKUP_CONCURRENCY: usize = 4;
+
+/// What request_batches decided for a token with pending batch fees
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchRequestDecision {
+    /// a batch request was sent
+    Requested,
+    /// the fees are not worth the cost of relaying the batch
+    SkippedUnprofitable,
+    /// a lookup or the request itself failed
+    SkippedError(String),
+    /// batch requests are turned off for this token
+    Disabled,
+    /// a batch was requested for this token less than batch_request_cooldown ago
+    CoolingDown,
+}
+
+/// The outcome of a round of batch requests
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchRequestSummary {
+    /// the decision made for each token with pending batch fees
+    pub tokens: Vec<(EthAddress, BatchRequestDecision)>,
+    /// set if the round was abandoned before any token was considered
+    pub error: Option<String>,
+}
+
+impl BatchRequestSummary {
+    fn decide(&mut self, token: EthAddress, decision: BatchRequestDecision) {
+        self.tokens.push((token, decision));
+    }
+
+    /// The decision made for `token`, None if it had no pending fees
+    pub fn decision(&self, token: EthAddress) -> Option<&BatchRequestDecision> {
+        self.tokens
+            .iter()
+            .find(|(t, _)| *t == token)
+            .map(|(_, decision)| decision)
+    }
+
+    /// The tokens a batch was requested for
+    pub fn requested(&self) -> Vec<EthAddress> {
+        self.tokens
+            .iter()
+            .filter(|(_, decision)| *decision == BatchRequestDecision::Requested)
+            .map(|(token, _)| *token)
+            .collect()
+    }
+}
+
+impl BatchRequestDecision {
+    /// Explains the decision to an operator wondering why a batch was or was not requested
+    fn reason(&self) -> String {
+        match self {
+            BatchRequestDecision::Requested => "a batch was requested".to_string(),
+            BatchRequestDecision::SkippedUnprofitable => {
+                "skipped, the fees are not worth the relay cost or the gas price rose past max_gas_price_increase".to_string()
+            }
+            BatchRequestDecision::SkippedError(e) => format!("skipped, {}", e),
+            BatchRequestDecision::Disabled => {
+                "skipped, turned off by batch_request_mode or batch_request_tokens".to_string()
+            }
+            BatchRequestDecision::CoolingDown => {
+                "skipped, requested less than batch_request_cooldown ago".to_string()
+            }
+        }
+    }
+}
+
+/// What request_batches found for a token on the way to its decision, logged when
+/// batch_request_diagnostics is set
+#[derive(Debug, Clone, Default)]
+struct TokenDiagnosis {
+    total_fee: Uint256,
+    denom: Option<String>,
+    fee_value: Option<Uint256>,
+}
+
+impl fmt::Display for BatchRequestSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(e) = &self.error {
+            return write!(f, "Batch requests abandoned: {}", e);
+        }
+        let count = |matches: fn(&BatchRequestDecision) -> bool| {
+            self.tokens.iter().filter(|(_, d)| matches(d)).count()
+        };
+        write!(
+            f,
+            "Batch requests: {} requested, {} unprofitable, {} failed, {} disabled, {} cooling down",
+            count(|d| *d == BatchRequestDecision::Requested),
+            count(|d| *d == BatchRequestDecision::SkippedUnprofitable),
+            count(|d| matches!(d, BatchRequestDecision::SkippedError(_))),
+            count(|d| *d == BatchRequestDecision::Disabled),
+            count(|d| *d == BatchRequestDecision::CoolingDown),
+        )
+    }
+}
+
+/// Requests batches for the tokens with pending transfers that `config` allows.
+/// `last_requests` holds the time a batch was last requested for each token and is kept
+/// by the caller across iterations to enforce batch_request_cooldown
+#[allow(clippy::too_many_arguments)]
 pub async fn request_batches(
     contact: &Contact,
     web30: &Web3,
     grpc_client: &mut GravityQueryClient<Channel>,
-    batch_request_mode: BatchRequestMode,
+    config: &RelayerConfig,
     eth_address: EthAddress,
     private_key: PrivateKey,
     request_fee: Coin,
-) {
+    last_requests: &mut HashMap<EthAddress, Instant>,
+) -> BatchRequestSummary {
+    let cooldown = Duration::from_secs(config.batch_request_cooldown);
+    let mut summary = BatchRequestSummary::default();
+    let mut diagnoses: HashMap<EthAddress, TokenDiagnosis> = HashMap::new();
     // this actually works either way but sending a tx with zero as the fee
     // value seems strange
     let request_fee = if request_fee.amount.is_zero() {
@@ -38,62 +153,353 @@ pub async fn request_batches(
     let eth_gas_price = web30.eth_gas_price().await;
     if let Err(e) = eth_gas_price {
         warn!("Could not get gas price for auto batch request {:?}", e);
-        return;
+        summary.error = Some(format!("Could not get gas price {:?}", e));
+        if config.batch_request_diagnostics {
+            info!("Batch request diagnosis: no tokens considered, could not get the Ethereum gas price");
+        }
+        return summary;
     }
     let eth_gas_price = eth_gas_price.unwrap();
 
     let batch_fees = get_pending_batch_fees(grpc_client).await;
     if let Err(e) = batch_fees {
         warn!("Failed to get batch fees with {:?}", e);
-        return;
+        summary.error = Some(format!("Failed to get batch fees {:?}", e));
+        if config.batch_request_diagnostics {
+            info!("Batch request diagnosis: no tokens considered, could not get the pending batch fees from Cosmos");
+        }
+        return summary;
     }
     let batch_fees = batch_fees.unwrap();
+    if config.batch_request_diagnostics && batch_fees.batch_fees.is_empty() {
+        info!("Batch request diagnosis: Cosmos reports no pending transfers for any token, there is nothing to request");
+    }
 
+    let mut requestable = Vec::new();
     for fee in batch_fees.batch_fees {
-        let total_fee = Uint256::from_dec_or_hex_str_restricted(&fee.total_fees).unwrap();
-        let token: EthAddress = fee.token.parse().unwrap();
+        // a single malformed entry from the node should not stop requests for every other token
+        let token: EthAddress = match fee.token.parse() {
+            Ok(token) => token,
+            Err(e) => {
+                error!(
+                    "Node returned invalid batch fee token {} {:?}, skipping it",
+                    fee.token, e
+                );
+                metrics_errors_counter(2, "Invalid batch fee token");
+                continue;
+            }
+        };
+        let total_fee = match Uint256::from_dec_or_hex_str_restricted(&fee.total_fees) {
+            Ok(total_fee) => total_fee,
+            Err(e) => {
+                error!(
+                    "Node returned invalid total fees {} for {} {:?}, skipping it",
+                    fee.total_fees, token, e
+                );
+                metrics_errors_counter(2, "Invalid batch fee total");
+                continue;
+            }
+        };
+        diagnoses.insert(
+            token,
+            TokenDiagnosis {
+                total_fee,
+                ..Default::default()
+            },
+        );
+        if !config.should_request_token(token) {
+            trace!(
+                "Not requesting batch for {}, it is not in batch_request_tokens",
+                token
+            );
+            summary.decide(token, BatchRequestDecision::Disabled);
+            continue;
+        }
+        if let Some(last) = last_requests.get(&token) {
+            if last.elapsed() < cooldown {
+                trace!(
+                    "Not requesting batch for {}, the last request was {}s ago",
+                    token,
+                    last.elapsed().as_secs()
+                );
+                summary.decide(token, BatchRequestDecision::CoolingDown);
+                continue;
+            }
+        }
         let denom = get_erc20_to_denom(grpc_client, token).await;
         if let Err(e) = denom {
             error!(
                 "Failed to lookup erc20 {} for batch with {:?}",
                 fee.token, e
             );
+            summary.decide(
+                token,
+                BatchRequestDecision::SkippedError(format!("Denom lookup failed {:?}", e)),
+            );
             continue;
         }
         let denom = denom.unwrap().denom;
+        if let Some(d) = diagnoses.get_mut(&token) {
+            d.denom = Some(denom.clone());
+        }
+        requestable.push((token, total_fee, denom));
+    }
+
+    let to_request = match config.batch_request_mode {
+        BatchRequestMode::ProfitableOnly => {
+            let weth_cost_estimate = eth_gas_price.checked_mul(TEST_GAS_LIMIT).unwrap();
+            // the price lookups are independent so they are issued concurrently, the
+            // requests themselves are sent one at a time to keep Cosmos sequences in order
+            let priced: Vec<_> = stream::iter(requestable)
+                .map(|(token, total_fee, denom)| async move {
+                    let price = get_base_token_price(
+                        config.price_base_token,
+                        token,
+                        total_fee,
+                        eth_address,
+                        web30,
+                    )
+                    .await;
+                    (token, total_fee, denom, price)
+                })
+                .buffer_unordered(PRICE_LOOKUP_CONCURRENCY)
+                .collect()
+                .await;
 
-        match batch_request_mode {
-            BatchRequestMode::ProfitableOnly => {
-                let weth_cost_estimate = eth_gas_price.checked_mul(TEST_GAS_LIMIT).unwrap();
-                match get_weth_price(token, total_fee, eth_address, web30).await {
+            let mut to_request = Vec::new();
+            for (token, total_fee, denom, price) in priced {
+                if let (Ok(price), Some(d)) = (&price, diagnoses.get_mut(&token)) {
+                    d.fee_value = Some(*price);
+                }
+                match price {
                     Ok(price) => {
                         if price > weth_cost_estimate {
-                            let res = send_request_batch(
-                                private_key,
-                                denom,
-                                request_fee.clone(),
-                                contact,
-                            )
-                            .await;
-                            if let Err(e) = res {
-                                warn!("Failed to request batch with {:?}", e);
-                            }
+                            to_request.push((token, denom));
+                        } else if config.always_relays_token(token) {
+                            warn!(
+                                "Requesting batch for always relay token {} at a loss, fees are worth {} against a cost of {}",
+                                token,
+                                print_eth(price),
+                                print_eth(weth_cost_estimate)
+                            );
+                            to_request.push((token, denom));
                         } else {
-                            trace!("Did not request unprofitable batch");
+                            // fees are in the token's own decimals, prices are always in 18
+                            let fee =
+                                match get_erc20_decimals_cached(token, eth_address, web30).await {
+                                    Ok(decimals) => print_eth(rescale_decimals(
+                                        total_fee,
+                                        decimals,
+                                        NATIVE_DECIMALS,
+                                    )),
+                                    Err(_) => total_fee.to_string(),
+                                };
+                            trace!(
+                                "Did not request unprofitable batch for {}, fees of {} are worth {} against a cost of {}",
+                                token,
+                                fee,
+                                print_eth(price),
+                                print_eth(weth_cost_estimate)
+                            );
+                            summary.decide(token, BatchRequestDecision::SkippedUnprofitable);
                         }
                     }
-                    Err(e) => warn!("Failed to get price for token {} with {:?}", fee.token, e),
+                    Err(e) if config.always_relays_token(token) => {
+                        warn!(
+                            "Requesting batch for always relay token {} without a price {:?}",
+                            token, e
+                        );
+                        to_request.push((token, denom));
+                    }
+                    Err(e) => {
+                        warn!("Failed to get price for token {} with {:?}", token, e);
+                        summary.decide(
+                            token,
+                            BatchRequestDecision::SkippedError(format!(
+                                "Price lookup failed {:?}",
+                                e
+                            )),
+                        );
+                    }
                 }
             }
-            BatchRequestMode::EveryBatch => {
-                info!("Requesting batch for {}", fee.token);
-                let res =
-                    send_request_batch(private_key, denom, request_fee.clone(), contact).await;
-                if let Err(e) = res {
-                    warn!("Failed to request batch with {:?}", e);
+            to_request
+        }
+        BatchRequestMode::EveryBatch => requestable
+            .into_iter()
+            .map(|(token, _, denom)| (token, denom))
+            .collect(),
+        BatchRequestMode::None => {
+            for (token, _, _) in requestable {
+                summary.decide(token, BatchRequestDecision::Disabled);
+            }
+            Vec::new()
+        }
+    };
+
+    // only profitability depends on the gas price, other modes request regardless of cost
+    let max_gas_price = match (config.batch_request_mode, config.max_gas_price_increase) {
+        (BatchRequestMode::ProfitableOnly, Some(increase)) => {
+            Some(gas_price_limit(eth_gas_price, increase))
+        }
+        _ => None,
+    };
+
+    let mut gas_price_exceeded = false;
+    for (token, denom) in to_request {
+        // always relay tokens are requested regardless of cost, so only they survive a gas spike
+        match max_gas_price {
+            Some(_) if config.always_relays_token(token) => {}
+            Some(_) if gas_price_exceeded => {
+                summary.decide(token, BatchRequestDecision::SkippedUnprofitable);
+                continue;
+            }
+            Some(max_gas_price) => match web30.eth_gas_price().await {
+                Ok(current) if current > max_gas_price => {
+                    warn!(
+                        "Gas price rose from {} to {} since profitability was checked, abandoning the remaining profitable batch requests",
+                        eth_gas_price, current
+                    );
+                    gas_price_exceeded = true;
+                    summary.decide(token, BatchRequestDecision::SkippedUnprofitable);
+                    continue;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    warn!(
+                        "Could not recheck gas price, abandoning the remaining profitable batch requests {:?}",
+                        e
+                    );
+                    gas_price_exceeded = true;
+                    summary.decide(
+                        token,
+                        BatchRequestDecision::SkippedError(format!(
+                            "Could not recheck gas price {:?}",
+                            e
+                        )),
+                    );
+                    continue;
                 }
+            },
+            None => {}
+        }
+        info!("Requesting batch for {}", token);
+        let res = send_request_batch(private_key, denom, request_fee.clone(), contact).await;
+        match res {
+            Ok(_) => {
+                last_requests.insert(token, Instant::now());
+                summary.decide(token, BatchRequestDecision::Requested)
+            }
+            Err(e) => {
+                warn!("Failed to request batch with {:?}", e);
+                summary.decide(
+                    token,
+                    BatchRequestDecision::SkippedError(format!("Request failed {:?}", e)),
+                );
             }
-            BatchRequestMode::None => {}
         }
     }
+    if config.batch_request_diagnostics {
+        log_diagnoses(
+            &summary,
+            &diagnoses,
+            config,
+            eth_gas_price,
+            eth_address,
+            web30,
+        )
+        .await;
+    }
+    summary
+}
+
+/// Logs the full reasoning behind the decision for every token with pending fees, one block
+/// per token, so that an operator can see why their relayer is or is not requesting batches
+async fn log_diagnoses(
+    summary: &BatchRequestSummary,
+    diagnoses: &HashMap<EthAddress, TokenDiagnosis>,
+    config: &RelayerConfig,
+    eth_gas_price: Uint256,
+    eth_address: EthAddress,
+    web30: &Web3,
+) {
+    let cost = eth_gas_price.checked_mul(TEST_GAS_LIMIT).unwrap();
+    for (token, decision) in summary.tokens.iter() {
+        let diagnosis = diagnoses.get(token).cloned().unwrap_or_default();
+        // fees are in the token's own decimals, prices are always in 18
+        let fees = match get_erc20_decimals_cached(*token, eth_address, web30).await {
+            Ok(decimals) => print_eth(rescale_decimals(
+                diagnosis.total_fee,
+                decimals,
+                NATIVE_DECIMALS,
+            )),
+            Err(_) => format!("{} (unknown decimals)", diagnosis.total_fee),
+        };
+        let value = match (config.batch_request_mode, diagnosis.fee_value) {
+            (BatchRequestMode::ProfitableOnly, Some(value)) => print_eth(value),
+            (BatchRequestMode::ProfitableOnly, None) => "not priced".to_string(),
+            (mode, _) => format!("not priced in {:?} mode", mode),
+        };
+        info!(
+            "Batch request diagnosis for {}\n  mode:          {:?}\n  pending fees:  {}\n  denom:         {}\n  gas price:     {} gwei\n  relay cost:    {} for {} gas\n  fee value:     {}\n  decision:      {}",
+            token,
+            config.batch_request_mode,
+            fees,
+            diagnosis.denom.as_deref().unwrap_or("not resolved"),
+            print_gwei(eth_gas_price),
+            print_eth(cost),
+            TEST_GAS_LIMIT,
+            value,
+            decision.reason()
+        );
+    }
+}
+
+/// The highest gas price at which requests judged profitable at `gas_price` are still sent
+fn gas_price_limit(gas_price: Uint256, increase: f64) -> Uint256 {
+    // scaled in integer math to avoid losing precision on large gas prices
+    let increase_millis = Uint256::from_u64((increase * 1000f64) as u64);
+    gas_price
+        .checked_mul(increase_millis)
+        .map(|v| v.checked_div(Uint256::from_u64(1000)).unwrap())
+        .unwrap_or_else(Uint256::max_value)
+}
+
+#[cfg(test)]
+mod tests {
+    use gravity_utils::{clarity::u256, u64_array_bigints};
+
+    use super::*;
+
+    #[test]
+    fn test_gas_price_limit() {
+        assert_eq!(gas_price_limit(u256!(100), 1.0), u256!(100));
+        assert_eq!(gas_price_limit(u256!(100), 1.25), u256!(125));
+        assert_eq!(
+            gas_price_limit(u256!(50_000_000_000), 1.2),
+            u256!(60_000_000_000)
+        );
+    }
+
+    #[test]
+    fn test_batch_request_summary() {
+        let a: EthAddress = "0x6Bd41fCdF129297c3524395d669c0865b3CA85B2"
+            .parse()
+            .unwrap();
+        let b: EthAddress = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"
+            .parse()
+            .unwrap();
+        let mut summary = BatchRequestSummary::default();
+        summary.decide(a, BatchRequestDecision::Requested);
+        summary.decide(b, BatchRequestDecision::SkippedUnprofitable);
+        assert_eq!(summary.requested(), vec![a]);
+        assert_eq!(
+            summary.decision(b),
+            Some(&BatchRequestDecision::SkippedUnprofitable)
+        );
+        assert_eq!(
+            summary.to_string(),
+            "Batch requests: 1 requested, 1 unprofitable, 0 failed, 0 disabled, 0 cooling down"
+        );
+    }
 }
@@ -4,7 +4,9 @@ use std::time::Duration;
 
 use cosmos_gravity::query::{get_all_valset_confirms, get_latest_valsets, get_valset};
 use ethereum_gravity::{
+    checkpoint_layout::describe_valset_confirm,
     message_signatures::encode_valset_confirm_hashed,
+    nonce_manager::NonceManager,
     utils::{get_valset_nonce, GasCost},
     valset_update::send_eth_valset_update,
 };
@@ -13,19 +15,20 @@ use gravity_utils::{
     clarity::{address::Address as EthAddress, PrivateKey as EthPrivateKey},
     error::GravityError,
     num_conversion::{print_eth, print_gwei},
-    prices::get_weth_price,
-    types::{RelayerConfig, Valset, ValsetConfirmResponse, ValsetRelayingMode},
+    prices::get_base_token_price,
+    types::{Erc20Token, RelayerConfig, Valset, ValsetConfirmResponse, ValsetRelayingMode},
     web30::client::Web3,
 };
 use tonic::transport::Channel;
 
-use crate::batch_relaying::get_cost_with_margin;
+use crate::{batch_relaying::get_cost_with_margin, pnl::record_relay};
 
 #[allow(clippy::too_many_arguments)]
 /// High level entry point for valset relaying, this function starts by finding
 /// what validator set is valid, then evaluating if it should be relayed according
 /// to the users preferences and finally relaying a validator set that is valid at
-/// this moment in time
+/// this moment in time. With `force` the update is relayed regardless of the relaying
+/// mode, returns true if an update was relayed
 pub async fn relay_valsets(
     // the validator set currently in the contract on Ethereum
     current_valset: &Valset,
@@ -36,7 +39,9 @@ pub async fn relay_valsets(
     gravity_id: String,
     timeout: Duration,
     config: &RelayerConfig,
-) {
+    nonce_manager: &NonceManager,
+    force: bool,
+) -> bool {
     // we have to start with the current valset, we need to know what's currently
     // in the contract in order to determine if a new validator set is valid.
     // For example the contract has set A which contains validators x/y/z the
@@ -46,7 +51,7 @@ pub async fn relay_valsets(
 
     let latest_cosmos_valset_nonce = match get_latest_cosmos_valset_nonce(grpc_client).await {
         Some(n) => n,
-        None => return,
+        None => return false,
     };
 
     // the latest cosmos validator set that it is possible to submit given the constraints
@@ -62,13 +67,13 @@ pub async fn relay_valsets(
         Ok(v) => v,
         // If we get the RecoverableError we don't need to log it, since it is a kind of flag to indicate that it was expected.
         // And here we expect the ValsetUpToDate error which means that we don't need to update up to date valset.
-        Err(GravityError::RecoverableError(_)) => return,
+        Err(GravityError::RecoverableError(_)) => return false,
         Err(e) => {
             error!(
                 "We were unable to find a valid validator set update to submit! {:?}",
                 e
             );
-            return;
+            return false;
         }
     };
 
@@ -83,8 +88,10 @@ pub async fn relay_valsets(
         ethereum_key,
         timeout,
         config,
+        nonce_manager,
+        force,
     )
-    .await;
+    .await
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -100,7 +107,9 @@ async fn relay_valid_valset(
     ethereum_key: EthPrivateKey,
     timeout: Duration,
     config: &RelayerConfig,
-) {
+    nonce_manager: &NonceManager,
+    force: bool,
+) -> bool {
     let cost = ethereum_gravity::valset_update::estimate_valset_cost(
         valset_to_relay,
         current_valset,
@@ -121,7 +130,7 @@ async fn relay_valid_valset(
             current_valset,
         )
         .await;
-        return;
+        return false;
     }
     let cost = cost.unwrap();
 
@@ -140,11 +149,18 @@ async fn relay_valid_valset(
         cost,
         web3,
         &config.valset_relaying_mode,
+        config.price_base_token,
     )
     .await;
+    if force && !should_relay {
+        info!(
+            "Relaying valset {} because pending batches can not be verified without it",
+            valset_to_relay.nonce
+        );
+    }
 
-    if should_relay {
-        let _res = send_eth_valset_update(
+    if should_relay || force {
+        let relayed = send_eth_valset_update(
             valset_to_relay,
             current_valset,
             &conformations,
@@ -153,13 +169,36 @@ async fn relay_valid_valset(
             gravity_contract_address,
             gravity_id,
             ethereum_key,
+            nonce_manager,
         )
-        .await;
+        .await
+        .is_ok();
+        if relayed {
+            let rewards: Vec<Erc20Token> = valset_to_relay
+                .reward_token
+                .map(|token_contract_address| Erc20Token {
+                    amount: valset_to_relay.reward_amount,
+                    token_contract_address,
+                })
+                .into_iter()
+                .collect();
+            record_relay(
+                &format!("valset {}", valset_to_relay.nonce),
+                &rewards,
+                cost.get_total(),
+                config.price_base_token,
+                ethereum_key.to_address(),
+                web3,
+            )
+            .await;
+        }
+        relayed
     } else {
         info!(
             "The valset {} won't be relayed by this orchestrator, because relayer is disabled",
             valset_to_relay.nonce
         );
+        false
     }
 }
 
@@ -209,6 +248,10 @@ async fn find_latest_valid_valset(
                 } else if let Err(e) = res {
                     // this error prints details about why the valset is not valid, look at it
                     // if you are confused
+                    trace!(
+                        "{}",
+                        describe_valset_confirm(gravity_id.to_string(), &valset)
+                    );
                     last_error = Some(e);
                 }
             }
@@ -234,29 +277,13 @@ async fn should_relay_valset(
     cost: GasCost,
     web3: &Web3,
     config: &ValsetRelayingMode,
+    price_base_token: EthAddress,
 ) -> bool {
     match config {
         // if the user has configured only profitable relaying then it is our only consideration
-        ValsetRelayingMode::ProfitableOnly { margin } => match valset.reward_token {
-            Some(reward_token) => {
-                let price = get_weth_price(reward_token, valset.reward_amount, pubkey, web3).await;
-                let cost_with_margin = get_cost_with_margin(cost.get_total(), *margin);
-                // we need to see how much WETH we can get for the reward token amount,
-                // and compare that value to the gas cost times the margin
-                match price {
-                    Ok(price) => price > cost_with_margin,
-                    Err(e) => {
-                        info!(
-                            "Unable to determine swap price of token {} for WETH \n
-                             it may just not be on Uniswap - Will not be relaying valset {:?}",
-                            reward_token, e
-                        );
-                        false
-                    }
-                }
-            }
-            None => false,
-        },
+        ValsetRelayingMode::ProfitableOnly { margin } => {
+            valset_reward_is_profitable(valset, pubkey, cost, web3, *margin, price_base_token).await
+        }
 
         // if the user has requested to relay every single valset, we do so
         ValsetRelayingMode::EveryValset => true,
@@ -272,7 +299,52 @@ async fn should_relay_valset(
         // since we store all the required signatures for as long as we may need them on the cosmos chain it's not fatal to wait, we can always play
         // them back later when we need them. Since 2/3 of voting power is required to spend funds and only 1/3 of voting power must change over
         // before this condition is triggered it should not risk a stale validator set in the Ethereum side of the bridge sending funds.
-        ValsetRelayingMode::Altruistic => latest_cosmos_valset_nonce != valset.nonce,
+        //
+        // A valset that pays for itself costs the altruistic relayer nothing, so those are relayed as soon as they
+        // are valid, checked second so that pricing never holds up a required update
+        ValsetRelayingMode::Altruistic => {
+            latest_cosmos_valset_nonce != valset.nonce
+                || valset_reward_is_profitable(valset, pubkey, cost, web3, 1.0, price_base_token)
+                    .await
+        }
+    }
+}
+
+/// Returns true if the reward of `valset` is worth more in `price_base_token` than the
+/// gas cost of relaying it times `margin`, false for valsets without a reward
+async fn valset_reward_is_profitable(
+    valset: &Valset,
+    pubkey: EthAddress,
+    cost: GasCost,
+    web3: &Web3,
+    margin: f64,
+    price_base_token: EthAddress,
+) -> bool {
+    let reward_token = match valset.reward_token {
+        Some(reward_token) if !valset.reward_amount.is_zero() => reward_token,
+        _ => return false,
+    };
+    let price = get_base_token_price(
+        price_base_token,
+        reward_token,
+        valset.reward_amount,
+        pubkey,
+        web3,
+    )
+    .await;
+    let cost_with_margin = get_cost_with_margin(cost.get_total(), margin);
+    // we need to see how much WETH we can get for the reward token amount,
+    // and compare that value to the gas cost times the margin
+    match price {
+        Ok(price) => price > cost_with_margin,
+        Err(e) => {
+            info!(
+                "Unable to determine swap price of token {} for WETH \n
+                 it may just not be on Uniswap - Will not be relaying valset {:?}",
+                reward_token, e
+            );
+            false
+        }
     }
 }
 
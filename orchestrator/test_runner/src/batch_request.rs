@@ -0,0 +1,127 @@
+//! This is the testing module for the relayer's ProfitableOnly batch request mode, testing
+//! that `request_batches` only requests a batch once the fees in the pool are worth more
+//! on Uniswap than the estimated gas cost of relaying it. Like RELAY_MARKET this needs a
+//! mainnet fork to price DAI
+use std::collections::HashMap;
+
+use cosmos_gravity::query::get_latest_transaction_batches;
+use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
+use gravity_utils::{
+    clarity::{u256, Address as EthAddress},
+    deep_space::{coin::Coin, private_key::PrivateKey as CosmosPrivateKey, Contact},
+    types::{BatchRequestMode, GravityBridgeToolsConfig, RelayerConfig},
+    u64_array_bigints,
+    web30::{amm::DAI_CONTRACT_ADDRESS, client::Web3},
+};
+use relayer::request_batches::{request_batches, BatchRequestDecision, BatchRequestSummary};
+use tonic::transport::Channel;
+
+use crate::{
+    relay_market::setup_batch_test,
+    utils::{start_orchestrators, ValidatorKeys},
+    MINER_ADDRESS, ONE_ETH, STAKING_TOKEN, TOTAL_TIMEOUT,
+};
+
+pub async fn batch_request_test(
+    web30: &Web3,
+    grpc_client: GravityQueryClient<Channel>,
+    contact: &Contact,
+    keys: Vec<ValidatorKeys>,
+    gravity_address: EthAddress,
+) {
+    let grpc_client = &mut grpc_client.clone();
+    let erc20_contract = *DAI_CONTRACT_ADDRESS;
+
+    // the integrated relayer is left disabled so that the only batch requests are the
+    // ones made by this test
+    start_orchestrators(
+        keys.clone(),
+        gravity_address,
+        false,
+        GravityBridgeToolsConfig::default(),
+    )
+    .await;
+
+    let mut config = RelayerConfig::default();
+    config.batch_request_mode = BatchRequestMode::ProfitableOnly;
+
+    // a fee of a tiny fraction of a DAI is far below the cost of relaying
+    let (_, _, requester_key, _, _) = setup_batch_test(
+        web30,
+        contact,
+        keys.clone(),
+        gravity_address,
+        erc20_contract,
+        u256!(2500),
+        grpc_client,
+    )
+    .await;
+    let summary = request_and_wait(web30, contact, grpc_client, &config, requester_key).await;
+    assert_eq!(
+        summary.decision(erc20_contract),
+        Some(&BatchRequestDecision::SkippedUnprofitable)
+    );
+    assert!(
+        !has_batch(grpc_client, erc20_contract).await,
+        "Requested an unprofitable batch for {erc20_contract}"
+    );
+    info!("Unprofitable batch was not requested");
+
+    // 10 DAI in fees is well above the cost of relaying on the test chain
+    setup_batch_test(
+        web30,
+        contact,
+        keys,
+        gravity_address,
+        erc20_contract,
+        ONE_ETH.checked_mul(u256!(10)).unwrap(),
+        grpc_client,
+    )
+    .await;
+    let summary = request_and_wait(web30, contact, grpc_client, &config, requester_key).await;
+    assert_eq!(
+        summary.decision(erc20_contract),
+        Some(&BatchRequestDecision::Requested)
+    );
+    assert!(
+        has_batch(grpc_client, erc20_contract).await,
+        "Failed to request a profitable batch for {erc20_contract}"
+    );
+    info!("Successfully requested a profitable batch!");
+}
+
+async fn request_and_wait(
+    web30: &Web3,
+    contact: &Contact,
+    grpc_client: &mut GravityQueryClient<Channel>,
+    config: &RelayerConfig,
+    requester_key: CosmosPrivateKey,
+) -> BatchRequestSummary {
+    let summary = request_batches(
+        contact,
+        web30,
+        grpc_client,
+        config,
+        *MINER_ADDRESS,
+        requester_key,
+        Coin {
+            amount: u256!(0),
+            denom: STAKING_TOKEN.clone(),
+        },
+        &mut HashMap::new(),
+    )
+    .await;
+    contact.wait_for_next_block(TOTAL_TIMEOUT).await.unwrap();
+    summary
+}
+
+async fn has_batch(
+    grpc_client: &mut GravityQueryClient<Channel>,
+    erc20_contract: EthAddress,
+) -> bool {
+    get_latest_transaction_batches(grpc_client)
+        .await
+        .expect("Failed to get latest batches")
+        .iter()
+        .any(|batch| batch.token_contract == erc20_contract)
+}
@@ -1,19 +1,20 @@
 //! This is a test for Evidence based slashing, we simply create a bad signature and submit it as evidence
 //! we don't launch the orchestrators here as they are not required.
 
-use cosmos_gravity::{send::submit_bad_signature_evidence, utils::BadSignatureEvidence};
+use cosmos_gravity::{send::submit_bad_signature_evidence_batch, utils::BadSignatureEvidence};
 use ethereum_gravity::{
     message_signatures::{encode_valset_confirm, encode_valset_confirm_hashed},
     utils::get_gravity_id,
 };
 use gravity_proto::cosmos_sdk_proto::cosmos::staking::v1beta1::QueryValidatorsRequest;
 use gravity_utils::{
-    clarity::{u256, utils::bytes_to_hex_str, Address as EthAddress},
+    clarity::{u256, utils::bytes_to_hex_str, Address as EthAddress, PrivateKey as EthPrivateKey},
     deep_space::{Coin, Contact, PrivateKey},
     types::{Valset, ValsetMember},
     u64_array_bigints,
     web30::client::Web3,
 };
+use rand::Rng;
 
 use crate::{
     get_fee,
@@ -35,10 +36,37 @@ pub async fn evidence_based_slashing(
 
     // our slashing victim is just the first validator
     let cosmos_private_key = keys[0].validator_key;
-    let eth_private_key = keys[0].eth_key;
-    let eth_addr = eth_private_key.to_address();
+
+    // now we are prepared to submit our evidence, we check first that validator 0 is in the set
+    print_validator_status(contact).await;
+    let (is_in_set, jailed) =
+        check_validator(contact, cosmos_private_key, "BOND_STATUS_BONDED").await;
+    assert!(is_in_set);
+    assert!(!jailed);
+    info!("Target validator is in the set and not jailed");
+
     // reporter is another validator using their delegate key
-    let submitter_private_key = keys[1].orch_key;
+    submit_false_valset_evidence(web30, contact, &keys[0], keys[1].orch_key, gravity_address).await;
+
+    // confirm that the validator for which the evidence has been submitted is removed
+    let (is_in_set, jailed) =
+        check_validator(contact, cosmos_private_key, "BOND_STATUS_UNBONDING").await;
+    assert!(is_in_set);
+    assert!(jailed);
+    info!("Evidence based slashing test succeeded! Validator now jailed!");
+}
+
+/// Signs a false valset with `victim`'s Ethereum key and has `submitter` report it as evidence,
+/// which gets the victim slashed and jailed
+pub async fn submit_false_valset_evidence(
+    web30: &Web3,
+    contact: &Contact,
+    victim: &ValidatorKeys,
+    submitter_private_key: PrivateKey,
+    gravity_address: EthAddress,
+) {
+    let eth_private_key = victim.eth_key;
+    let eth_addr = eth_private_key.to_address();
     // this is a false valset, one that happens to contain only the
     // validator signing it, as if they where trying to take over the
     // bridge. This valset isn't valid for submitting but that's not a
@@ -67,36 +95,39 @@ pub async fn evidence_based_slashing(
         eth_addr
     );
 
-    // now we are prepared to submit our evidence, we check first that validator 0 is in the set
-    print_validator_status(contact).await;
-    let (is_in_set, jailed) =
-        check_validator(contact, cosmos_private_key, "BOND_STATUS_BONDED").await;
-    assert!(is_in_set);
-    assert!(!jailed);
-    info!("Target validator is in the set and not jailed");
+    // evidence signed by a key that no validator has is rejected by the chain, batching it with
+    // the real evidence checks that it does not keep the real evidence from landing
+    let secret: [u8; 32] = rand::thread_rng().gen();
+    let unknown_signature = EthPrivateKey::from_slice(&secret)
+        .unwrap()
+        .sign_ethereum_msg(&message);
 
     info!("Submitting Evidence");
     // submit the evidence
-    let res = submit_bad_signature_evidence(
+    let res = submit_bad_signature_evidence_batch(
         submitter_private_key,
         get_fee(),
         contact,
-        BadSignatureEvidence::Valset(false_valset),
-        eth_signature,
+        vec![
+            (
+                BadSignatureEvidence::Valset(false_valset.clone()),
+                eth_signature,
+            ),
+            (
+                BadSignatureEvidence::Valset(false_valset),
+                unknown_signature,
+            ),
+        ],
+        2,
     )
     .await
     .unwrap();
     trace!("{:?}", res);
-
-    // confirm that the validator for which the evidence has been submitted is removed
-    let (is_in_set, jailed) =
-        check_validator(contact, cosmos_private_key, "BOND_STATUS_UNBONDING").await;
-    assert!(is_in_set);
-    assert!(jailed);
-    info!("Evidence based slashing test succeeded! Validator now jailed!");
+    assert!(res[0].is_ok());
+    assert!(res[1].is_err());
 }
 
-async fn check_validator(contact: &Contact, key: PrivateKey, filter: &str) -> (bool, bool) {
+pub async fn check_validator(contact: &Contact, key: PrivateKey, filter: &str) -> (bool, bool) {
     let validators = contact
         .get_validators_list(QueryValidatorsRequest {
             pagination: None,
@@ -113,7 +144,7 @@ async fn check_validator(contact: &Contact, key: PrivateKey, filter: &str) -> (b
     (false, false)
 }
 
-async fn print_validator_status(contact: &Contact) {
+pub async fn print_validator_status(contact: &Contact) {
     let validators = contact.get_active_validators().await.unwrap();
     for val in validators.iter() {
         info!(
@@ -124,12 +155,43 @@ async fn print_validator_status(contact: &Contact) {
 }
 
 /// Delegates to a specific validator
-async fn delegate_to_validator(keys: &[ValidatorKeys], to: PrivateKey, contact: &Contact) {
+pub async fn delegate_to_validator(keys: &[ValidatorKeys], to: PrivateKey, contact: &Contact) {
     let delegate_address = get_operator_address(to);
     let amount = Coin {
         denom: STAKING_TOKEN.to_string(),
         amount: STARTING_STAKE_PER_VALIDATOR.wrapping_shr(2),
     };
+
+    // fail with a clear message instead of somewhere inside the delegation if the stake
+    // constants ever change so that the delegator can no longer afford this
+    let delegator = keys[1]
+        .validator_key
+        .to_address(&contact.get_prefix())
+        .unwrap();
+    let balance = contact
+        .get_balance(delegator, STAKING_TOKEN.to_string())
+        .await
+        .unwrap()
+        .map(|c| c.amount)
+        .unwrap_or_default();
+    let fee = get_fee();
+    let required = if fee.denom == amount.denom {
+        amount.amount.checked_add(fee.amount).unwrap()
+    } else {
+        amount.amount
+    };
+    assert!(
+        balance >= required,
+        "Delegator {} has {}{} but delegating {} to {} needs {}{}, check STARTING_STAKE_PER_VALIDATOR",
+        delegator,
+        balance,
+        amount.denom,
+        amount,
+        delegate_address,
+        required,
+        amount.denom
+    );
+
     let res = contact
         .delegate_to_validator(
             delegate_address,
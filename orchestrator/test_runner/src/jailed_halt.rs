@@ -0,0 +1,188 @@
+//! This is the complement of the evidence based slashing test. Jailing a validator removes its
+//! power from the attestation tally, so the jailing itself can't halt the bridge. What halts it
+//! is a validator with more than 33% of the power that stops attesting, so the victim never runs
+//! an Orchestrator and is given more than a third of the power after the bridge is checked to
+//! work without it. Once the halt is asserted the oracle is reset through a governance unhalt
+//! proposal, then the victim is jailed so that the remaining validators can pass attestations
+//! again and the events observed during the halt must be replayed.
+
+use std::{env, time::Duration};
+
+use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
+use gravity_utils::{
+    clarity::{u256, Address as EthAddress, Uint256},
+    deep_space::Contact,
+    u64_array_bigints,
+    web30::client::Web3,
+};
+use tokio::time::sleep;
+use tonic::transport::Channel;
+
+use crate::{
+    airdrop_proposal::wait_for_proposals_to_execute,
+    evidence_based_slashing::{
+        check_validator, delegate_to_validator, print_validator_status,
+        submit_false_valset_evidence,
+    },
+    happy_path::{test_erc20_deposit_panic, test_erc20_deposit_result},
+    unhalt_bridge::{get_nonces, submit_and_pass_unhalt_bridge_proposal},
+    utils::*,
+    ONE_ETH, TOTAL_TIMEOUT,
+};
+
+/// How long a deposit is given to go through after the victim holds more than a third of the
+/// power before the bridge is considered halted, can be overridden with the HALT_GRACE_PERIOD
+/// env var in seconds
+const DEFAULT_HALT_GRACE_PERIOD: Duration = Duration::from_secs(60);
+
+pub async fn jailed_halt_test(
+    web30: &Web3,
+    grpc_client: GravityQueryClient<Channel>,
+    contact: &Contact,
+    keys: Vec<ValidatorKeys>,
+    gravity_address: EthAddress,
+    erc20_address: EthAddress,
+) {
+    let prefix = contact.get_prefix();
+    let mut grpc_client = grpc_client;
+    let grace_period = env::var("HALT_GRACE_PERIOD")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_HALT_GRACE_PERIOD);
+    let bridge_user = get_user_key();
+
+    send_one_eth(bridge_user.eth_address, web30).await;
+    send_erc20_bulk(
+        ONE_ETH.checked_mul(u256!(10)).unwrap(),
+        erc20_address,
+        &[bridge_user.eth_address],
+        web30,
+    )
+    .await;
+
+    // our victim is the first validator, it never runs an Orchestrator. With the starting
+    // stake spread evenly the others hold enough power for the bridge to work without it
+    let victim = &keys[0];
+    let attesting_keys = keys[1..].to_vec();
+    start_orchestrators(
+        attesting_keys.clone(),
+        gravity_address,
+        false,
+        create_default_test_config(),
+    )
+    .await;
+
+    // make sure the bridge works and every attesting validator agrees on the last event
+    // before the halt
+    test_erc20_deposit_panic(
+        web30,
+        contact,
+        &mut grpc_client,
+        bridge_user.cosmos_address,
+        gravity_address,
+        erc20_address,
+        u256!(10_000_000_000_000_000),
+        None,
+        None,
+    )
+    .await;
+    let valid_nonce = tokio::time::timeout(TOTAL_TIMEOUT, async {
+        loop {
+            let nonces = get_nonces(&mut grpc_client, &attesting_keys, &prefix).await;
+            if nonces.iter().all(|n| *n == nonces[0]) {
+                return nonces[0];
+            }
+            sleep(Duration::from_secs(1)).await;
+        }
+    })
+    .await
+    .expect("Can't await for same nonces within timeout");
+    info!(
+        "All attesting validators agree on event nonce {}",
+        valid_nonce
+    );
+
+    // once the victim holds more than a third of the power the others can no longer pass
+    // attestations without it
+    while !has_over_a_third_of_power(contact, victim).await {
+        delegate_to_validator(&keys, victim.validator_key, contact).await;
+    }
+    print_validator_status(contact).await;
+    let (is_in_set, jailed) =
+        check_validator(contact, victim.validator_key, "BOND_STATUS_BONDED").await;
+    assert!(is_in_set);
+    assert!(!jailed);
+    info!("Validator with more than a third of the power is bonded and not attesting");
+
+    let halted_bridge_amt = u256!(100_000_000_000_000_000);
+    info!(
+        "Checking that the bridge is halted, waiting {}s for the deposit",
+        grace_period.as_secs()
+    );
+    let res = test_erc20_deposit_result(
+        web30,
+        contact,
+        &mut grpc_client,
+        bridge_user.cosmos_address,
+        gravity_address,
+        erc20_address,
+        halted_bridge_amt,
+        Some(grace_period),
+        None,
+    )
+    .await;
+    if res.is_ok() {
+        panic!("Bridge not halted while a validator with more than a third of the power is not attesting!")
+    }
+    info!(
+        "Bridge halted, starting governance vote to reset nonce to {}",
+        valid_nonce
+    );
+
+    submit_and_pass_unhalt_bridge_proposal(valid_nonce, contact, &keys).await;
+    wait_for_proposals_to_execute(contact).await;
+
+    // jailing the victim takes its power out of the tally, so the claims the oracles replay
+    // after the reset can pass again
+    submit_false_valset_evidence(web30, contact, victim, keys[1].orch_key, gravity_address).await;
+    let (is_in_set, jailed) =
+        check_validator(contact, victim.validator_key, "BOND_STATUS_UNBONDING").await;
+    assert!(is_in_set);
+    assert!(jailed);
+    info!("Validator with more than a third of the power is jailed");
+
+    // the deposit made during the halt must be replayed by the oracle along with the new one
+    let fixed_bridge_amt = u256!(50_000_000_000_000_000);
+    let res = test_erc20_deposit_result(
+        web30,
+        contact,
+        &mut grpc_client,
+        bridge_user.cosmos_address,
+        gravity_address,
+        erc20_address,
+        fixed_bridge_amt,
+        None,
+        halted_bridge_amt.checked_add(fixed_bridge_amt),
+    )
+    .await;
+    match res {
+        Ok(_) => info!("Successfully replayed events after the halt!"),
+        Err(e) => panic!("Failed to bridge ERC20 after unhalting {:?}", e),
+    }
+}
+
+async fn has_over_a_third_of_power(contact: &Contact, victim: &ValidatorKeys) -> bool {
+    let victim_address = get_operator_address(victim.validator_key).to_string();
+    let validators = contact.get_active_validators().await.unwrap();
+    let mut total = u256!(0);
+    let mut victim_tokens = u256!(0);
+    for val in validators {
+        let tokens = Uint256::from_dec_or_hex_str_restricted(&val.tokens).unwrap();
+        total = total.checked_add(tokens).unwrap();
+        if val.operator_address == victim_address {
+            victim_tokens = tokens;
+        }
+    }
+    victim_tokens.checked_mul(u256!(3)).unwrap() > total
+}
@@ -0,0 +1,102 @@
+//! A TCP proxy that delays every chunk of traffic passing through it, placed between the
+//! orchestrators and the Cosmos gRPC endpoint it lets tests simulate a slow node so that
+//! timeouts and failover can be exercised under controlled conditions. Since gRPC runs over
+//! plain HTTP/2 in the test environment the proxy does not need to understand the protocol.
+
+use std::{env, time::Duration};
+
+use rand::Rng;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{
+        tcp::{OwnedReadHalf, OwnedWriteHalf},
+        TcpListener, TcpStream,
+    },
+    time::sleep,
+};
+
+/// Returns the latency and jitter to inject into the orchestrators' Cosmos gRPC connections,
+/// set with the COSMOS_GRPC_LATENCY_MS and COSMOS_GRPC_JITTER_MS env vars. Latency injection
+/// is disabled unless COSMOS_GRPC_LATENCY_MS is set
+pub fn grpc_latency_from_env() -> Option<(Duration, Duration)> {
+    let parse = |var| {
+        env::var(var)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .map(Duration::from_millis)
+    };
+    let latency = parse("COSMOS_GRPC_LATENCY_MS")?;
+    let jitter = parse("COSMOS_GRPC_JITTER_MS").unwrap_or_default();
+    Some((latency, jitter))
+}
+
+/// Starts a proxy on a random local port forwarding to `target_url`, every chunk read in
+/// either direction is held for `latency` plus a random amount up to `jitter` before being
+/// forwarded. A zero jitter gives a fixed, deterministic delay. Returns the url to connect to
+pub async fn start_latency_proxy(target_url: &str, latency: Duration, jitter: Duration) -> String {
+    let target = target_url
+        .trim_start_matches("http://")
+        .trim_end_matches('/')
+        .to_string();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let proxy_addr = listener.local_addr().unwrap();
+    info!(
+        "Started latency proxy on {} for {} with {}ms latency and {}ms jitter",
+        proxy_addr,
+        target,
+        latency.as_millis(),
+        jitter.as_millis()
+    );
+
+    tokio::spawn(async move {
+        loop {
+            let inbound = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    warn!("Latency proxy failed to accept a connection {:?}", e);
+                    continue;
+                }
+            };
+            let target = target.clone();
+            tokio::spawn(async move {
+                let outbound = match TcpStream::connect(&target).await {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        warn!("Latency proxy failed to connect to {} {:?}", target, e);
+                        return;
+                    }
+                };
+                let (inbound_read, inbound_write) = inbound.into_split();
+                let (outbound_read, outbound_write) = outbound.into_split();
+                tokio::join!(
+                    delayed_copy(inbound_read, outbound_write, latency, jitter),
+                    delayed_copy(outbound_read, inbound_write, latency, jitter)
+                );
+            });
+        }
+    });
+
+    format!("http://{}", proxy_addr)
+}
+
+/// Copies from `from` to `to` until either side closes, delaying each chunk
+async fn delayed_copy(
+    mut from: OwnedReadHalf,
+    mut to: OwnedWriteHalf,
+    latency: Duration,
+    jitter: Duration,
+) {
+    let mut buf = vec![0u8; 16 * 1024];
+    loop {
+        let len = match from.read(&mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(len) => len,
+        };
+        let extra = rand::thread_rng().gen_range(0..=jitter.as_millis() as u64);
+        sleep(latency + Duration::from_millis(extra)).await;
+        if to.write_all(&buf[..len]).await.is_err() {
+            break;
+        }
+    }
+    let _ = to.shutdown().await;
+}
@@ -30,15 +30,16 @@ use unhalt_bridge::unhalt_bridge_test;
 use valset_stress::validator_set_stress_test;
 
 use crate::{
-    airdrop_proposal::airdrop_proposal_test, bootstrapping::*,
+    airdrop_proposal::airdrop_proposal_test, batch_request::batch_request_test, bootstrapping::*,
     deposit_overflow::deposit_overflow_test, ethereum_blacklist_test::ethereum_blacklist_test,
     ibc_metadata::ibc_metadata_proposal_test, invalid_events::invalid_events,
-    pause_bridge::pause_bridge_test, signature_slashing::signature_slashing_test,
-    slashing_delegation::slashing_delegation_test, tx_cancel::send_to_eth_and_cancel, utils::*,
-    valset_rewards::valset_rewards_test,
+    jailed_halt::jailed_halt_test, pause_bridge::pause_bridge_test,
+    signature_slashing::signature_slashing_test, slashing_delegation::slashing_delegation_test,
+    tx_cancel::send_to_eth_and_cancel, utils::*, valset_rewards::valset_rewards_test,
 };
 
 mod airdrop_proposal;
+mod batch_request;
 mod bootstrapping;
 mod deposit_overflow;
 mod ethereum_blacklist_test;
@@ -47,6 +48,8 @@ mod happy_path;
 mod happy_path_v2;
 mod ibc_metadata;
 mod invalid_events;
+mod jailed_halt;
+mod latency_proxy;
 mod orch_keys;
 mod pause_bridge;
 mod relay_market;
@@ -363,6 +366,10 @@ pub async fn main() {
             info!("Starting relay market tests!");
             relay_market_test(&web30, grpc_client, &contact, keys, gravity_address).await;
             return;
+        } else if test_type == "RELAY_MARKET_BATCH_REQUEST" {
+            info!("Starting profitable batch request test!");
+            batch_request_test(&web30, grpc_client, &contact, keys, gravity_address).await;
+            return;
         } else if test_type == "ORCHESTRATOR_KEYS" {
             info!("Starting orchestrator key update tests!");
             orch_keys(grpc_client, &contact, keys).await;
@@ -379,7 +386,7 @@ pub async fn main() {
                 &web30,
                 keys,
                 gravity_address,
-                erc20_addresses[0],
+                &erc20_addresses,
             )
             .await;
             return;
@@ -407,6 +414,18 @@ pub async fn main() {
             )
             .await;
             return;
+        } else if test_type == "JAILED_HALT" {
+            info!("Starting jailed validator halt tests");
+            jailed_halt_test(
+                &web30,
+                grpc_client,
+                &contact,
+                keys,
+                gravity_address,
+                erc20_addresses[0],
+            )
+            .await;
+            return;
         } else if test_type == "PAUSE_BRIDGE" {
             info!("Starting pause bridge tests");
             pause_bridge_test(
@@ -74,7 +74,7 @@ async fn test_batches(
     .await;
 }
 
-async fn setup_batch_test(
+pub(crate) async fn setup_batch_test(
     web30: &Web3,
     contact: &Contact,
     keys: Vec<ValidatorKeys>,
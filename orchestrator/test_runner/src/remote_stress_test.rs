@@ -9,7 +9,7 @@ use gravity_utils::{
     num_conversion::print_eth,
     u64_array_bigints,
     web30::{client::Web3, types::SendTxOption},
-    TESTS_BATCH_NUM_USERS,
+    TESTS_BATCH_NUM_USERS, TEST_GAS_LIMIT,
 };
 use lazy_static::lazy_static;
 use tokio::time::sleep;
@@ -38,6 +38,47 @@ lazy_static! {
         env::var("NUM_OF_SEND_ITERATIONS").unwrap_or_else(|_| "5".to_string()).parse().unwrap();
 }
 
+/// Estimates what the run takes from the miner account, gas is priced at the gas limits and
+/// price multipliers the bulk sends use so this is an upper bound
+async fn estimate_funding(web30: &Web3, erc20_addresses: &[EthAddress]) -> FundingEstimate {
+    let gas_price = web30.eth_gas_price().await.unwrap();
+    let users = Uint256::from_usize(*NUM_USERS);
+    // every user has a sending and a destination address funded with ETH
+    let eth_sends = users.checked_mul(u256!(2)).unwrap();
+    let eth_send_cost = WEI_PER_USER
+        .checked_add(
+            gas_price
+                .checked_mul(u256!(2))
+                .unwrap()
+                .checked_mul(TEST_GAS_LIMIT)
+                .unwrap(),
+        )
+        .unwrap();
+    let erc20_sends = users
+        .checked_mul(Uint256::from_usize(erc20_addresses.len()))
+        .unwrap();
+    let erc20_send_cost = gas_price
+        .checked_mul(u256!(5))
+        .unwrap()
+        .checked_mul(TEST_GAS_LIMIT)
+        .unwrap();
+    let eth = eth_sends
+        .checked_mul(eth_send_cost)
+        .unwrap()
+        .checked_add(erc20_sends.checked_mul(erc20_send_cost).unwrap())
+        .unwrap();
+
+    let per_token = ONE_ETH
+        .checked_mul(Uint256::from_usize(*NUM_OF_SEND_ITERATIONS))
+        .unwrap()
+        .checked_mul(users)
+        .unwrap();
+    FundingEstimate {
+        eth,
+        erc20: erc20_addresses.iter().map(|e| (*e, per_token)).collect(),
+    }
+}
+
 /// Perform a stress test by sending thousands of
 /// transactions and producing large batches
 #[allow(clippy::too_many_arguments)]
@@ -48,6 +89,8 @@ pub async fn remote_stress_test(
     gravity_address: EthAddress,
     erc20_addresses: Vec<EthAddress>,
 ) {
+    check_miner_funding(&estimate_funding(web30, &erc20_addresses).await, web30).await;
+
     if !keys.is_empty() {
         start_orchestrators(
             keys.clone(),
@@ -4,7 +4,7 @@ use cosmos_gravity::{
 };
 use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
 use gravity_utils::{
-    clarity::{u256, Address as EthAddress},
+    clarity::{u256, Address as EthAddress, Uint256},
     deep_space::{coin::Coin, Contact},
     u64_array_bigints,
     web30::client::Web3,
@@ -13,15 +13,15 @@ use tonic::transport::Channel;
 
 use crate::{happy_path::test_erc20_deposit_panic, utils::*, GRAVITY_DENOM_PREFIX, ONE_ETH};
 
-// Justin: Here's the method I set up to test out sending and cancelling, but I have not been able to get any transaction ids
-// So I have not been able to generate the cancel request
+/// Deposits each of `erc20_addresses`, sends the resulting vouchers back to Ethereum and then
+/// cancels every send, checking that nothing is left pending and each balance is refunded
 pub async fn send_to_eth_and_cancel(
     contact: &Contact,
     grpc_client: GravityQueryClient<Channel>,
     web30: &Web3,
     keys: Vec<ValidatorKeys>,
     gravity_address: EthAddress,
-    erc20_address: EthAddress,
+    erc20_addresses: &[EthAddress],
 ) {
     let mut grpc_client = grpc_client;
 
@@ -31,71 +31,123 @@ pub async fn send_to_eth_and_cancel(
     // a pair of cosmos and Ethereum keys + addresses to use for this test
     let user_keys = get_user_key();
 
-    test_erc20_deposit_panic(
-        web30,
-        contact,
-        &mut grpc_client,
-        user_keys.cosmos_address,
-        gravity_address,
-        erc20_address,
-        ONE_ETH,
-        None,
-        None,
-    )
-    .await;
-
-    let token_name = format!("{GRAVITY_DENOM_PREFIX}{erc20_address}");
+    let mut sends = Vec::new();
+    for erc20_address in erc20_addresses {
+        test_erc20_deposit_panic(
+            web30,
+            contact,
+            &mut grpc_client,
+            user_keys.cosmos_address,
+            gravity_address,
+            *erc20_address,
+            ONE_ETH,
+            None,
+            None,
+        )
+        .await;
+        sends.push((
+            format!("{GRAVITY_DENOM_PREFIX}{erc20_address}"),
+            ONE_ETH.checked_sub(u256!(1_500)).unwrap(),
+        ));
+    }
 
-    let bridge_denom_fee = Coin {
-        denom: token_name.clone(),
-        amount: u256!(500),
-    };
-    let amount = ONE_ETH.checked_sub(u256!(1_500)).unwrap();
-    info!(
-        "Sending {}{} from {} on Cosmos back to Ethereum",
-        amount, token_name, user_keys.cosmos_address
-    );
+    send_and_cancel_all(contact, &mut grpc_client, &user_keys, &sends).await;
+    info!("Successfully canceled SendToEth!")
+}
 
-    // Generate the tx (this part is working for me)
-    let res = send_to_eth(
-        user_keys.cosmos_key,
-        user_keys.eth_address,
-        Coin {
-            denom: token_name.clone(),
-            amount,
-        },
-        bridge_denom_fee.clone(),
-        bridge_denom_fee.clone(),
-        contact,
-    )
-    .await
-    .unwrap();
-    info!("{:?}", res);
-    for thing in res.logs {
-        for event in thing.events {
-            info!("attribute for {:?}", event.attributes);
-        }
-    }
+/// Sends each (denom, amount) in `sends` to Ethereum then cancels all of them, paying fees in
+/// the denom being sent. The user must hold each amount plus 1500 of fees
+pub async fn send_and_cancel_all(
+    contact: &Contact,
+    grpc_client: &mut GravityQueryClient<Channel>,
+    user_keys: &BridgeUserKey,
+    sends: &[(String, Uint256)],
+) {
+    let mut sent = Vec::new();
+    for (denom, amount) in sends {
+        let bridge_denom_fee = Coin {
+            denom: denom.clone(),
+            amount: u256!(500),
+        };
+        let balance_before = contact
+            .get_balance(user_keys.cosmos_address, denom.clone())
+            .await
+            .unwrap()
+            .unwrap()
+            .amount;
+        info!(
+            "Sending {}{} from {} on Cosmos back to Ethereum",
+            amount, denom, user_keys.cosmos_address
+        );
 
-    let res = get_pending_send_to_eth(&mut grpc_client, user_keys.cosmos_address)
+        let pending_before = get_pending_send_to_eth(grpc_client, user_keys.cosmos_address)
+            .await
+            .unwrap();
+        let res = send_to_eth(
+            user_keys.cosmos_key,
+            user_keys.eth_address,
+            Coin {
+                denom: denom.clone(),
+                amount: *amount,
+            },
+            bridge_denom_fee.clone(),
+            bridge_denom_fee.clone(),
+            contact,
+        )
         .await
         .unwrap();
+        trace!("{:?}", res);
 
-    let send_to_eth_id = res.unbatched_transfers[0].id;
-
-    cancel_send_to_eth(
-        user_keys.cosmos_key,
-        bridge_denom_fee,
-        contact,
-        send_to_eth_id,
-    )
-    .await
-    .unwrap();
+        let pending = get_pending_send_to_eth(grpc_client, user_keys.cosmos_address)
+            .await
+            .unwrap();
+        let send_to_eth_id = pending
+            .unbatched_transfers
+            .iter()
+            .map(|t| t.id)
+            .find(|id| {
+                !pending_before
+                    .unbatched_transfers
+                    .iter()
+                    .any(|t| t.id == *id)
+            })
+            .expect("SendToEth is not pending");
+        sent.push((send_to_eth_id, bridge_denom_fee, balance_before));
+    }
 
-    let res = get_pending_send_to_eth(&mut grpc_client, user_keys.cosmos_address)
+    for (send_to_eth_id, bridge_denom_fee, _) in sent.iter() {
+        cancel_send_to_eth(
+            user_keys.cosmos_key,
+            bridge_denom_fee.clone(),
+            contact,
+            *send_to_eth_id,
+        )
         .await
         .unwrap();
+    }
 
+    let res = get_pending_send_to_eth(grpc_client, user_keys.cosmos_address)
+        .await
+        .unwrap();
     assert!(res.unbatched_transfers.is_empty());
-    info!("Successfully canceled SendToEth!")
+
+    // the amount and bridge fee are refunded on cancel, only the tx fees
+    // for the send and the cancel are lost
+    for (_, bridge_denom_fee, balance_before) in sent {
+        let balance_after = contact
+            .get_balance(user_keys.cosmos_address, bridge_denom_fee.denom.clone())
+            .await
+            .unwrap()
+            .unwrap()
+            .amount;
+        let tx_fees = bridge_denom_fee
+            .amount
+            .checked_add(bridge_denom_fee.amount)
+            .unwrap();
+        assert_eq!(balance_after, balance_before.checked_sub(tx_fees).unwrap());
+        info!(
+            "Cancelled SendToEth of {} was refunded",
+            bridge_denom_fee.denom
+        );
+    }
 }
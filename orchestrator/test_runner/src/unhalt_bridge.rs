@@ -229,7 +229,7 @@ pub async fn unhalt_bridge_test(
 }
 
 // Submits the custom Unhalt bridge governance proposal, votes yes for each validator, waits for votes to be submitted
-async fn submit_and_pass_unhalt_bridge_proposal(
+pub async fn submit_and_pass_unhalt_bridge_proposal(
     nonce: u64,
     contact: &Contact,
     keys: &[ValidatorKeys],
@@ -1,8 +1,8 @@
-use std::{panic, time::Duration};
+use std::{env, future::Future, panic, time::Duration};
 
 use cosmos_gravity::{proposals::submit_parameter_change_proposal, query::get_gravity_params};
 use ethereum_gravity::utils::get_event_nonce;
-use futures::future::join_all;
+use futures::stream::{self, StreamExt};
 use gravity_proto::{
     cosmos_sdk_proto::cosmos::{
         bank::v1beta1::Metadata,
@@ -18,6 +18,7 @@ use gravity_utils::{
         address::Address as CosmosAddress, coin::Coin, error::CosmosGrpcError,
         private_key::PrivateKey as CosmosPrivateKey, Contact, Fee, Msg,
     },
+    num_conversion::print_eth,
     types::{BatchRelayingMode, BatchRequestMode, GravityBridgeToolsConfig, ValsetRelayingMode},
     u64_array_bigints,
     web30::{client::Web3, jsonrpc::error::Web3Error, types::SendTxOption},
@@ -28,8 +29,10 @@ use rand::Rng;
 use tokio::time::sleep;
 
 use crate::{
-    get_deposit, get_fee, ADDRESS_PREFIX, COSMOS_NODE_GRPC, ETH_NODE, MINER_ADDRESS,
-    MINER_PRIVATE_KEY, ONE_ETH, ONE_HUNDRED_ETH, OPERATION_TIMEOUT, STAKING_TOKEN, TOTAL_TIMEOUT,
+    get_deposit, get_fee,
+    latency_proxy::{grpc_latency_from_env, start_latency_proxy},
+    ADDRESS_PREFIX, COSMOS_NODE_GRPC, ETH_NODE, MINER_ADDRESS, MINER_PRIVATE_KEY, ONE_ETH,
+    ONE_HUNDRED_ETH, OPERATION_TIMEOUT, STAKING_TOKEN, TOTAL_TIMEOUT,
 };
 
 /// returns the required denom metadata for deployed the Footoken
@@ -129,14 +132,73 @@ pub async fn send_erc20_bulk(
         transactions.push(send);
         nonce = nonce.checked_add(u256!(1)).unwrap();
     }
-    let txids = join_all(transactions).await;
+    let txids = join_bounded(transactions).await;
     wait_for_txids(txids, web3).await;
     let mut balance_checks = Vec::new();
     for address in destinations {
         let check = check_erc20_balance(erc20, amount, *address, web3);
         balance_checks.push(check);
     }
-    join_all(balance_checks).await;
+    join_bounded(balance_checks).await;
+}
+
+/// Returns how many user setup operations, funding transfers and the like, may be in flight
+/// at once. Set with the SETUP_CONCURRENCY env var, by default there is no limit
+pub fn setup_concurrency() -> usize {
+    env::var("SETUP_CONCURRENCY")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or(usize::MAX)
+}
+
+/// Runs `futures` with at most `setup_concurrency()` in flight, futures are started in order
+/// and their results are returned in the same order so per user indexing is preserved
+pub async fn join_bounded<F: Future>(futures: Vec<F>) -> Vec<F::Output> {
+    stream::iter(futures)
+        .buffered(setup_concurrency())
+        .collect()
+        .await
+}
+
+/// The funds a test run needs from the miner account, see `check_miner_funding`
+pub struct FundingEstimate {
+    pub eth: Uint256,
+    pub erc20: Vec<(EthAddress, Uint256)>,
+}
+
+/// Checks that the miner account can cover `needed` before a run starts, so that expensive
+/// runs against remote chains abort with a clear message instead of failing midway
+pub async fn check_miner_funding(needed: &FundingEstimate, web3: &Web3) {
+    let mut shortfalls = Vec::new();
+    let eth_balance = web3.eth_get_balance(*MINER_ADDRESS).await.unwrap();
+    if eth_balance < needed.eth {
+        shortfalls.push(format!(
+            "{} ETH but holds {} ETH",
+            print_eth(needed.eth),
+            print_eth(eth_balance)
+        ));
+    }
+    for (erc20, amount) in needed.erc20.iter() {
+        let balance = get_erc20_balance_safe(*erc20, web3, *MINER_ADDRESS)
+            .await
+            .unwrap();
+        if balance < *amount {
+            shortfalls.push(format!("{} {} but holds {}", amount, erc20, balance));
+        }
+    }
+    if !shortfalls.is_empty() {
+        panic!(
+            "Funding account {} can not pay for this run, it needs {}",
+            *MINER_ADDRESS,
+            shortfalls.join(", ")
+        );
+    }
+    info!(
+        "Funding account {} holds the estimated {} ETH needed for this run",
+        *MINER_ADDRESS,
+        print_eth(needed.eth)
+    );
 }
 
 /// This function efficiently distributes ETH to a large number of provided Ethereum addresses
@@ -170,7 +232,7 @@ pub async fn send_eth_bulk(amount: Uint256, destinations: &[EthAddress], web3: &
     for tx in transactions {
         sends.push(web3.eth_send_raw_transaction(tx.to_bytes().unwrap()));
     }
-    let txids = join_all(sends).await;
+    let txids = join_bounded(sends).await;
     wait_for_txids(txids, web3).await;
 }
 
@@ -181,7 +243,7 @@ async fn wait_for_txids(txids: Vec<Result<Uint256, Web3Error>>, web3: &Web3) {
         let wait = web3.wait_for_transaction(txid.unwrap(), TOTAL_TIMEOUT, None);
         wait_for_txid.push(wait);
     }
-    let results = join_all(wait_for_txid).await;
+    let results = join_bounded(wait_for_txid).await;
     for (i, res) in results.into_iter().enumerate() {
         if let Err(e) = res {
             panic!("`wait_for_txids` failed on index {}: {:?}", i, e);
@@ -277,7 +339,8 @@ pub struct ValidatorKeys {
 }
 
 /// This function pays the piper for the strange concurrency model that we use for the tests
-/// we spwan a thread, create a tokio executor and then start the orchestrator within that scope
+/// we spwan a thread, create a tokio executor and then start the orchestrator within that scope.
+/// If COSMOS_GRPC_LATENCY_MS is set the orchestrators reach Cosmos through a latency injecting proxy
 pub async fn start_orchestrators(
     keys: Vec<ValidatorKeys>,
     gravity_address: EthAddress,
@@ -288,6 +351,10 @@ pub async fn start_orchestrators(
     // not running an Orchestrator
     let num_validators = keys.len();
     let mut count = 0;
+    let cosmos_grpc = match grpc_latency_from_env() {
+        Some((latency, jitter)) => start_latency_proxy(&COSMOS_NODE_GRPC, latency, jitter).await,
+        None => COSMOS_NODE_GRPC.clone(),
+    };
 
     #[allow(clippy::explicit_counter_loop)]
     for k in keys {
@@ -298,7 +365,8 @@ pub async fn start_orchestrators(
             k.orch_key.to_address(&ADDRESS_PREFIX).unwrap(),
             get_operator_address(k.validator_key),
         );
-        let mut grpc_client = GravityQueryClient::connect(COSMOS_NODE_GRPC.as_str())
+        let cosmos_grpc = cosmos_grpc.clone();
+        let mut grpc_client = GravityQueryClient::connect(cosmos_grpc.clone())
             .await
             .unwrap();
         let params = get_gravity_params(&mut grpc_client)
@@ -312,7 +380,7 @@ pub async fn start_orchestrators(
                 gravity_utils::web30::client::Web3::new(ETH_NODE.as_str(), OPERATION_TIMEOUT);
 
             let contact = Contact::new(
-                COSMOS_NODE_GRPC.as_str(),
+                cosmos_grpc.as_str(),
                 OPERATION_TIMEOUT,
                 ADDRESS_PREFIX.as_str(),
             )
@@ -43,26 +43,29 @@ fi
 # local variable is unset
 REPLICATED_VARS=""
 if [[ -n "${COSMOS_NODE_GRPC}" ]]; then
-   REPLICATED_VARS=REPLICATED_VARS:"--env COSMOS_NODE_GRPC=${COSMOS_NODE_GRPC} "
+   REPLICATED_VARS="${REPLICATED_VARS}--env COSMOS_NODE_GRPC=${COSMOS_NODE_GRPC} "
 fi
 if [[ -n "${COSMOS_NODE_ABCI}" ]]; then
-   REPLICATED_VARS=REPLICATED_VARS:"--env COSMOS_NODE_ABCI=${COSMOS_NODE_ABCI} "
+   REPLICATED_VARS="${REPLICATED_VARS}--env COSMOS_NODE_ABCI=${COSMOS_NODE_ABCI} "
 fi
 if [[ -n "${ETH_NODE}" ]]; then
-   REPLICATED_VARS=REPLICATED_VARS:"--env ETH_NODE=${ETH_NODE} "
+   REPLICATED_VARS="${REPLICATED_VARS}--env ETH_NODE=${ETH_NODE} "
 fi
 if [[ -n "${MINER_PRIVATE_KEY}" ]]; then
-   REPLICATED_VARS=REPLICATED_VARS:"--env MINER_PRIVATE_KEY=${MINER_PRIVATE_KEY} "
+   REPLICATED_VARS="${REPLICATED_VARS}--env MINER_PRIVATE_KEY=${MINER_PRIVATE_KEY} "
 fi
 if [[ -n "${GRAVITY_ADDRESS}" ]]; then
-   REPLICATED_VARS=REPLICATED_VARS:"--env GRAVITY_ADDRESS=${GRAVITY_ADDRESS} "
+   REPLICATED_VARS="${REPLICATED_VARS}--env GRAVITY_ADDRESS=${GRAVITY_ADDRESS} "
 fi
 # used by manual remote stress testing
 if [[ -n "${NUM_USERS}" ]]; then
-   REPLICATED_VARS=REPLICATED_VARS:"--env NUM_USERS=${NUM_USERS} "
+   REPLICATED_VARS="${REPLICATED_VARS}--env NUM_USERS=${NUM_USERS} "
 fi
 if [[ -n "${WEI_PER_USER}" ]]; then
-   REPLICATED_VARS=REPLICATED_VARS:"--env WEI_PER_USER=${WEI_PER_USER} "
+   REPLICATED_VARS="${REPLICATED_VARS}--env WEI_PER_USER=${WEI_PER_USER} "
+fi
+if [[ -n "${SETUP_CONCURRENCY}" ]]; then
+   REPLICATED_VARS="${REPLICATED_VARS}--env SETUP_CONCURRENCY=${SETUP_CONCURRENCY} "
 fi
 
 RUN_ARGS=""